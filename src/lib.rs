@@ -6,23 +6,12 @@
 
 #![doc = stringify!(include!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md")))]
 
-use input::MacroInput;
 use proc_macro::TokenStream;
-use proc_macro2::Span;
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
-use std::env;
-use std::path::PathBuf;
-use syn::{parse_macro_input, LitStr};
-
-mod comments;
-mod field;
-mod input;
-mod module;
-mod pattern;
-mod utils;
-
-use utils::*;
+use std::{env, path::PathBuf};
+use syn::{parse_macro_input, ItemMod, LitStr};
+use tomlfuse_codegen::{find_workspace_root, is_package_manifest, MacroInput, SourceFormat};
 
 /// Expands to a bound constants from the workspace's `Cargo.toml`.
 ///
@@ -49,10 +38,8 @@ use utils::*;
 /// }
 ///
 /// // access the generated constants
-/// fn main() {
-///     for member in workspace::MEMBERS {
-///         println!("Found workspace member: {}", member);
-///     }
+/// for member in workspace::MEMBERS {
+///     println!("Found workspace member: {}", member);
 /// }
 /// ```
 ///
@@ -63,7 +50,7 @@ pub fn workspace(input: TokenStream) -> TokenStream {
     // find workspace root
     let cargo_path = find_workspace_root().join("Cargo.toml");
 
-    __codegen(input, Some(cargo_path))
+    __codegen(input, Some(cargo_path), SourceFormat::Toml)
 }
 
 /// Expands to a module exposing constants from the current crate's `Cargo.toml`.
@@ -112,8 +99,82 @@ pub fn package(input: TokenStream) -> TokenStream {
     // use manifest dir for crate
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
     let cargo_path = PathBuf::from(manifest_dir).join("Cargo.toml");
+    // a virtual workspace manifest has no package to bind, so point to `workspace!` instead
+    if !is_package_manifest(&cargo_path) {
+        let msg = format!(
+            "`package!` needs a `[package]` table, but {} has none; use `workspace!` to bind a \
+             virtual workspace manifest",
+            cargo_path.display()
+        );
+        return syn::Error::new(Span::call_site(), msg).to_compile_error().into();
+    }
+
+    __codegen(input, Some(cargo_path), SourceFormat::Toml)
+}
+
+/// Expands to a module exposing the resolved versions of the packages in the workspace's
+/// `Cargo.lock`.
+///
+/// Locates the lockfile at the workspace root, like [`workspace!`] does the manifest. Its
+/// `[[package]]` array is keyed by the packages' names, so each locked package is a module
+/// with its `NAME`, `VERSION` and the rest of its keys. A crate locked at several versions is
+/// keyed by `name@version` for each, e.g. `package::syn_2_0_48`.
+///
+/// The same lockfile handling applies to a `Cargo.lock` bound with [`file!`].
+///
+/// # Example
+/// ```
+/// use tomlfuse::lockfile;
+///
+/// lockfile! {
+///     [locked]
+///     package.*
+/// }
+///
+/// println!("Built against toml v{}", locked::toml::VERSION);
+/// ```
+///
+/// See also: [`workspace!`], [`file!`]
+#[proc_macro]
+#[deprecated(since = "0.0.3", note = "This crate is deprecated. Please use the `confuse` crate instead.")]
+pub fn lockfile(input: TokenStream) -> TokenStream {
+    let lock_path = find_workspace_root().join("Cargo.lock");
+
+    __codegen(input, Some(lock_path), SourceFormat::Toml)
+}
 
-    __codegen(input, Some(cargo_path))
+/// Expands to modules exposing the `[package]`s of the workspace's members, one submodule per
+/// member named after it.
+///
+/// Locates the workspace root like [`workspace!`] does, then reads the manifest of each of
+/// its `members`, including the ones matched by globs like `crates/*` and leaving out the
+/// `exclude`d ones. The workspace's own package is a member too, if it has one. Keys a member
+/// inherits from the workspace, like `version.workspace = true`, are bound as what they
+/// inherit. A path to another workspace's manifest can be given first, like in [`file!`].
+///
+/// # Example
+/// ```
+/// use tomlfuse::workspace_members;
+///
+/// workspace_members! {
+///     [members]
+///     *.name
+///     *.version
+/// }
+///
+/// println!("{} v{}", members::tomlfuse_build::NAME, members::tomlfuse_build::VERSION);
+/// ```
+///
+/// See also: [`workspace!`], [`package!`]
+#[proc_macro]
+#[deprecated(since = "0.0.3", note = "This crate is deprecated. Please use the `confuse` crate instead.")]
+pub fn workspace_members(input: TokenStream) -> TokenStream {
+    // the path is optional, and defaults to the workspace of the crate
+    let ts: TokenStream2 = input.clone().into();
+    let has_path = syn::parse2::<LitStr>(ts.into_iter().take(1).collect()).is_ok();
+    let cargo_path = (!has_path).then(|| find_workspace_root().join("Cargo.toml"));
+
+    __codegen(input, cargo_path, SourceFormat::WorkspaceMembers)
 }
 
 /// Expands to bound constants from any toml file.
@@ -156,10 +217,107 @@ pub fn package(input: TokenStream) -> TokenStream {
 #[proc_macro]
 #[deprecated(since = "0.0.3", note = "This crate is deprecated. Please use the `confuse` crate instead.")]
 pub fn file(input: TokenStream) -> TokenStream {
-    __codegen(input, None) // we require the path to be passed in the macro, so we can directly do this
+    __codegen(input, None, SourceFormat::Toml) // we require the path to be passed in the macro, so we can directly do this
+}
+
+/// Expands to modules exposing constants from a json file, like [`file!`] does for toml files.
+///
+/// The json document is converted into the same value tree as toml before binding, so all
+/// the same pattern syntax applies: objects are tables, numbers that fit in an `i64` are
+/// integers (floats otherwise), and `null`s are dropped since toml has no counterpart for them.
+/// There are no comments in json, so no doc comments are generated.
+///
+/// Requires the `json` feature.
+///
+/// # Example
+/// ```
+/// use tomlfuse::json;
+///
+/// json! {
+///     "path/to/config.json"
+///
+///     [server]
+///     server.*
+/// }
+///
+/// println!("Listening on port {}", server::PORT);
+/// ```
+///
+/// See also: [`file!`]
+#[cfg(feature = "json")]
+#[proc_macro]
+#[deprecated(since = "0.0.3", note = "This crate is deprecated. Please use the `confuse` crate instead.")]
+pub fn json(input: TokenStream) -> TokenStream {
+    __codegen(input, None, SourceFormat::Json)
+}
+
+/// Expands to modules exposing constants from a yaml file, like [`file!`] does for toml files.
+///
+/// The yaml document is converted into the same value tree as toml before binding, so all
+/// the same pattern syntax applies: mappings are tables, sequences are arrays, and `null`s are
+/// dropped since toml has no counterpart for them. Anchors, aliases and `<<` merge keys are
+/// resolved before binding. Only single document files are supported.
+/// Yaml comments are not carried over, so no doc comments are generated.
+///
+/// Requires the `yaml` feature.
+///
+/// # Example
+/// ```
+/// use tomlfuse::yaml;
+///
+/// yaml! {
+///     "path/to/config.yaml"
+///
+///     [server]
+///     server.*
+/// }
+///
+/// println!("Listening on port {}", server::PORT);
+/// ```
+///
+/// See also: [`file!`]
+#[cfg(feature = "yaml")]
+#[proc_macro]
+#[deprecated(since = "0.0.3", note = "This crate is deprecated. Please use the `confuse` crate instead.")]
+pub fn yaml(input: TokenStream) -> TokenStream {
+    __codegen(input, None, SourceFormat::Yaml)
+}
+
+/// Fills the annotated module with the modules [`file!`] would expand to, for when the bindings
+/// should live under a module of their own rather than next to the macro call.
+///
+/// Takes the same input as [`file!`], the path first and then the sections. The module keeps
+/// its attributes, visibility and any items it already has; the generated ones are appended
+/// after them. The module needs a body, even an empty one, to put them in.
+///
+/// # Example
+/// ```
+/// #[tomlfuse::attach("tests/test.toml", [app] app.*)]
+/// mod config {}
+///
+/// fn setup() {
+///     println!("Starting {} v{}", config::app::NAME, config::app::VERSION);
+/// }
+/// ```
+///
+/// See also: [`file!`]
+#[proc_macro_attribute]
+#[deprecated(since = "0.0.3", note = "This crate is deprecated. Please use the `confuse` crate instead.")]
+pub fn attach(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut module = parse_macro_input!(item as ItemMod);
+    let Some((_, items)) = module.content.as_mut() else {
+        let msg = "`attach` needs a module with a body to put the bindings in, e.g. `mod x {}`";
+        return syn::Error::new_spanned(&module, msg).to_compile_error().into();
+    };
+    let generated = __codegen(attr, None, SourceFormat::Toml);
+    match syn::parse::<syn::File>(generated) {
+        Ok(file) => items.extend(file.items),
+        Err(err) => return err.to_compile_error().into(),
+    }
+    quote! {#module}.into()
 }
 
-fn __codegen(input: TokenStream, src: Option<PathBuf>) -> TokenStream {
+fn __codegen(input: TokenStream, src: Option<PathBuf>, format: SourceFormat) -> TokenStream {
     let ts: TokenStream = if let Some(path) = src {
         // for better dx, the path can be omitted in macro input, we'll prepend it for convenience here
         // (requires the caller to pass us something in `src` though)
@@ -172,6 +330,7 @@ fn __codegen(input: TokenStream, src: Option<PathBuf>) -> TokenStream {
     } else {
         input
     };
-    let macro_input: MacroInput = parse_macro_input!(ts as MacroInput);
+    let mut macro_input: MacroInput = parse_macro_input!(ts as MacroInput);
+    macro_input.format = format;
     quote! {#macro_input}.into()
 }
@@ -0,0 +1,14 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#[test]
+fn test_compile_fail() {
+    // the fixtures are compiled in the target dir, wherever that is, so the paths they bind are
+    // looked up in the repo through `CARGO_WORKSPACE_DIR`
+    std::env::set_var("CARGO_WORKSPACE_DIR", env!("CARGO_MANIFEST_DIR"));
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,173 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+#![allow(clippy::assertions_on_constants)]
+
+use std::num::{NonZeroI32, NonZeroU16, NonZeroUsize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tomlfuse::file;
+
+file! {
+    "tests/annotations.toml"
+
+    [bytes]
+    bytes.magic as u8-bytes
+
+    [little]
+    protocol.header as le-bytes(u32)
+
+    [big]
+    protocol.header as be-bytes(u32)
+
+    [timeouts]
+    timeouts.connect as secs
+    timeouts.retry_ms as millis
+
+    [tagged]
+    tagged.tags as unique
+
+    [nested]
+    package.metadata.** as nested
+
+    [assets]
+    assets.logo as path-relative-to-config
+
+    [paths]
+    paths.data_dir as path
+
+    [keyword_paths]
+    keyword_paths.* as path
+
+    [owned_paths as_fns]
+    paths.data_dir as path
+
+    [blobs]
+    blobs.key as bytes(base64)
+    blobs.digest as bytes(hex)
+
+    [files]
+    files.{mode,modes} as oct
+    files.mask as hex
+    files.flags as bin
+    files.max_size
+
+    [pool]
+    pool.size as nonzero(usize)
+    pool.port as nonzero(u16)
+    pool.offset as nonzero(i32)
+
+    [exprs]
+    exprs.buffer as expr(usize)
+    exprs.timeout as expr(core::time::Duration)
+
+    [palette]
+    palette.colors as array
+}
+
+#[test]
+fn test_u8_bytes_annotation() {
+    // should break if the type is not properly emitted as a byte slice
+    let magic: &'static [u8] = bytes::MAGIC;
+    assert_eq!(magic, &[0u8, 127, 255]);
+}
+
+#[test]
+fn test_endian_bytes_annotation() {
+    // should break if the type is not properly emitted as a sized byte array
+    let little: &'static [u8; 4] = little::HEADER;
+    let big: &'static [u8; 4] = big::HEADER;
+    assert_eq!(little, &0x12345678u32.to_le_bytes());
+    assert_eq!(big, &[0x12, 0x34, 0x56, 0x78]);
+}
+
+#[test]
+fn test_duration_annotations() {
+    // should break if the type is not properly emitted as a duration
+    const CONNECT: Duration = timeouts::CONNECT;
+    assert_eq!(CONNECT, Duration::from_secs(30));
+    assert_eq!(timeouts::RETRY_MS, Duration::from_millis(250));
+}
+
+#[test]
+fn test_unique_annotation() {
+    assert_eq!(tagged::TAGS, &["fast", "safe", "small"]);
+}
+
+#[test]
+fn test_nested_annotation() {
+    assert!(nested::tool::ENABLED);
+    assert_eq!(nested::tool::profile::release::LEVEL, 3);
+    assert_eq!(nested::tool::profile::dev::LEVEL, 0);
+    // would be flattened into `tool` if segment names shared with the pattern were stripped
+    assert_eq!(nested::tool::metadata::REVISION, 2);
+}
+
+#[test]
+fn test_path_relative_to_config_annotation() {
+    // joined onto the directory of the toml file, not the directory the crate is built from
+    let tests_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let expected = tests_dir.canonicalize().unwrap().join("assets/logo.png");
+    assert!(std::path::Path::new(assets::LOGO).is_absolute());
+    assert_eq!(std::path::Path::new(assets::LOGO), expected);
+}
+
+#[test]
+fn test_path_annotation() {
+    assert_eq!(paths::DATA_DIR, "/var/lib/fixture");
+    // should break if the helper doesn't return the const as a path
+    let data_dir: &'static Path = paths::data_dir();
+    assert_eq!(data_dir, Path::new("/var/lib/fixture"));
+    assert_eq!(data_dir.file_name().unwrap(), "fixture");
+    let owned: PathBuf = owned_paths::data_dir();
+    assert_eq!(owned, data_dir);
+    assert_eq!(keyword_paths::r#type(), Path::new(keyword_paths::TYPE));
+}
+
+#[test]
+fn test_bytes_annotation() {
+    // should break if the type is not properly emitted as a byte slice
+    let key: &'static [u8] = blobs::KEY;
+    assert_eq!(key, &[0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(blobs::DIGEST, blobs::KEY);
+}
+
+#[test]
+fn test_radix_annotations() {
+    // the literals are only written differently, the values are what the toml has
+    assert_eq!(files::MODE, 0o755);
+    assert_eq!(files::MODE, 493);
+    assert_eq!(files::MASK, 0xdead_beef);
+    assert_eq!(files::FLAGS, 10);
+    assert_eq!(files::MODES, &[0o644, 0o600]);
+    assert_eq!(files::MAX_SIZE, 1_000_000);
+}
+
+#[test]
+fn test_nonzero_annotation() {
+    // should break if the types are not properly emitted as the `NonZero*` types
+    const SIZE: NonZeroUsize = pool::SIZE;
+    let port: NonZeroU16 = pool::PORT;
+    let offset: NonZeroI32 = pool::OFFSET;
+    assert_eq!(SIZE.get(), 8);
+    assert_eq!(port.get(), 5432);
+    assert_eq!(offset.get(), -1);
+}
+
+#[test]
+fn test_expr_annotation() {
+    const BUFFER: [u8; exprs::BUFFER] = [0; exprs::BUFFER];
+    assert_eq!(BUFFER.len(), 4096);
+    assert_eq!(exprs::TIMEOUT, std::time::Duration::from_secs(30));
+}
+
+#[test]
+fn test_array_annotation() {
+    // should break if the type is not properly emitted as an array with its length
+    const COLORS: [i64; 3] = palette::COLORS;
+    assert_eq!(COLORS, [255, 128, 0]);
+}
@@ -0,0 +1,85 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+
+use tomlfuse::file;
+
+file! {
+    "tests/arrays.toml"
+
+    // each element of an array of tables becomes a submodule named by its index
+    [targets]
+    bin.*
+
+    [all]
+    *
+
+    [bins]
+    #targets(bin)
+
+    // there are no `[[example]]`s, so this is empty rather than an error
+    [examples]
+    #targets(example)
+
+    [tuples]
+    tuples.*
+
+    // what has a typed constant is bound the same when the rest would be an error
+    [strict_tuples strict_types]
+    tuples.*
+    !tuples.nested
+}
+
+#[test]
+fn test_array_of_tables() {
+    assert_eq!(targets::_0::NAME, "server");
+    assert_eq!(targets::_0::PATH, "src/bin/server.rs");
+    assert_eq!(targets::_1::NAME, "client");
+    assert_eq!(targets::_1::PATH, "src/bin/client.rs");
+    assert_eq!(targets::_1::REQUIRED_FEATURES, &["cli"]);
+}
+
+#[test]
+fn test_array_of_tables_keeps_its_name() {
+    assert_eq!(all::package::NAME, "fixture");
+    assert_eq!(all::bin::_0::NAME, "server");
+    assert_eq!(all::bin::_1::NAME, "client");
+}
+
+#[test]
+fn test_nested_array_of_tables() {
+    assert_eq!(
+        targets::_1::target::_0::TRIPLE,
+        "x86_64-unknown-linux-gnu"
+    );
+}
+
+#[test]
+fn test_targets() {
+    assert_eq!(bins::NAMES, &["server", "client", "tool"]);
+    assert_eq!(
+        bins::PATHS,
+        &["src/bin/server.rs", "src/bin/client.rs", "src/bin/tool.rs"]
+    );
+    assert!(examples::NAMES.is_empty());
+    assert!(examples::PATHS.is_empty());
+}
+
+#[test]
+fn test_mixed_arrays_as_tuples() {
+    assert_eq!(tuples::PAIR, (8080, "http"));
+    assert_eq!(tuples::PAIR.0, 8080);
+    assert_eq!(tuples::POINT, (1.0, 2.0, "label"));
+    // what can't be a tuple falls back to the debug string of the array
+    assert!(tuples::NESTED.starts_with("[String(\"a\"), Array(["));
+}
+
+#[test]
+fn test_strict_types() {
+    assert_eq!(strict_tuples::PAIR, tuples::PAIR);
+    assert_eq!(strict_tuples::POINT, tuples::POINT);
+}
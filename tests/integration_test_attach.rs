@@ -0,0 +1,31 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+#![allow(clippy::assertions_on_constants)]
+
+#[tomlfuse::attach("tests/test.toml", [section] section.* [nested] nested.*)]
+mod generated {}
+
+// items already in the module are kept alongside the generated ones
+#[tomlfuse::attach("tests/test.toml", [config] config.*)]
+pub(crate) mod settings {
+    pub const EXTRA: &str = "extra";
+}
+
+#[test]
+fn test_attached_module_is_populated() {
+    assert_eq!(generated::section::KEY, "value");
+    assert_eq!(generated::section::NUMBER, 42);
+    assert_eq!(generated::section::ARRAY, &["item1", "item2", "item3"]);
+    assert!(generated::nested::inner::VALUE);
+}
+
+#[test]
+fn test_existing_items_are_kept() {
+    assert_eq!(settings::EXTRA, "extra");
+    assert!(!settings::config::DEBUG);
+}
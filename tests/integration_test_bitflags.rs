@@ -0,0 +1,26 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![cfg(feature = "bitflags")]
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+
+use tomlfuse::file;
+
+file! {
+    "tests/flags.toml"
+
+    [features as_bitflags]
+    features.*
+}
+
+#[test]
+fn test_enabled_flags() {
+    assert_eq!(features::ENABLED, features::Flags::RENDERER | features::Flags::CACHE);
+    assert!(!features::ENABLED.contains(features::Flags::TELEMETRY));
+    assert_eq!(features::Flags::all().iter().count(), 3);
+    // the values are still there as they are
+    assert_eq!((features::RENDERER, features::TELEMETRY), (true, false));
+}
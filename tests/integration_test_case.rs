@@ -0,0 +1,36 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+
+use tomlfuse::file;
+
+file! {
+    "tests/case.toml"
+
+    [normalized]
+    *
+
+    [kept keep_case]
+    *
+
+    [struct Kept keep_case]
+    HttpClient.*
+}
+
+#[test]
+fn test_normalized_case() {
+    assert_eq!(normalized::httpclient::MYVALUE, 1);
+    assert_eq!(normalized::httpclient::MAX_RETRIES, 3);
+}
+
+#[test]
+fn test_keep_case() {
+    assert_eq!(kept::HttpClient::MyValue, 1);
+    // still sanitized into a valid ident
+    assert_eq!(kept::HttpClient::max_retries, 3);
+    assert_eq!(Kept::MyValue, 1);
+}
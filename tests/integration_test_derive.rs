@@ -0,0 +1,91 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [struct AppConfig derive(Default, Debug, Clone, PartialEq)]
+    config.**
+
+    [struct Section derive(Default)]
+    section.*
+
+    [struct Settings serde derive(Debug, Clone, PartialEq)]
+    config.**
+    section.array
+    special-chars.with-dash
+}
+
+#[test]
+fn test_default_struct() {
+    let config = AppConfig::default();
+    assert!(!config.debug);
+    assert_eq!(config.settings.timeout, 500);
+    assert_eq!(config.settings.retries, 3);
+    assert_eq!(config.logging.level, "info");
+    assert_eq!(config.logging.format, "json");
+}
+
+#[test]
+fn test_nested_structs() {
+    let settings = AppConfigSettings::default();
+    assert_eq!(settings, AppConfig::default().settings);
+    // the fields are plain data, so they can be changed from the defaults
+    let mut config = AppConfig::default();
+    config.settings.retries = 5;
+    assert_ne!(config, AppConfig::default());
+    assert_eq!(config.clone().settings.retries, 5);
+}
+
+#[test]
+fn test_default_struct_arrays() {
+    let section = Section::default();
+    assert_eq!(section.key, "value");
+    assert_eq!(section.number, 42);
+    assert_eq!(section.array, &["item1", "item2", "item3"]);
+}
+
+#[test]
+fn test_serde_overrides() {
+    let overrides = r#"
+        debug = true
+        array = ["one"]
+        with-dash = "overridden"
+
+        [settings]
+        retries = 5
+    "#;
+    let settings: Settings = toml::from_str(overrides).unwrap();
+    let expected = Settings {
+        debug: true,
+        array: vec!["one".into()].into(),
+        with_dash: "overridden".into(),
+        settings: SettingsSettings {
+            retries: 5,
+            ..SettingsSettings::DEFAULTS
+        },
+        ..Settings::DEFAULTS
+    };
+    assert_eq!(settings, expected);
+    assert_eq!(settings.logging, Settings::DEFAULTS.logging);
+
+    // what isn't overridden keeps the values in the toml
+    assert_eq!(toml::from_str::<Settings>("").unwrap(), Settings::DEFAULTS);
+    assert_eq!(Settings::default(), Settings::DEFAULTS);
+    assert_eq!(Settings::DEFAULTS.with_dash, "dashed");
+    assert_eq!(Settings::DEFAULTS.settings.timeout, 500);
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let serialized = toml::to_string(&Settings::DEFAULTS).unwrap();
+    assert!(serialized.contains(r#"with-dash = "dashed""#), "{}", serialized);
+    assert_eq!(toml::from_str::<Settings>(&serialized).unwrap(), Settings::DEFAULTS);
+}
@@ -0,0 +1,231 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+#![allow(clippy::assertions_on_constants)]
+#![allow(clippy::approx_constant)] // test.toml has a `3.14` float
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [main]
+    #checksum
+    section.*
+
+    [main_again]
+    #checksum
+    section.*
+
+    [config]
+    #checksum
+    config.*
+
+    [struct Settings]
+    #checksum
+    config.*
+
+    [originals]
+    #with-original-names
+    special-chars.*
+
+    [renamed_originals]
+    #with-original-names
+    alias clean_name = special-chars.with-dash
+
+    [documented with_docs]
+    config.settings.*
+
+    [flat flatten_exports]
+    config.*
+
+    // the re-exports chain up, so values at any depth are reachable from the top
+    [deep_flat flatten_exports]
+    deep.*
+
+    [interned intern_strings]
+    hosts.*
+
+    [mixed as_map]
+    mixed-types.*
+
+    [struct Mixed as_map]
+    mixed-types.*
+
+    [lookup lookup_fn]
+    config.settings.*
+
+    [struct Lookup lookup_fn]
+    config.settings.*
+
+    [overridable env_override]
+    config.settings.*
+
+    [struct Overridable env_override]
+    config.settings.*
+
+    [counted count]
+    config.*
+
+    [struct Counted count]
+    config.*
+
+    [attributed]
+    #attr("#[allow(dead_code)]")
+    #attr("#[doc(alias = \"cfg\")]")
+    config.*
+
+    [narrowed narrow_ints]
+    config.settings.*
+    mixed-types.*
+
+    [owned as_fns]
+    section.*
+    alias ints = mixed-types.array
+
+    [struct Owned as_fns]
+    section.*
+
+    [inner_documented inner_docs]
+    **.standalone
+
+    // keys that are identifiers already are kept as they are, case aside
+    [raw raw_idents]
+    special-chars.with_underscore
+    nested.inner.*
+}
+
+#[test]
+fn test_checksum() {
+    // same bound values, same checksum
+    assert_eq!(main::CHECKSUM, main_again::CHECKSUM);
+    assert_ne!(main::CHECKSUM, config::CHECKSUM);
+    assert_eq!(Settings::CHECKSUM, config::CHECKSUM);
+}
+
+#[test]
+fn test_with_original_names() {
+    assert_eq!(originals::WITH_DASH, "dashed");
+    assert_eq!(originals::WITH_DASH_ORIGINAL, "with-dash");
+    assert_eq!(originals::WITH_UNDERSCORE_ORIGINAL, "with_underscore");
+    // aliases still point back to the key in the file
+    assert_eq!(renamed_originals::CLEAN_NAME, "dashed");
+    assert_eq!(renamed_originals::CLEAN_NAME_ORIGINAL, "with-dash");
+}
+
+#[test]
+fn test_env_override_without_env() {
+    // `TOMLFUSE_config_settings_timeout` isn't set for the tests, so the toml values stay
+    assert_eq!(overridable::TIMEOUT, 500);
+    assert_eq!(Overridable::RETRIES, 3);
+}
+
+#[test]
+fn test_with_docs() {
+    assert_eq!(documented::TIMEOUT_DOC, "how long to wait for a response, in milliseconds");
+    // no comment, no text
+    assert_eq!(documented::RETRIES_DOC, "");
+}
+
+#[test]
+fn test_as_map() {
+    let entries = mixed::entries();
+    assert_eq!(entries.len(), 5);
+    assert!(entries.contains(&("string", "text")));
+    assert!(entries.contains(&("number", "42")));
+    assert!(entries.contains(&("array", "[1, 2, 3]")));
+    // iterable without knowing any of the keys
+    let total: usize = entries.iter().map(|(key, value)| key.len() + value.len()).sum();
+    assert!(total > 0);
+    assert_eq!(Mixed::entries(), entries);
+}
+
+#[test]
+fn test_lookup_fn() {
+    assert_eq!(lookup::get("timeout"), Some("500"));
+    assert_eq!(lookup::get("nope"), None);
+    // keys are looked up as in the TOML, not by the names of the consts
+    assert_eq!(lookup::get("TIMEOUT"), None);
+    assert_eq!(Lookup::get("timeout"), lookup::get("timeout"));
+}
+
+#[test]
+fn test_flatten_exports() {
+    assert_eq!(flat::settings::TIMEOUT, 500);
+    assert_eq!(flat::TIMEOUT, flat::settings::TIMEOUT);
+    assert_eq!(flat::LEVEL, "info");
+    assert!(!flat::DEBUG);
+
+    assert!(deep_flat::level1::level2::level3::VALUE);
+    assert_eq!(deep_flat::VALUE, deep_flat::level1::level2::level3::VALUE);
+    assert_eq!(deep_flat::OTHER, deep_flat::level1::level2::OTHER);
+    assert_eq!(deep_flat::PATH, "branch");
+    assert_eq!(deep_flat::level2::OTHER, "sibling");
+}
+
+#[test]
+fn test_intern_strings() {
+    // all three refer to the one pooled const
+    assert_eq!(interned::PRIMARY, "localhost");
+    assert_eq!(interned::FALLBACK, interned::PRIMARY);
+    assert_eq!(interned::replica::HOST, interned::PRIMARY);
+    assert_eq!(interned::NAME, "hosts");
+}
+
+#[test]
+fn test_count() {
+    // `debug` and the two values in each of `settings` and `logging`
+    assert_eq!(counted::__COUNT, 5);
+    assert_eq!(counted::settings::__COUNT, 2);
+    assert_eq!(counted::logging::__COUNT, 2);
+    assert_eq!(Counted::__COUNT, 5);
+    assert_eq!(CountedSettings::__COUNT, 2);
+}
+
+#[test]
+fn test_extra_attrs() {
+    // the attributes go on the items as they are, and don't change what's bound
+    assert!(!attributed::DEBUG);
+    assert_eq!(attributed::settings::TIMEOUT, 500);
+}
+
+#[test]
+fn test_narrow_ints() {
+    let timeout: i16 = narrowed::TIMEOUT;
+    let retries: i8 = narrowed::RETRIES;
+    let array: &[i8] = narrowed::ARRAY;
+    assert_eq!(timeout, 500);
+    assert_eq!(retries, 3);
+    assert_eq!(array, &[1, 2, 3]);
+    // floats stay as they are
+    assert_eq!(narrowed::FLOAT, 3.14);
+}
+
+#[test]
+fn test_as_fns() {
+    let key: String = owned::key();
+    assert_eq!(key, owned::KEY);
+    let array: Vec<String> = owned::array();
+    assert_eq!(array, ["item1", "item2", "item3"]);
+    // already owned values are returned as they are
+    assert_eq!(owned::number(), 42);
+    assert_eq!(owned::ints(), vec![1, 2, 3]);
+    assert_eq!(Owned::key(), "value".to_string());
+}
+
+#[test]
+fn test_inner_docs() {
+    // inner docs have to come first in the module body, so this breaks if they don't
+    assert_eq!(inner_documented::deep::STANDALONE, "top-level");
+}
+
+#[test]
+fn test_raw_idents() {
+    assert_eq!(raw::WITH_UNDERSCORE, "underscore");
+    assert!(raw::VALUE);
+    assert_eq!(raw::STRING, "nested string");
+}
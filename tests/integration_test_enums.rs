@@ -0,0 +1,98 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+
+use std::str::FromStr;
+use tomlfuse::file;
+
+file! {
+    "tests/enums.toml"
+
+    [log]
+    log.levels as enum
+
+    [build]
+    build.profiles as enum(non_exhaustive)
+
+    [struct Log]
+    log.levels as enum
+
+    [current]
+    log.level as enum(Trace, Debug, Info, Warn, Error)
+    log.volume as enum(Quiet, VeryLoud)
+}
+
+#[test]
+fn test_enum_from_array() {
+    assert_eq!(log::LEVELS, log::Levels::ALL);
+    assert_eq!(
+        log::LEVELS,
+        &[
+            log::Levels::Error,
+            log::Levels::Warn,
+            log::Levels::Info,
+            log::Levels::Debug
+        ]
+    );
+    // exhaustive, so no wildcard arm is needed
+    let loud = |level: log::Levels| match level {
+        log::Levels::Error | log::Levels::Warn => false,
+        log::Levels::Info | log::Levels::Debug => true,
+    };
+    assert!(loud(log::Levels::Debug));
+}
+
+#[test]
+fn test_enum_strings() {
+    assert_eq!(log::Levels::Warn.as_str(), "warn");
+    assert_eq!(log::Levels::Info.to_string(), "info");
+    assert_eq!(log::Levels::from_str("debug"), Ok(log::Levels::Debug));
+    assert!(log::Levels::from_str("trace").is_err());
+    assert_eq!(
+        build::Profiles::from_str("size-optimized"),
+        Ok(build::Profiles::SizeOptimized)
+    );
+    assert_eq!(build::Profiles::SizeOptimized.as_str(), "size-optimized");
+}
+
+#[test]
+fn test_non_exhaustive_enum() {
+    // `#[non_exhaustive]` only applies outside the crate invoking the macro, where the wildcard
+    // arm is required; here it's merely unreachable, so this checks that the match still compiles
+    #[allow(unreachable_patterns)]
+    let optimized = |profile: build::Profiles| match profile {
+        build::Profiles::Dev => false,
+        build::Profiles::Release | build::Profiles::SizeOptimized => true,
+        _ => false,
+    };
+    assert!(optimized(build::Profiles::Release));
+    assert!(build::PROFILES.iter().copied().any(optimized));
+}
+
+#[test]
+fn test_struct_enum() {
+    // prefixed with the struct name, like substructs are
+    assert_eq!(Log::LEVELS, LogLevels::ALL);
+    assert_eq!(LogLevels::Error.as_str(), "error");
+}
+
+#[test]
+fn test_enum_from_string() {
+    // should break if the type is not properly emitted as the enum
+    const LEVEL: current::Level = current::LEVEL;
+    assert_eq!(LEVEL, current::Level::Info);
+    assert_eq!(current::Level::ALL.len(), 5);
+    // the listed variants are the strings, matched in any case like the value is
+    assert_eq!(current::Level::Warn.as_str(), "Warn");
+    assert_eq!(current::Level::from_str("trace"), Ok(current::Level::Trace));
+    assert!(current::Level::from_str("verbose").is_err());
+    // and in the PascalCase form the value was matched in
+    assert_eq!(current::VOLUME, current::Volume::VeryLoud);
+    assert_eq!(current::Volume::from_str("very-loud"), Ok(current::Volume::VeryLoud));
+    assert_eq!(current::Volume::from_str("VERY_LOUD"), Ok(current::Volume::VeryLoud));
+    assert!(current::Volume::from_str("very-quiet").is_err());
+}
@@ -4,13 +4,18 @@
 // SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
 //------------------------------------------------------------------------------
 
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+// the generated items are constants by design, so asserting on them is the whole point
+#![allow(clippy::assertions_on_constants, clippy::bool_assert_comparison)]
+#![allow(clippy::approx_constant)] // test.toml has a `3.14` float
+
 use tomlfuse::file;
 
 // generate constants from test.toml
 file! {
     "tests/test.toml"
 
-    [main]
+    [main array_lens]
     section.*
 
     // test hierarchies with glob patterns
@@ -18,11 +23,32 @@ file! {
     config.*          // should keep settings.timeout as settings::TIMEOUT
     nested.inner.*    // should flatten these as VALUE and STRING
 
+    // the same, with the consumed `config` and `nested.inner` kept in three ways
+    [flat_full flatten(full)]
+    config.*
+    nested.inner.*
+
+    [flat_joined flatten(joined)]
+    config.*
+    nested.inner.*
+
+    [flat_sep flatten_sep("__")]
+    config.*
+    nested.inner.*
+
+    [flat_hierarchy flatten(hierarchy)]
+    config.*
+    nested.inner.*
+
     // test deep hierarchies
     [deep_stuff]
     deep.*
     !deep.level1.alternative.*  // exclude a branch
 
+    // the same, with the tables holding a single value folded into their parents
+    [deep_collapsed collapse_singletons]
+    deep.*
+
     // test mixed sources in one module
     [mixed]
     deep.standalone
@@ -44,6 +70,15 @@ file! {
     alias short_path = deep.level1.level2.level3.value
     alias clean_name = special-chars.with-dash
 
+    // several aliases of one key each get a const of their own
+    [fanned]
+    alias first_name = section.number
+    alias second_name = section.number
+
+    // floats at the extremes keep their exact values
+    [floats]
+    floats.*
+
     // original test case
     [original]
     config.*
@@ -53,6 +88,14 @@ file! {
     *
 }
 
+// the variable isn't set in tests, so this reads the default
+file! {
+    env = "TOMLFUSE_TEST_UNSET_CONFIG" default = "tests/test.toml"
+
+    [from_env]
+    section.*
+}
+
 #[test]
 fn test_generated_file_constants() {
     // verify generated constants match test data
@@ -63,6 +106,10 @@ fn test_generated_file_constants() {
     assert_eq!(main::ARRAY[0], "item1");
     assert_eq!(main::ARRAY[1], "item2");
     assert_eq!(main::ARRAY[2], "item3");
+    // `array_lens` makes the length usable in const contexts
+    const BUF: [u8; main::ARRAY_LEN] = [0; main::ARRAY_LEN];
+    assert_eq!(main::ARRAY_LEN, 3);
+    assert_eq!(BUF.len(), main::ARRAY.len());
 
     // config section with hierarchy preserved
     assert!(!config_vals::DEBUG); // should break if the type is not properly parsed as bool
@@ -95,6 +142,7 @@ fn test_generated_file_constants() {
     assert_eq!(mixed::WITH_DASH, "dashed");
     assert_eq!(mixed::WITH_UNDERSCORE, "underscore");
     assert_eq!(mixed::quoted::KEY, "quoted");
+    assert_eq!(mixed::WITH_DOT, "dotted");
 
     // direct paths
     assert!(direct::VALUE);
@@ -116,3 +164,53 @@ fn test_generated_file_constants() {
     assert!(!original::DEBUG);
     assert_eq!(original::settings::TIMEOUT, 500);
 }
+
+#[test]
+fn test_aliases_of_the_same_key() {
+    assert_eq!(fanned::FIRST_NAME, 42);
+    assert_eq!(fanned::SECOND_NAME, 42);
+}
+
+#[test]
+fn test_float_extremes() {
+    assert_eq!(floats::HUGE, 1e300);
+    assert_eq!(floats::NEGATIVE_ZERO, 0.0);
+    assert!(floats::NEGATIVE_ZERO.is_sign_negative());
+    assert_eq!(floats::SMALLEST.to_bits(), 1);
+}
+
+#[test]
+fn test_flatten_modes() {
+    assert!(!flat_full::DEBUG);
+    assert_eq!(flat_full::settings::TIMEOUT, 500);
+    assert!(flat_full::VALUE);
+
+    assert!(!flat_joined::CONFIG_DEBUG);
+    assert_eq!(flat_joined::config_settings::TIMEOUT, 500);
+    assert!(flat_joined::NESTED_INNER_VALUE);
+    assert_eq!(flat_joined::NESTED_INNER_STRING, "nested string");
+
+    assert!(!flat_sep::CONFIG__DEBUG);
+    assert_eq!(flat_sep::config__settings::RETRIES, 3);
+    assert!(flat_sep::NESTED__INNER__VALUE);
+
+    assert!(!flat_hierarchy::config::DEBUG);
+    assert_eq!(flat_hierarchy::config::settings::TIMEOUT, 500);
+    assert!(flat_hierarchy::nested::inner::VALUE);
+    assert_eq!(flat_hierarchy::nested::inner::STRING, "nested string");
+}
+
+#[test]
+fn test_collapse_singletons() {
+    assert_eq!(deep_collapsed::level1::ALTERNATIVE_PATH, "branch");
+    assert!(deep_collapsed::level1::level2::LEVEL3_VALUE);
+    // `level2` holds two, so it stays a module
+    assert_eq!(deep_collapsed::level1::level2::OTHER, "sibling");
+    assert_eq!(deep_collapsed::STANDALONE, "top-level");
+}
+
+#[test]
+fn test_env_path_default() {
+    assert_eq!(from_env::KEY, main::KEY);
+    assert_eq!(from_env::ARRAY, &["item1", "item2", "item3"]);
+}
@@ -0,0 +1,43 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+#![allow(clippy::assertions_on_constants)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/groups.toml"
+
+    [server]
+    server.**
+
+    [struct Server]
+    server.**
+}
+
+#[test]
+fn test_group_markers() {
+    // ungrouped keys stay where they are
+    assert_eq!(server::NAME, "flat");
+    // flat keys sharing a marker end up in the same submodule
+    assert_eq!(server::network::HOST, "0.0.0.0");
+    assert_eq!(server::network::PORT, 8080);
+    assert_eq!(server::runtime::THREADS, 4);
+}
+
+#[test]
+fn test_grouped_table() {
+    // a grouped table brings its contents along
+    assert!(server::network::tls::ENABLED);
+}
+
+#[test]
+fn test_group_markers_in_structs() {
+    assert_eq!(ServerNetwork::HOST, "0.0.0.0");
+    assert_eq!(Server::RUNTIME, ServerRuntime);
+    assert!(ServerNetworkTls::ENABLED);
+}
@@ -0,0 +1,39 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+
+use tomlfuse::file;
+
+file! {
+    "tests/inline.toml"
+
+    [point]
+    point.*
+
+    [shapes]
+    shapes.*
+
+    [origin]
+    shapes.origin.*
+}
+
+#[test]
+fn test_top_level_inline_table() {
+    assert_eq!(point::X, 1);
+    assert_eq!(point::Y, 2);
+}
+
+#[test]
+fn test_inline_tables_in_section() {
+    // inline tables become modules like regular subtables do
+    assert_eq!(shapes::origin::X, 0);
+    // a `#` in a string isn't mistaken for a comment
+    assert_eq!(shapes::origin::LABEL, "#0");
+    assert_eq!(shapes::circle::RADIUS, 3);
+    assert_eq!(shapes::circle::center::X, 5);
+    assert_eq!(origin::Y, 0);
+}
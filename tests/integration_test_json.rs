@@ -0,0 +1,47 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![cfg(feature = "json")]
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+#![allow(clippy::assertions_on_constants)]
+
+use tomlfuse::json;
+
+json! {
+    "tests/config.json"
+
+    [server]
+    server.*
+
+    [limits]
+    limits.*
+    !limits.secret
+
+    [all]
+    features
+    workers.*
+}
+
+#[test]
+fn test_json_nested_objects() {
+    assert_eq!(server::HOST, "localhost");
+    assert_eq!(server::PORT, 8080);
+    assert!(server::tls::ENABLED);
+}
+
+#[test]
+fn test_json_patterns() {
+    assert_eq!(limits::RATIO, 0.75);
+    assert_eq!(limits::MAX_CONNECTIONS, 100);
+}
+
+#[test]
+fn test_json_arrays() {
+    assert_eq!(all::FEATURES, &["auth", "metrics"]);
+    // arrays of objects work like arrays of tables
+    assert_eq!(all::_0::NAME, "fetch");
+    assert_eq!(all::_1::THREADS, 4);
+}
@@ -0,0 +1,39 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+
+use tomlfuse::{file, lockfile};
+
+// the workspace's own lockfile, which has this crate in it
+lockfile! {
+    [locked]
+    package.tomlfuse.*
+}
+
+// a fixture lockfile, bound like any toml file since it's recognized by its name
+file! {
+    "tests/lockfile/Cargo.lock"
+
+    [fixture]
+    package.*
+}
+
+#[test]
+fn test_lockfile() {
+    assert_eq!(locked::NAME, "tomlfuse");
+    assert_eq!(locked::VERSION, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn test_fixture_lockfile() {
+    assert_eq!(fixture::serde::VERSION, "1.0.197");
+    assert_eq!(fixture::proc_macro2::NAME, "proc-macro2");
+    assert_eq!(fixture::demo::DEPENDENCIES, &["serde", "syn 1.0.109", "syn 2.0.48"]);
+    // a crate locked at several versions has both of them
+    assert_eq!(fixture::syn_1_0_109::VERSION, "1.0.109");
+    assert_eq!(fixture::syn_2_0_48::VERSION, "2.0.48");
+}
@@ -0,0 +1,31 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+#![allow(clippy::assertions_on_constants, clippy::bool_assert_comparison)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/post.md"
+
+    [post]
+    title
+    date
+    draft
+
+    [extra]
+    extra.*
+}
+
+#[test]
+fn test_front_matter() {
+    assert_eq!(post::TITLE, "Binding front-matter");
+    assert_eq!(post::DATE, "2025-01-31");
+    assert_eq!(post::DRAFT, false);
+    assert!(extra::TOC);
+    assert_eq!(extra::READING_MINUTES, 4);
+}
@@ -0,0 +1,46 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+
+use tomlfuse::file;
+
+file! {
+    "tests/base.toml", "tests/override.toml"
+
+    [cfg]
+    app.*
+
+    [appended]
+    #merge-arrays(append)
+    app.features
+
+    [united merge_arrays(union)]
+    app.features
+}
+
+#[test]
+fn test_merged_tables() {
+    // only in the base file
+    assert_eq!(cfg::NAME, "layered");
+    assert_eq!(cfg::server::HOST, "localhost");
+    // changed by the override
+    assert_eq!(cfg::server::PORT, 9090);
+    // added by the override
+    assert_eq!(cfg::server::WORKERS, 4);
+}
+
+#[test]
+fn test_merged_arrays_are_replaced() {
+    assert_eq!(cfg::FEATURES, &["auth"]);
+}
+
+#[test]
+fn test_merged_arrays_are_appended() {
+    assert_eq!(appended::FEATURES, &["auth", "metrics", "auth"]);
+    // like appending, but without the elements that were there already
+    assert_eq!(united::FEATURES, &["auth", "metrics"]);
+}
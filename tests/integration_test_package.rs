@@ -4,8 +4,12 @@
 // SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
 //------------------------------------------------------------------------------
 
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+// the generated items are constants by design, so asserting on them is the whole point
+#![allow(clippy::assertions_on_constants)]
+
 use std::path::PathBuf;
-use tomlfuse::package;
+use tomlfuse::{file, package};
 // creates compile-time constants from the closest Cargo.toml file
 package! {
     [package]
@@ -25,6 +29,47 @@ package! {
 
     [all]
     *
+
+    // keys that may be missing, with what to bind then
+    [fallbacks]
+    package.rust_version ?= "1.70"
+    package.msrv_policy ?= "latest"
+
+    // single elements of arrays, from the start or the end
+    [first]
+    package.authors[0]
+    package.keywords[-1]
+
+    // the version of each dependency given as a table, named after the dependency
+    [dep_versions name_by_wildcard]
+    dependencies.*.version
+
+    // this crate only has implicit targets
+    [bins]
+    #targets(bin)
+}
+
+// platform specific dependencies are in `[target.'cfg(..)'.dependencies]` tables, whose keys
+// are sanitized into readable idents
+file! {
+    "tests/target_deps/Cargo.toml"
+
+    [target_deps]
+    target.**.dependencies.*
+
+    // value predicates leave out the dependencies that have a matching key
+    [required count]
+    dependencies.*
+    !*.optional == true
+
+    [released count]
+    dependencies.*
+    !dependencies.*.branch == "master"
+
+    // the `*` of a templated alias stands in for the key of each match
+    [prefixed]
+    alias d_* = dependencies.*
+    !dependencies.regex
 }
 
 #[test]
@@ -70,3 +115,58 @@ fn test_generated_constants() {
     println!("Metadata test1: {} (should be \"bar\")", metadata::FOO);
     println!("Metadata test2: {} (should be 1)", defaults::VALUE);
 }
+
+#[test]
+fn test_defaults() {
+    assert_eq!(fallbacks::RUST_VERSION, package::RUST_VERSION);
+    assert_eq!(fallbacks::MSRV_POLICY, "latest");
+}
+
+#[test]
+fn test_array_index() {
+    assert_eq!(first::AUTHORS_0, package::AUTHORS[0]);
+    assert_eq!(first::KEYWORDS_3, package::KEYWORDS[package::KEYWORDS.len() - 1]);
+}
+
+#[test]
+fn test_collected_versions() {
+    assert_eq!(dep_versions::TOMLFUSE_CODEGEN, "0.0.3");
+    assert_eq!(dep_versions::SYN, "^2.0");
+    assert_eq!(dep_versions::PROC_MACRO2, "^1.0");
+}
+
+#[test]
+fn test_implicit_targets() {
+    assert!(bins::NAMES.is_empty());
+    assert!(bins::PATHS.is_empty());
+}
+
+#[test]
+fn test_target_dependencies() {
+    assert_eq!(target_deps::cfg_unix::dependencies::LIBC, "0.2");
+    assert_eq!(target_deps::cfg_target_os_linux::dependencies::inotify::VERSION, "0.11");
+    assert!(!target_deps::cfg_target_os_linux::dependencies::inotify::DEFAULT_FEATURES);
+    assert_eq!(target_deps::x86_64_pc_windows_gnu::dependencies::WINAPI, "0.3");
+}
+
+#[test]
+fn test_templated_alias() {
+    assert_eq!(prefixed::D_SERDE, "1.0");
+    // tables are renamed with what's in them kept as is
+    assert_eq!(prefixed::d_tokio::VERSION, "1.0");
+    assert_eq!(prefixed::d_rand::VERSION, "0.8");
+    assert!(!prefixed::d_rand::DEFAULT_FEATURES);
+}
+
+#[test]
+fn test_exclude_by_value() {
+    assert_eq!(required::SERDE, "1.0");
+    assert_eq!(required::rand::VERSION, "0.8");
+    assert_eq!(required::regex::BRANCH, "master");
+    // serde, rand's two and regex's two, but not tokio's
+    assert_eq!(required::__COUNT, 5);
+
+    assert_eq!(released::tokio::VERSION, "1.0");
+    assert!(released::tokio::OPTIONAL);
+    assert_eq!(released::__COUNT, 5);
+}
@@ -0,0 +1,297 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+// the generated items are constants by design, so asserting on them is the whole point
+#![allow(clippy::assertions_on_constants)]
+#![allow(clippy::approx_constant)] // test.toml has a `3.14` float
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    // brace groups expand to one pattern per alternative
+    [braced]
+    section.{key, number}
+
+    // ...and compose with negation
+    [braced_negated]
+    mixed-types.*
+    !mixed-types.{string, number}
+
+    // double star matches descendants at any depth
+    [recursive]
+    deep.**
+
+    // negating a table leaves out everything below it, the table's module included
+    [excluded_table]
+    deep.**
+    !deep.level1.level2
+
+    // a depth constraint keeps just the paths that many segments below the prefix
+    [exact_depth]
+    deep.** depth == 3
+
+    // question mark matches exactly one character
+    [single_char]
+    versions.v?
+
+    // matching nothing is an error unless explicitly allowed
+    [optional]
+    section.key
+    allow_empty not-there.*
+
+    // a section that matches nothing at all isn't generated, see `nothing_matched` below
+    [nothing_matched]
+    allow_empty not-there.*
+    allow_empty empty-tables.**
+
+    // aliasing a subtree re-roots it, and exclusions apply within it
+    [aliased_subtree]
+    alias cfg = config.* !config.debug
+
+    // aliasing a table renames it, keeping everything below it as is
+    [aliased_table]
+    deep.standalone
+    alias net = deep.level1.level2
+
+    // a leading double star matches the key at any depth, keeping where each match was
+    [anywhere]
+    **.key
+
+    // a section can be reopened to add to it...
+    [reopened]
+    section.key
+
+    [in_between]
+    versions.v1
+
+    // ...and its patterns are merged into the one module
+    [reopened]
+    section.number
+    alias items = section.array
+
+    // required keys don't need to be bound, just to be there
+    [required]
+    require section.key
+    require config.settings.timeout
+    section.number
+
+    // overlapping patterns place a key below the longest one it falls under
+    [overlapping]
+    config.*
+    config.settings.*
+}
+
+file! {
+    "tests/test.toml"
+    ignore_file = "tests/test.tomlfuseignore"
+
+    // the ignore file's patterns apply on top of each section's own
+    [ignored]
+    section.*
+    config.*
+}
+
+// NOTE: these sentinels collide with the generated consts if the patterns leak extra keys,
+//       so a glob import of both sides is ambiguous and this fails to compile
+mod sentinels {
+    pub const ARRAY: () = ();
+    pub const STRING: () = ();
+    pub const NUMBER: () = ();
+    pub const V10: () = ();
+    pub const DEBUG: () = ();
+    pub const STANDALONE: () = ();
+    pub mod level3 {
+        pub const SENTINEL: () = ();
+    }
+    pub mod level2 {
+        pub const SENTINEL: () = ();
+    }
+    pub mod settings {
+        pub const SENTINEL: () = ();
+    }
+}
+
+// only compiles when the section of the same name generated no module
+mod nothing_matched {
+    pub const OWN: &str = "own";
+}
+
+mod braced_probe {
+    #[allow(unused_imports)] // only imported to provoke the ambiguity
+    use super::braced::*;
+    use super::sentinels::*;
+
+    pub const LEAKED_ARRAY: () = ARRAY;
+}
+
+mod braced_negated_probe {
+    #[allow(unused_imports)] // only imported to provoke the ambiguity
+    use super::braced_negated::*;
+    use super::sentinels::*;
+
+    pub const LEAKED_STRING: () = STRING;
+    pub const LEAKED_NUMBER: () = NUMBER;
+}
+
+mod ignored_probe {
+    #[allow(unused_imports)] // only imported to provoke the ambiguity
+    use super::ignored::*;
+    use super::sentinels::*;
+
+    pub const LEAKED_ARRAY: () = ARRAY;
+    pub const LEAKED_SETTINGS: () = settings::SENTINEL;
+}
+
+mod single_char_probe {
+    #[allow(unused_imports)] // only imported to provoke the ambiguity
+    use super::single_char::*;
+    use super::sentinels::*;
+
+    pub const LEAKED_V10: () = V10;
+}
+
+mod aliased_subtree_probe {
+    #[allow(unused_imports)] // only imported to provoke the ambiguity
+    use super::aliased_subtree::cfg::*;
+    use super::sentinels::*;
+
+    pub const LEAKED_DEBUG: () = DEBUG;
+}
+
+mod excluded_table_probe {
+    #[allow(unused_imports)] // only imported to provoke the ambiguity
+    use super::excluded_table::level1::*;
+    use super::sentinels::*;
+
+    pub const LEAKED_LEVEL2: () = level2::SENTINEL;
+}
+
+mod exact_depth_probe {
+    #[allow(unused_imports)] // only imported to provoke the ambiguity
+    use super::exact_depth::*;
+    #[allow(unused_imports)] // only imported to provoke the ambiguity
+    use super::exact_depth::level1::level2::*;
+    use super::sentinels::*;
+
+    pub const LEAKED_STANDALONE: () = STANDALONE;
+    pub const LEAKED_LEVEL3: () = level3::SENTINEL;
+}
+
+#[test]
+fn test_braces() {
+    assert_eq!(braced::KEY, "value");
+    assert_eq!(braced::NUMBER, 42);
+    // only reachable when `section.array` was not included
+    let _: () = braced_probe::LEAKED_ARRAY;
+}
+
+#[test]
+fn test_negated_braces() {
+    assert_eq!(braced_negated::FLOAT, 3.14);
+    assert!(braced_negated::BOOL);
+    assert_eq!(braced_negated::ARRAY, &[1, 2, 3]);
+    // only reachable when `mixed-types.string` and `mixed-types.number` were excluded
+    let _: () = braced_negated_probe::LEAKED_STRING;
+    let _: () = braced_negated_probe::LEAKED_NUMBER;
+}
+
+#[test]
+fn test_double_star() {
+    assert!(recursive::level1::level2::level3::VALUE);
+    assert_eq!(recursive::level1::level2::OTHER, "sibling");
+    assert_eq!(recursive::level1::alternative::PATH, "branch");
+    assert_eq!(recursive::STANDALONE, "top-level");
+}
+
+#[test]
+fn test_excluded_table() {
+    assert_eq!(excluded_table::level1::alternative::PATH, "branch");
+    assert_eq!(excluded_table::STANDALONE, "top-level");
+    // only reachable when `deep.level1.level2` generated no module
+    let _: () = excluded_table_probe::LEAKED_LEVEL2;
+}
+
+#[test]
+fn test_exact_depth() {
+    assert_eq!(exact_depth::level1::level2::OTHER, "sibling");
+    assert_eq!(exact_depth::level1::alternative::PATH, "branch");
+    // only reachable when `deep.standalone` and `deep.level1.level2.level3.value` weren't matched
+    let _: () = exact_depth_probe::LEAKED_STANDALONE;
+    let _: () = exact_depth_probe::LEAKED_LEVEL3;
+}
+
+#[test]
+fn test_ignore_file() {
+    assert_eq!(ignored::KEY, "value");
+    assert_eq!(ignored::NUMBER, 42);
+    assert_eq!(ignored::logging::LEVEL, "info");
+    // only reachable when `section.array` and `config.settings` were left out
+    let _: () = ignored_probe::LEAKED_ARRAY;
+    let _: () = ignored_probe::LEAKED_SETTINGS;
+}
+
+#[test]
+fn test_question_mark() {
+    assert_eq!(single_char::V1, "one");
+    assert_eq!(single_char::V2, "two");
+    // only reachable when `versions.v10` was not matched
+    let _: () = single_char_probe::LEAKED_V10;
+}
+
+#[test]
+fn test_required_keys() {
+    assert_eq!(required::NUMBER, 42);
+}
+
+#[test]
+fn test_allow_empty() {
+    assert_eq!(optional::KEY, "value");
+    assert_eq!(nothing_matched::OWN, "own");
+}
+
+#[test]
+fn test_subtree_alias_with_exclusion() {
+    assert_eq!(aliased_subtree::cfg::settings::TIMEOUT, 500);
+    assert_eq!(aliased_subtree::cfg::settings::RETRIES, 3);
+    assert_eq!(aliased_subtree::cfg::logging::LEVEL, "info");
+    // only reachable when `config.debug` was excluded
+    let _: () = aliased_subtree_probe::LEAKED_DEBUG;
+}
+
+#[test]
+fn test_table_alias() {
+    assert_eq!(aliased_table::net::OTHER, "sibling");
+    assert!(aliased_table::net::level3::VALUE);
+    assert_eq!(aliased_table::STANDALONE, "top-level");
+}
+
+#[test]
+fn test_suffix_anywhere() {
+    assert_eq!(anywhere::section::KEY, "value");
+    assert_eq!(anywhere::duplicates::KEY, "top");
+    assert_eq!(anywhere::duplicates::nested::KEY, "middle");
+    assert_eq!(anywhere::duplicates::nested::deeper::KEY, "bottom");
+    assert_eq!(anywhere::special_chars::quoted::KEY, "quoted");
+}
+
+#[test]
+fn test_reopened_section() {
+    assert_eq!(reopened::KEY, "value");
+    assert_eq!(reopened::NUMBER, 42);
+    assert_eq!(reopened::ITEMS, &["item1", "item2", "item3"]);
+}
+
+#[test]
+fn test_overlapping_patterns() {
+    assert!(!overlapping::DEBUG);
+    assert_eq!(overlapping::TIMEOUT, 500);
+    assert_eq!(overlapping::RETRIES, 3);
+    assert_eq!(overlapping::logging::LEVEL, "info");
+    assert_eq!(overlapping::logging::FORMAT, "json");
+}
@@ -0,0 +1,33 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![cfg(feature = "phf")]
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+
+use tomlfuse::file;
+
+file! {
+    "tests/phf.toml"
+
+    [mime as_phf]
+    mime.*
+
+    [ports as_phf]
+    ports.*
+}
+
+#[test]
+fn test_phf_lookup() {
+    assert_eq!(mime::MAP.len(), 7);
+    assert_eq!(mime::MAP.get("html"), Some(&"text/html"));
+    assert_eq!(mime::MAP.get("svg"), Some(&"image/svg+xml"));
+    assert_eq!(mime::MAP.get("wasm"), Some(&mime::WASM));
+    assert_eq!(mime::MAP.get("exe"), None);
+
+    assert_eq!(ports::MAP.get("https"), Some(&443));
+    assert_eq!(ports::MAP.get("ssh"), Some(&22));
+    assert_eq!(ports::MAP.get("HTTP"), None);
+}
@@ -0,0 +1,54 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+
+use tomlfuse::file;
+
+file! {
+    "tests/os.toml"
+
+    [app]
+    app.*
+    select_os app.cache from app.cache_by_os
+
+    [paths]
+    select-os paths.cache from paths.cache
+
+    [struct App]
+    app.*
+    select_os app.cache from app.cache_by_os
+}
+
+#[test]
+fn test_select_os_with_default() {
+    let expected = if cfg!(target_os = "linux") {
+        "~/.cache/app"
+    } else if cfg!(target_os = "macos") {
+        "~/Library/Caches/app"
+    } else if cfg!(target_os = "windows") {
+        "%LOCALAPPDATA%\\app"
+    } else {
+        "/tmp/app"
+    };
+    assert_eq!(app::CACHE, expected);
+    assert_eq!(App::CACHE, expected);
+    // the rest of the table is bound as usual
+    assert_eq!(app::NAME, "app");
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+fn test_select_os_in_place() {
+    let expected = if cfg!(target_os = "linux") {
+        1
+    } else if cfg!(target_os = "macos") {
+        2
+    } else {
+        3
+    };
+    assert_eq!(paths::CACHE, expected);
+}
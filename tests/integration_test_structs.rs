@@ -0,0 +1,47 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+#![allow(clippy::assertions_on_constants)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [struct Config]
+    config.*
+
+    [struct Deep]
+    deep.**
+}
+
+// the whole point of a struct over a module is that it can be passed around
+fn timeout_of(_config: Config) -> i64 {
+    ConfigSettings::TIMEOUT
+}
+
+#[test]
+fn test_struct_consts() {
+    let config = Config;
+    assert!(!Config::DEBUG);
+    assert_eq!(timeout_of(config), 500);
+    assert_eq!(ConfigSettings::RETRIES, 3);
+    assert_eq!(ConfigLogging::LEVEL, "info");
+    assert_eq!(ConfigLogging::FORMAT, "json");
+}
+
+#[test]
+fn test_nested_structs() {
+    // subtables are reachable as associated consts of the parent struct too
+    let settings: ConfigSettings = Config::SETTINGS;
+    assert_eq!(settings, ConfigSettings);
+    assert_eq!(Deep::STANDALONE, "top-level");
+    assert!(DeepLevel1Level2Level3::VALUE);
+    assert_eq!(DeepLevel1Level2::OTHER, "sibling");
+    let _: DeepLevel1Level2Level3 = DeepLevel1Level2::LEVEL3;
+    assert_eq!(DeepLevel1Alternative::PATH, "branch");
+}
@@ -0,0 +1,68 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+#![allow(clippy::assertions_on_constants)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [internal pub(crate) checksum]
+    config.**
+
+    [private priv]
+    section.*
+
+    [struct Internal pub(crate)]
+    config.**
+}
+
+mod outer {
+    use tomlfuse::file;
+
+    file! {
+        "tests/test.toml"
+
+        [narrow pub(super) flatten_exports]
+        deep.**
+    }
+}
+
+#[test]
+fn test_crate_visible_section() {
+    assert!(!internal::DEBUG);
+    assert_eq!(internal::settings::TIMEOUT, 500);
+    assert_ne!(internal::CHECKSUM, 0);
+}
+
+#[test]
+fn test_private_section() {
+    assert_eq!(private::KEY, "value");
+    assert_eq!(private::NUMBER, 42);
+}
+
+#[test]
+fn test_super_visible_section() {
+    // flattened items follow the section's visibility too
+    assert_eq!(outer::narrow::STANDALONE, "top-level");
+    assert_eq!(outer::narrow::OTHER, "sibling");
+}
+
+#[test]
+fn test_crate_visible_struct() {
+    assert_eq!(InternalSettings::RETRIES, 3);
+    assert_eq!(Internal::SETTINGS, InternalSettings);
+}
+
+mod inner {
+    #[test]
+    fn test_reachable_from_submodules() {
+        assert_eq!(crate::internal::logging::LEVEL, "info");
+        assert_eq!(super::private::KEY, "value");
+    }
+}
@@ -0,0 +1,24 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+
+use tomlfuse::workspace_members;
+
+// this workspace has the root package, `tomlfuse-codegen` and `tomlfuse-build` as its members
+workspace_members! {
+    [members]
+    *.name
+    *.version
+}
+
+#[test]
+fn test_own_workspace_members() {
+    assert_eq!(members::tomlfuse::NAME, "tomlfuse");
+    assert_eq!(members::tomlfuse::VERSION, env!("CARGO_PKG_VERSION"));
+    assert_eq!(members::tomlfuse_codegen::NAME, "tomlfuse-codegen");
+    assert_eq!(members::tomlfuse_build::NAME, "tomlfuse-build");
+}
@@ -0,0 +1,51 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+#![cfg(feature = "yaml")]
+#![allow(deprecated)] // the crate itself is deprecated, but we still want to test it
+#![allow(clippy::assertions_on_constants)]
+
+use tomlfuse::yaml;
+
+yaml! {
+    "tests/config.yaml"
+
+    [server]
+    server.*
+
+    [client]
+    client.*
+
+    [backup]
+    backup.*
+
+    [all]
+    features
+    workers.*
+}
+
+#[test]
+fn test_yaml_nested_maps() {
+    assert_eq!(server::HOST, "localhost");
+    assert_eq!(server::PORT, 8080);
+    assert!(server::tls::ENABLED);
+}
+
+#[test]
+fn test_yaml_anchors() {
+    assert_eq!(client::RETRIES, 3);
+    // set next to the merge key, so it wins over the anchored value
+    assert_eq!(client::TIMEOUT, 30);
+    assert_eq!(backup::TIMEOUT, 10);
+}
+
+#[test]
+fn test_yaml_sequences() {
+    assert_eq!(all::FEATURES, &["auth", "metrics"]);
+    // sequences of mappings work like arrays of tables
+    assert_eq!(all::_0::NAME, "fetch");
+    assert_eq!(all::_1::THREADS, 4);
+}
@@ -0,0 +1,13 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/ui/generated.toml"
+
+    // `keep_case` leaves the const as lowercase as the fn
+    [section as_fns keep_case]
+    section.other
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#![allow(deprecated)]
+
+// there's no body to put the generated modules in
+#[tomlfuse::attach("tests/test.toml", [section] section.*)]
+mod generated;
+
+fn main() {}
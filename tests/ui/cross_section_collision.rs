@@ -0,0 +1,24 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    // generates `ConfigSettings` for the `config.settings` table...
+    [struct Config]
+    config.*
+
+    // ...which this one generates too
+    [struct ConfigSettings]
+    config.settings.*
+
+    // a module and a struct of the same name aren't the same section reopened
+    [Section]
+    section.*
+
+    [struct Section]
+    section.key
+}
+
+fn main() {}
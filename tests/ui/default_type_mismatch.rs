@@ -0,0 +1,13 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [defaults]
+    section.key ?= 1
+    section.number ?= 0
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [deps]
+    section.*
+    dependancies.*
+    allow_empty optional.*
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/enums.toml"
+
+    [current]
+    log.level as enum(Trace, Debug)
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/annotations.toml"
+
+    [exprs]
+    exprs.broken as expr(i64)
+}
+
+fn main() {}
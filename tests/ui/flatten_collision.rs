@@ -0,0 +1,14 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [dups flatten_exports]
+    duplicates.**
+}
+
+fn main() {
+    let _ = dups::FIRST;
+}
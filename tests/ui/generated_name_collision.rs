@@ -0,0 +1,14 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/ui/generated.toml"
+
+    // the `CHECKSUM` const would shadow the one bound from the toml
+    [struct Section]
+    #checksum
+    section.*
+}
+
+fn main() {}
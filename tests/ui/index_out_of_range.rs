@@ -0,0 +1,12 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [first]
+    mixed-types.array[3]
+}
+
+fn main() {}
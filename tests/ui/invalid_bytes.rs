@@ -0,0 +1,12 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/annotations.toml"
+
+    [blobs]
+    blobs.broken as bytes(hex)
+}
+
+fn main() {}
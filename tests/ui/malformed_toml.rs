@@ -0,0 +1,12 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/ui/malformed.toml"
+
+    [config]
+    config.*
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    // the paths tried for a relative one depend on the machine, see `test_declared_workspace_dir`
+    "/nonexistent/tests/ui/no_such_file.toml"
+
+    [config]
+    config.*
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [schema]
+    require section.key
+    require section.license
+    require config.settings.missing
+    section.key
+}
+
+fn main() {}
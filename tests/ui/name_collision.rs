@@ -0,0 +1,14 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/ui/collision.toml"
+
+    [section]
+    section.*
+}
+
+fn main() {
+    let _ = section::OTHER;
+}
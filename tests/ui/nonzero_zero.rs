@@ -0,0 +1,12 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/annotations.toml"
+
+    [pool]
+    pool.empty as nonzero(usize)
+}
+
+fn main() {}
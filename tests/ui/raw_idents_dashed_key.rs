@@ -0,0 +1,12 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [raw raw_idents]
+    special-chars.*
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [section]
+    section.key
+
+    [section keep_case]
+    section.number
+}
+
+fn main() {}
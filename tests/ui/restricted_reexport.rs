@@ -0,0 +1,14 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [internal pub(crate)]
+    section.*
+}
+
+pub use internal::KEY;
+
+fn main() {}
@@ -0,0 +1,13 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    [section]
+    section.key.*
+    section.number.value
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/ui/select_os.toml"
+
+    [app]
+    select_os app.timeout from app.timeout_by_os
+}
+
+fn main() {}
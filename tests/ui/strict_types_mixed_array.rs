@@ -0,0 +1,12 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/arrays.toml"
+
+    [tuples strict_types]
+    tuples.*
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#![allow(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    env = "TOMLFUSE_UI_UNSET_CONFIG"
+
+    [config]
+    config.*
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+#![deny(deprecated)]
+
+use tomlfuse::file;
+
+file! {
+    "tests/test.toml"
+
+    // a typo'd alias is only a warning, denied here to show it
+    [lenient]
+    section.key
+    alias renamed = section.nmuber
+
+    [strict strict_aliases]
+    section.key
+    alias renamed = section.nmuber
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+//! The code generation of the `tomlfuse` macros, for generating the bound constants from build
+//! scripts with [`build::generate`].
+
+pub use tomlfuse_codegen::build;
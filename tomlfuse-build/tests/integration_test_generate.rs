@@ -0,0 +1,50 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use std::fs;
+use tomlfuse_build::build::generate;
+
+const TOML: &str = r#"
+[app]
+# the name of the app
+name = "demo"
+ports = [8080, 8081]
+secret = "hunter2"
+
+[app.logging]
+level = "info"
+"#;
+
+#[test]
+fn test_generated_source_compiles() {
+    let source = generate(TOML, "[app] app.* !app.secret [struct Logging] app.logging.*").unwrap();
+    assert!(source.contains("the name of the app"), "{}", source);
+    assert!(!source.contains("hunter2"), "{}", source);
+
+    // what a build script writes to `OUT_DIR` and the crate `include!`s, here in one file
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("generated.rs");
+    let main = r#"
+fn main() {
+    assert_eq!(app::NAME, "demo");
+    assert_eq!(app::PORTS, &[8080, 8081]);
+    assert_eq!(app::logging::LEVEL, "info");
+    assert_eq!(Logging::LEVEL, "info");
+}
+"#;
+    fs::write(&path, format!("{}\n{}", source, main)).unwrap();
+    trybuild::TestCases::new().pass(&path);
+}
+
+#[test]
+fn test_errors() {
+    let err = generate(TOML, "[app] missing.*").unwrap_err();
+    assert!(err.starts_with("[app]: "), "{}", err);
+    assert!(generate("not toml", "[app] app.*").is_err());
+    assert!(generate(TOML, r#""config.toml" [app] app.*"#).is_err());
+    let collision = generate(TOML, "[Logging] app.logging.* [struct Logging] app.logging.*");
+    assert!(collision.unwrap_err().contains("is generated by both"));
+}
@@ -0,0 +1,858 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use crate::utils::{
+    convert_array_to_fixed_tokens, convert_value_to_radix_tokens, convert_value_to_tokens,
+    parse_kebab_name, snake_to_pascal, to_valid_ident,
+};
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::path::PathBuf;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Ident, Result as SynResult, Token};
+use toml::Value;
+
+/// A type annotation attached to a pattern with `as`, e.g. `key as u8-bytes`.
+///
+/// Annotations override the type that would otherwise be inferred from the TOML value,
+/// and apply to every field matched by the annotated pattern.
+///
+/// Like `Pattern`, the span is kept for diagnostics but ignored for equality, as is the
+/// directory of the source file, which is only known once the file has been resolved.
+#[derive(Clone, Debug)]
+pub struct Annotation {
+    pub kind: AnnotationKind,
+    span: Span,
+    /// Directory of the source file, that `path-relative-to-config` paths are joined onto
+    config_dir: Option<PathBuf>,
+}
+
+/// The supported annotation kinds.
+///
+/// - `U8Bytes`: `u8-bytes`, an array of integers in `0..=255` emitted as `&'static [u8]`
+/// - `LeBytes`/`BeBytes`: `le-bytes(u32)`/`be-bytes(u32)`, an integer emitted as its
+///   little/big-endian byte representation in the given integer type, e.g. `&'static [u8; 4]`
+/// - `Secs`/`Millis`: `secs`/`millis`, a non-negative integer emitted as a
+///   `core::time::Duration` of that many seconds/milliseconds
+/// - `Unique`: `unique`, an array with duplicates removed (keeping first occurrences in order),
+///   emitted as the array type it would otherwise be inferred as
+/// - `Enum`: `enum` or `enum(non_exhaustive)`, an array of strings that becomes an enum with a
+///   variant per string (see `TomlFields::generate_enum`), bound as a slice of all the variants;
+///   or with the variants listed, e.g. `enum(Trace, Debug)`, a string that's bound as the
+///   variant it matches case-insensitively
+/// - `Nested`: `nested`, keeps the whole hierarchy below the pattern's literal prefix
+///   as nested modules instead of flattening it; values keep their inferred types
+/// - `PathRelativeToConfig`: `path-relative-to-config`, a string path joined onto the directory
+///   of the source file at macro time, emitted as an absolute `&'static str`
+/// - `Bytes`: `bytes(base64)`/`bytes(hex)`, a string in one of `BYTE_ENCODINGS` decoded at macro
+///   time and emitted as a `&'static [u8]` byte string
+/// - `Radix`: `hex`/`oct`/`bin`, an integer (or an array of them) emitted as a literal in that
+///   radix instead of in decimal, e.g. `0o755i64` for a file mode; see `RADIXES`
+/// - `Path`: `path`, a string emitted as `&'static str` like without the annotation, with a fn
+///   next to the const returning it as a `&'static std::path::Path` (see
+///   `TomlFields::generate_const`), as a `Path` can't be made in a const
+/// - `NonZero`: `nonzero(usize)`, a non-zero integer emitted as the `core::num::NonZero*` type
+///   of the given integer type, e.g. `NonZeroUsize` for a pool size, where a `0` is an error
+/// - `FixedArray`: `array`, an array of elements of the same type emitted as a `[T; N]` array
+///   with its length in the type, rather than as a `&'static [T]` slice
+/// - `Expr`: `expr(i64)`, a string of Rust parsed as an expression and emitted verbatim as the
+///   value of the given type, e.g. `"1 + 2"`; it's up to the compiler whether it's valid there
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum AnnotationKind {
+    U8Bytes,
+    LeBytes(String),
+    BeBytes(String),
+    Secs,
+    Millis,
+    Unique,
+    Enum {
+        non_exhaustive: bool,
+        /// Variants listed in the annotation, empty when they come from an array of strings
+        variants: Vec<String>,
+    },
+    Nested,
+    PathRelativeToConfig,
+    Bytes(String),
+    Radix(u32),
+    Path,
+    NonZero(String),
+    FixedArray,
+    /// The type the expression is bound as, as written in the annotation
+    Expr(String),
+}
+
+/// Converts an integer into the bytes of the given integer type, checking that it fits.
+fn int_to_bytes(int: i64, ty: &str, little_endian: bool) -> Result<Vec<u8>, String> {
+    macro_rules! to_bytes {
+        ($($t:ident),*) => {
+            match ty {
+                $(stringify!($t) => {
+                    let v = <$t>::try_from(int)
+                        .map_err(|_| format!("{} is out of range for `{}`", int, ty))?;
+                    Ok(if little_endian { v.to_le_bytes() } else { v.to_be_bytes() }.to_vec())
+                },)*
+                _ => Err(format!("`{}` is not an integer type", ty)),
+            }
+        };
+    }
+    to_bytes!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128)
+}
+
+const INT_TYPES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128",
+];
+
+/// The integer types `nonzero(..)` takes, the ones with a `core::num::NonZero*` counterpart.
+const NONZERO_TYPES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+/// Checks that an integer fits in the given integer type, with `usize` and `isize` as they are
+/// where the macro runs.
+fn int_fits(int: i64, ty: &str) -> bool {
+    macro_rules! fits {
+        ($($t:ident),*) => {
+            match ty {
+                $(stringify!($t) => <$t>::try_from(int).is_ok(),)*
+                _ => false,
+            }
+        };
+    }
+    fits!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize)
+}
+
+/// The encodings `bytes(..)` decodes strings from.
+pub const BYTE_ENCODINGS: &[&str] = &["base64", "hex"];
+
+/// The radixes integers can be written in, by the names of their annotations.
+pub const RADIXES: &[(&str, u32)] = &[("hex", 16), ("oct", 8), ("bin", 2)];
+
+/// Decodes a hex string, two digits of either case per byte.
+fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    if s.len() % 2 != 0 {
+        return Err(format!("hex takes two digits per byte, but there are {}", s.len()));
+    }
+    s.as_bytes()
+        .chunks(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            std::str::from_utf8(pair)
+                .ok()
+                .filter(|pair| pair.chars().all(|c| c.is_ascii_hexdigit()))
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| {
+                    format!("`{}` at {} is not a hex byte", String::from_utf8_lossy(pair), i * 2)
+                })
+        })
+        .collect()
+}
+
+/// Decodes a base64 string, in the standard or the URL-safe alphabet, padded or not.
+fn decode_base64(s: &str) -> Result<Vec<u8>, String> {
+    let data = s.trim_end_matches('=');
+    if s.len() - data.len() > 2 || (s.len() > data.len() && s.len() % 4 != 0) {
+        return Err("the padding doesn't match the length".to_string());
+    }
+    if data.len() % 4 == 1 {
+        return Err(format!("{} characters can't be base64", data.len()));
+    }
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    let (mut acc, mut bits) = (0u32, 0);
+    for (i, c) in data.char_indices() {
+        let sextet = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => return Err(format!("`{}` at {} is not a base64 character", c, i)),
+        };
+        acc = (acc << 6) | sextet;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Ok(bytes)
+}
+
+impl PartialEq for Annotation {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl Eq for Annotation {}
+
+impl Hash for Annotation {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.kind.hash(state);
+    }
+}
+
+impl Annotation {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Sets the directory of the source file, see `AnnotationKind::PathRelativeToConfig`.
+    pub fn with_config_dir(mut self, config_dir: Option<PathBuf>) -> Self {
+        self.config_dir = config_dir;
+        self
+    }
+
+    /// Converts a TOML value into type and value tokens as dictated by this annotation.
+    ///
+    /// # Returns
+    /// The `(type, value)` token pair, or a message describing why the value
+    /// can't be represented as the annotated type.
+    pub fn convert(&self, value: &Value) -> Result<(TokenStream2, TokenStream2), String> {
+        match self.kind {
+            AnnotationKind::U8Bytes => {
+                let arr = value
+                    .as_array()
+                    .ok_or_else(|| format!("`{}` requires an array of integers", self))?;
+                let bytes = arr
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| match v.as_integer() {
+                        Some(int) => u8::try_from(int).map_err(|_| {
+                            format!("element {} ({}) is out of range for `{}`", i, int, self)
+                        }),
+                        None => Err(format!("element {} ({}) is not an integer", i, v)),
+                    })
+                    .collect::<Result<Vec<u8>, String>>()?;
+                Ok((quote! { &'static [u8] }, quote! { &[#(#bytes),*] }))
+            },
+            AnnotationKind::LeBytes(ref ty) | AnnotationKind::BeBytes(ref ty) => {
+                let int = value
+                    .as_integer()
+                    .ok_or_else(|| format!("`{}` requires an integer", self))?;
+                let little_endian = matches!(self.kind, AnnotationKind::LeBytes(_));
+                let bytes = int_to_bytes(int, ty, little_endian)?;
+                let len = bytes.len();
+                Ok((quote! { &'static [u8; #len] }, quote! { &[#(#bytes),*] }))
+            },
+            AnnotationKind::Secs | AnnotationKind::Millis => {
+                let int = value
+                    .as_integer()
+                    .ok_or_else(|| format!("`{}` requires an integer", self))?;
+                let int = u64::try_from(int)
+                    .map_err(|_| format!("a duration can't be negative, got {}", int))?;
+                let ctor = if self.kind == AnnotationKind::Secs {
+                    quote! { from_secs }
+                } else {
+                    quote! { from_millis }
+                };
+                Ok((
+                    quote! { ::core::time::Duration },
+                    quote! { ::core::time::Duration::#ctor(#int) },
+                ))
+            },
+            AnnotationKind::Unique => {
+                let arr = value
+                    .as_array()
+                    .ok_or_else(|| format!("`{}` requires an array", self))?;
+                let mut unique: Vec<Value> = Vec::with_capacity(arr.len());
+                for v in arr {
+                    if !unique.contains(v) {
+                        unique.push(v.clone());
+                    }
+                }
+                Ok(convert_value_to_tokens(&Value::Array(unique)))
+            },
+            // the enum type is named after the field, see `TomlFields::generate_enum`
+            AnnotationKind::Enum { .. } => Ok(convert_value_to_tokens(value)),
+            // structural only, see `TomlFields::build`
+            AnnotationKind::Nested => Ok(convert_value_to_tokens(value)),
+            AnnotationKind::PathRelativeToConfig => {
+                let path = value
+                    .as_str()
+                    .ok_or_else(|| format!("`{}` requires a string", self))?;
+                let config_dir = self
+                    .config_dir
+                    .as_ref()
+                    .ok_or_else(|| "the source file's directory isn't known".to_string())?;
+                // an absolute path stays as it is, like with `Path::join`
+                let joined = config_dir.join(path);
+                let joined = joined
+                    .to_str()
+                    .ok_or_else(|| format!("{} isn't valid UTF-8", joined.display()))?;
+                Ok((quote! { &'static str }, quote! { #joined }))
+            },
+            AnnotationKind::Bytes(ref encoding) => {
+                let s = value
+                    .as_str()
+                    .ok_or_else(|| format!("`{}` requires a string", self))?;
+                let bytes = if encoding == "hex" { decode_hex(s) } else { decode_base64(s) }
+                    .map_err(|msg| format!("invalid {}, {}", encoding, msg))?;
+                let lit = Literal::byte_string(&bytes);
+                Ok((quote! { &'static [u8] }, quote! { #lit }))
+            },
+            AnnotationKind::Radix(radix) => {
+                let is_int = |v: &Value| v.is_integer();
+                if !is_int(value) && !value.as_array().is_some_and(|arr| arr.iter().all(is_int)) {
+                    return Err(format!("`{}` requires an integer or an array of integers", self));
+                }
+                Ok(convert_value_to_radix_tokens(value, Some(radix)))
+            },
+            AnnotationKind::Path => {
+                let path = value
+                    .as_str()
+                    .ok_or_else(|| format!("`{}` requires a string", self))?;
+                Ok((quote! { &'static str }, quote! { #path }))
+            },
+            AnnotationKind::NonZero(ref ty) => {
+                let int = value
+                    .as_integer()
+                    .ok_or_else(|| format!("`{}` requires an integer", self))?;
+                if int == 0 {
+                    return Err(format!("`{}` requires an integer other than 0", self));
+                }
+                if !int_fits(int, ty) {
+                    return Err(format!("{} is out of range for `{}`", int, ty));
+                }
+                let nonzero = format_ident!("NonZero{}", snake_to_pascal(ty));
+                let abs = format!("{}{}", int.unsigned_abs(), ty);
+                let lit = syn::LitInt::new(&abs, Span::call_site());
+                let lit = if int < 0 { quote! { -#lit } } else { quote! { #lit } };
+                // `Option::unwrap` isn't const on older compilers, but matching is, and the value
+                // was checked to be non-zero above so the panic can't happen
+                Ok((
+                    quote! { ::core::num::#nonzero },
+                    quote! {
+                        match ::core::num::#nonzero::new(#lit) {
+                            ::core::option::Option::Some(n) => n,
+                            ::core::option::Option::None => ::core::panic!("checked to be non-0"),
+                        }
+                    },
+                ))
+            },
+            AnnotationKind::FixedArray => {
+                let arr = value
+                    .as_array()
+                    .ok_or_else(|| format!("`{}` requires an array", self))?;
+                convert_array_to_fixed_tokens(arr)
+            },
+            AnnotationKind::Expr(ref ty) => {
+                let source = value
+                    .as_str()
+                    .ok_or_else(|| format!("`{}` requires a string", self))?;
+                let expr = syn::parse_str::<syn::Expr>(source)
+                    .map_err(|err| format!("`{}` isn't a Rust expression: {}", source, err))?;
+                let ty = syn::parse_str::<syn::Type>(ty).map_err(|err| err.to_string())?;
+                Ok((quote! { #ty }, quote! { #expr }))
+            },
+        }
+    }
+
+    /// Collects the enum variants for an `enum` annotated array of strings.
+    ///
+    /// # Returns
+    /// The `(string, variant)` pairs in array order, or a message describing why the value
+    /// can't be made into an enum.
+    pub fn enum_variants(&self, value: &Value) -> Result<Vec<(String, Ident)>, String> {
+        if let AnnotationKind::Enum { ref variants, .. } = self.kind {
+            if !variants.is_empty() {
+                return Ok(variants
+                    .iter()
+                    .map(|variant| (variant.clone(), format_ident!("{}", variant)))
+                    .collect());
+            }
+        }
+        let arr = value
+            .as_array()
+            .ok_or_else(|| format!("`{}` requires an array of strings", self))?;
+        let mut variants: Vec<(String, Ident)> = Vec::with_capacity(arr.len());
+        for (i, v) in arr.iter().enumerate() {
+            let s = v
+                .as_str()
+                .ok_or_else(|| format!("element {} ({}) is not a string", i, v))?;
+            let name = snake_to_pascal(&to_valid_ident(s));
+            let variant = syn::parse_str::<Ident>(&name)
+                .map_err(|_| format!("element {} ({:?}) is not a valid variant name", i, s))?;
+            if let Some((first, _)) = variants.iter().find(|(_, other)| *other == variant) {
+                return Err(format!(
+                    "{:?} and {:?} both generate the variant `{}`",
+                    first, s, variant
+                ));
+            }
+            variants.push((s.to_string(), variant));
+        }
+        Ok(variants)
+    }
+
+    /// Finds the variant listed in an `enum(..)` annotation that a string value names.
+    ///
+    /// The string is matched case-insensitively, and in its PascalCase form too, so e.g.
+    /// `"info"`, `"INFO"` and `"Info"` all name `Info`, and `"very-loud"` names `VeryLoud`.
+    ///
+    /// # Returns
+    /// The variant, or a message listing the variants when none of them matches.
+    pub fn enum_value(&self, value: &Value) -> Result<Ident, String> {
+        let s = value
+            .as_str()
+            .ok_or_else(|| format!("`{}` requires a string", self))?;
+        let variants = self.enum_variants(value)?;
+        let pascal = snake_to_pascal(&to_valid_ident(s));
+        variants
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s) || name.eq_ignore_ascii_case(&pascal))
+            .map(|(_, variant)| variant.clone())
+            .ok_or_else(|| {
+                let expected = variants
+                    .iter()
+                    .map(|(name, _)| format!("`{}`", name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{:?} is none of {}", s, expected)
+            })
+    }
+}
+
+impl Parse for Annotation {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let span = input.span();
+        let name = parse_kebab_name(input)?;
+
+        let kind = match name.as_str() {
+            "u8-bytes" => AnnotationKind::U8Bytes,
+            "le-bytes" | "be-bytes" => {
+                // the integer type to take the bytes of, e.g. `le-bytes(u32)`
+                let content;
+                syn::parenthesized!(content in input);
+                let ty = content.parse::<Ident>()?;
+                if !INT_TYPES.contains(&ty.to_string().as_str()) {
+                    return Err(syn::Error::new(
+                        ty.span(),
+                        format!("Expected an integer type for `{}`, got `{}`", name, ty),
+                    ));
+                }
+                if name == "le-bytes" {
+                    AnnotationKind::LeBytes(ty.to_string())
+                } else {
+                    AnnotationKind::BeBytes(ty.to_string())
+                }
+            },
+            "secs" => AnnotationKind::Secs,
+            "millis" => AnnotationKind::Millis,
+            "unique" => AnnotationKind::Unique,
+            "enum" => {
+                // optionally `enum(non_exhaustive)` for enums exposed in a public api, and the
+                // variants for binding a single string, e.g. `enum(non_exhaustive, Fast, Slow)`
+                let mut non_exhaustive = false;
+                let mut variants: Vec<String> = Vec::new();
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    let idents = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                    for (i, ident) in idents.iter().enumerate() {
+                        let name = ident.to_string();
+                        if i == 0 && name == "non_exhaustive" {
+                            non_exhaustive = true;
+                        } else if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                            return Err(syn::Error::new(
+                                ident.span(),
+                                format!(
+                                    "Expected `non_exhaustive` or PascalCase variants for \
+                                     `enum`, got `{}`",
+                                    ident
+                                ),
+                            ));
+                        } else if variants.contains(&name) {
+                            return Err(syn::Error::new(
+                                ident.span(),
+                                format!("Variant `{}` is listed twice", ident),
+                            ));
+                        } else {
+                            variants.push(name);
+                        }
+                    }
+                }
+                AnnotationKind::Enum {
+                    non_exhaustive,
+                    variants,
+                }
+            },
+            "nested" => AnnotationKind::Nested,
+            "array" => AnnotationKind::FixedArray,
+            "path" => AnnotationKind::Path,
+            "path-relative-to-config" => AnnotationKind::PathRelativeToConfig,
+            "bytes" => {
+                // the encoding of the string, e.g. `bytes(base64)`
+                let content;
+                syn::parenthesized!(content in input);
+                let encoding = content.parse::<Ident>()?;
+                if !BYTE_ENCODINGS.contains(&encoding.to_string().as_str()) {
+                    return Err(syn::Error::new(
+                        encoding.span(),
+                        format!(
+                            "Expected one of `{}` for `bytes`, got `{}`",
+                            BYTE_ENCODINGS.join("`, `"),
+                            encoding
+                        ),
+                    ));
+                }
+                AnnotationKind::Bytes(encoding.to_string())
+            },
+            "nonzero" => {
+                // the integer type of the `NonZero*` type, e.g. `nonzero(u32)` for `NonZeroU32`
+                let content;
+                syn::parenthesized!(content in input);
+                let ty = content.parse::<Ident>()?;
+                if !NONZERO_TYPES.contains(&ty.to_string().as_str()) {
+                    return Err(syn::Error::new(
+                        ty.span(),
+                        format!("Expected an integer type for `nonzero`, got `{}`", ty),
+                    ));
+                }
+                AnnotationKind::NonZero(ty.to_string())
+            },
+            "expr" => {
+                // the type of the expression, which can't be inferred from it, e.g. `expr(i64)`
+                let content;
+                syn::parenthesized!(content in input);
+                let ty = content.parse::<syn::Type>()?;
+                AnnotationKind::Expr(quote! { #ty }.to_string())
+            },
+            // `hex`, `oct` or `bin`, see `RADIXES`
+            name => match RADIXES.iter().find(|(radix, _)| *radix == name) {
+                Some((_, radix)) => AnnotationKind::Radix(*radix),
+                None => {
+                    return Err(syn::Error::new(
+                        span,
+                        format!("Unknown annotation `{}`", name),
+                    ))
+                },
+            },
+        };
+
+        Ok(Annotation {
+            kind,
+            span,
+            config_dir: None,
+        })
+    }
+}
+
+impl Display for Annotation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            AnnotationKind::U8Bytes => write!(f, "u8-bytes"),
+            AnnotationKind::LeBytes(ref ty) => write!(f, "le-bytes({})", ty),
+            AnnotationKind::BeBytes(ref ty) => write!(f, "be-bytes({})", ty),
+            AnnotationKind::Secs => write!(f, "secs"),
+            AnnotationKind::Millis => write!(f, "millis"),
+            AnnotationKind::Unique => write!(f, "unique"),
+            AnnotationKind::Enum {
+                non_exhaustive,
+                ref variants,
+            } => {
+                let args = non_exhaustive
+                    .then_some("non_exhaustive")
+                    .into_iter()
+                    .chain(variants.iter().map(String::as_str))
+                    .collect::<Vec<_>>();
+                if args.is_empty() {
+                    write!(f, "enum")
+                } else {
+                    write!(f, "enum({})", args.join(", "))
+                }
+            },
+            AnnotationKind::Nested => write!(f, "nested"),
+            AnnotationKind::PathRelativeToConfig => write!(f, "path-relative-to-config"),
+            AnnotationKind::Bytes(ref encoding) => write!(f, "bytes({})", encoding),
+            AnnotationKind::Radix(radix) => {
+                let (name, _) = RADIXES
+                    .iter()
+                    .find(|(_, r)| *r == radix)
+                    .expect("Expected a radix from `RADIXES`");
+                write!(f, "{}", name)
+            },
+            AnnotationKind::Path => write!(f, "path"),
+            AnnotationKind::NonZero(ref ty) => write!(f, "nonzero({})", ty),
+            AnnotationKind::FixedArray => write!(f, "array"),
+            AnnotationKind::Expr(ref ty) => write!(f, "expr({})", ty),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotation(s: &str) -> Annotation {
+        syn::parse_str::<Annotation>(s).expect("Expected a valid annotation")
+    }
+
+    #[test]
+    fn test_parse_annotation() {
+        assert_eq!(annotation("u8-bytes").kind, AnnotationKind::U8Bytes);
+        assert_eq!(annotation("u8-bytes").to_string(), "u8-bytes");
+        assert_eq!(annotation("nested").kind, AnnotationKind::Nested);
+        assert!(syn::parse_str::<Annotation>("no-such-thing").is_err());
+        assert_eq!(
+            annotation("le-bytes(u32)").kind,
+            AnnotationKind::LeBytes("u32".to_string())
+        );
+        assert_eq!(annotation("be-bytes(i16)").to_string(), "be-bytes(i16)");
+        assert!(syn::parse_str::<Annotation>("le-bytes").is_err());
+        assert!(syn::parse_str::<Annotation>("le-bytes(f32)").is_err());
+        assert_eq!(
+            annotation("enum").kind,
+            AnnotationKind::Enum {
+                non_exhaustive: false,
+                variants: Vec::new()
+            }
+        );
+        assert_eq!(annotation("enum(non_exhaustive)").to_string(), "enum(non_exhaustive)");
+        assert!(syn::parse_str::<Annotation>("enum(exhaustive)").is_err());
+        assert_eq!(
+            annotation("enum(non_exhaustive, Fast, Slow)").to_string(),
+            "enum(non_exhaustive, Fast, Slow)"
+        );
+        assert_eq!(annotation("enum(Fast, Slow,)").to_string(), "enum(Fast, Slow)");
+        assert!(syn::parse_str::<Annotation>("enum(Fast, Fast)").is_err());
+        assert!(syn::parse_str::<Annotation>("enum(Fast, non_exhaustive)").is_err());
+    }
+
+    #[test]
+    fn test_u8_bytes() {
+        let value: Value = "bytes = [0, 127, 255]".parse::<Value>().unwrap()["bytes"].clone();
+        let (ty, val) = annotation("u8-bytes").convert(&value).unwrap();
+        assert_eq!(ty.to_string(), "& 'static [u8]");
+        assert_eq!(val.to_string(), "& [0u8 , 127u8 , 255u8]");
+    }
+
+    #[test]
+    fn test_u8_bytes_out_of_range() {
+        let value: Value = "bytes = [1, 2, 256]".parse::<Value>().unwrap()["bytes"].clone();
+        let err = annotation("u8-bytes").convert(&value).unwrap_err();
+        assert!(err.contains("element 2 (256)"), "{}", err);
+
+        let value: Value = "bytes = [-1]".parse::<Value>().unwrap()["bytes"].clone();
+        let err = annotation("u8-bytes").convert(&value).unwrap_err();
+        assert!(err.contains("element 0 (-1)"), "{}", err);
+    }
+
+    #[test]
+    fn test_u8_bytes_requires_array() {
+        let value = Value::Integer(1);
+        assert!(annotation("u8-bytes").convert(&value).is_err());
+    }
+
+    #[test]
+    fn test_endian_bytes() {
+        let value = Value::Integer(0x12345678);
+        let (ty, val) = annotation("le-bytes(u32)").convert(&value).unwrap();
+        assert_eq!(ty.to_string(), "& 'static [u8 ; 4usize]");
+        assert_eq!(val.to_string(), "& [120u8 , 86u8 , 52u8 , 18u8]");
+        let (_, val) = annotation("be-bytes(u32)").convert(&value).unwrap();
+        assert_eq!(val.to_string(), "& [18u8 , 52u8 , 86u8 , 120u8]");
+    }
+
+    #[test]
+    fn test_endian_bytes_out_of_range() {
+        let err = annotation("le-bytes(u8)").convert(&Value::Integer(256)).unwrap_err();
+        assert!(err.contains("out of range for `u8`"), "{}", err);
+        let err = annotation("be-bytes(u16)").convert(&Value::Integer(-1)).unwrap_err();
+        assert!(err.contains("out of range for `u16`"), "{}", err);
+        assert!(annotation("le-bytes(u32)").convert(&Value::from("x")).is_err());
+    }
+
+    #[test]
+    fn test_durations() {
+        let (ty, val) = annotation("secs").convert(&Value::Integer(30)).unwrap();
+        assert_eq!(ty.to_string(), ":: core :: time :: Duration");
+        assert_eq!(val.to_string(), ":: core :: time :: Duration :: from_secs (30u64)");
+        let (_, val) = annotation("millis").convert(&Value::Integer(250)).unwrap();
+        assert_eq!(val.to_string(), ":: core :: time :: Duration :: from_millis (250u64)");
+
+        let err = annotation("secs").convert(&Value::Integer(-1)).unwrap_err();
+        assert_eq!(err, "a duration can't be negative, got -1");
+        assert!(annotation("millis").convert(&Value::Float(1.5)).is_err());
+    }
+
+    #[test]
+    fn test_unique() {
+        let value: Value =
+            r#"tags = ["b", "a", "b", "c", "a"]"#.parse::<Value>().unwrap()["tags"].clone();
+        let (ty, val) = annotation("unique").convert(&value).unwrap();
+        assert_eq!(ty.to_string(), "& 'static [& 'static str]");
+        assert_eq!(val.to_string(), "& [\"b\" , \"a\" , \"c\"]");
+        assert!(annotation("unique").convert(&Value::from("x")).is_err());
+    }
+
+    #[test]
+    fn test_enum_variants() {
+        let value: Value = r#"levels = ["debug", "info", "very-loud"]"#.parse::<Value>().unwrap()
+            ["levels"]
+            .clone();
+        let variants = annotation("enum").enum_variants(&value).unwrap();
+        let names = variants
+            .iter()
+            .map(|(s, v)| format!("{}={}", s, v))
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["debug=Debug", "info=Info", "very-loud=VeryLoud"]);
+
+        let value: Value = r#"levels = ["a-b", "a_b"]"#.parse::<Value>().unwrap()["levels"].clone();
+        let err = annotation("enum").enum_variants(&value).unwrap_err();
+        assert!(err.contains("both generate the variant `AB`"), "{}", err);
+        let value: Value = r#"levels = ["1st"]"#.parse::<Value>().unwrap()["levels"].clone();
+        assert!(annotation("enum").enum_variants(&value).is_err());
+        assert!(annotation("enum").enum_variants(&Value::from("x")).is_err());
+    }
+
+    #[test]
+    fn test_enum_value() {
+        let annotation = annotation("enum(Trace, Debug, Info, VeryLoud)");
+        for level in ["info", "INFO", "Info"] {
+            assert_eq!(annotation.enum_value(&Value::from(level)).unwrap(), "Info");
+        }
+        assert_eq!(annotation.enum_value(&Value::from("very-loud")).unwrap(), "VeryLoud");
+        let err = annotation.enum_value(&Value::from("warn")).unwrap_err();
+        assert_eq!(err, "\"warn\" is none of `Trace`, `Debug`, `Info`, `VeryLoud`");
+        assert!(annotation.enum_value(&Value::Integer(1)).is_err());
+    }
+
+    #[test]
+    fn test_path_relative_to_config() {
+        let annotation = annotation("path-relative-to-config")
+            .with_config_dir(Some(PathBuf::from("/etc/app")));
+        let (ty, val) = annotation.convert(&Value::from("assets/logo.png")).unwrap();
+        assert_eq!(ty.to_string(), "& 'static str");
+        assert_eq!(val.to_string(), "\"/etc/app/assets/logo.png\"");
+        let (_, val) = annotation.convert(&Value::from("/abs/logo.png")).unwrap();
+        assert_eq!(val.to_string(), "\"/abs/logo.png\"");
+        let err = annotation.convert(&Value::Integer(1)).unwrap_err();
+        assert_eq!(err, "`path-relative-to-config` requires a string");
+    }
+
+    #[test]
+    fn test_bytes() {
+        let (ty, val) = annotation("bytes(hex)").convert(&Value::from("CAfe00")).unwrap();
+        assert_eq!(ty.to_string(), "& 'static [u8]");
+        assert_eq!(val.to_string(), Literal::byte_string(&[0xca, 0xfe, 0]).to_string());
+        assert_eq!(annotation("bytes(base64)").to_string(), "bytes(base64)");
+        for (encoded, decoded) in [
+            ("", &b""[..]),
+            ("Zg==", b"f"),
+            ("Zm8=", b"fo"),
+            ("Zm9v", b"foo"),
+            ("Zm9vYg", b"foob"),
+            ("-_-_", &[0xfb, 0xff, 0xbf]),
+        ] {
+            let (_, val) = annotation("bytes(base64)").convert(&Value::from(encoded)).unwrap();
+            assert_eq!(val.to_string(), Literal::byte_string(decoded).to_string(), "{}", encoded);
+        }
+        assert!(syn::parse_str::<Annotation>("bytes").is_err());
+        assert!(syn::parse_str::<Annotation>("bytes(base32)").is_err());
+    }
+
+    #[test]
+    fn test_bytes_invalid() {
+        let err = annotation("bytes(hex)").convert(&Value::from("abc")).unwrap_err();
+        assert_eq!(err, "invalid hex, hex takes two digits per byte, but there are 3");
+        let err = annotation("bytes(hex)").convert(&Value::from("ab+f")).unwrap_err();
+        assert_eq!(err, "invalid hex, `+f` at 2 is not a hex byte");
+        let err = annotation("bytes(base64)").convert(&Value::from("Zm9v!")).unwrap_err();
+        assert!(err.contains("can't be base64"), "{}", err);
+        let err = annotation("bytes(base64)").convert(&Value::from("Zm9v!A==")).unwrap_err();
+        assert_eq!(err, "invalid base64, `!` at 4 is not a base64 character");
+        assert!(annotation("bytes(base64)").convert(&Value::from("Zg=")).is_err());
+        assert!(annotation("bytes(hex)").convert(&Value::Integer(1)).is_err());
+    }
+
+    #[test]
+    fn test_radix() {
+        assert_eq!(annotation("oct").kind, AnnotationKind::Radix(8));
+        assert_eq!(annotation("hex").to_string(), "hex");
+        let convert = |annotation_str: &str, value: Value| {
+            let (ty, val) = annotation(annotation_str).convert(&value).unwrap();
+            (ty.to_string(), val.to_string())
+        };
+        assert_eq!(convert("oct", Value::Integer(0o755)), ("i64".into(), "0o755i64".into()));
+        assert_eq!(convert("hex", Value::Integer(0xdead_beef)).1, "0xDEADBEEFi64");
+        assert_eq!(convert("bin", Value::Integer(-10)).1, "- 0b1010i64");
+        // `i64::MIN` can't be negated from a literal of its own
+        assert_eq!(convert("hex", Value::Integer(i64::MIN)).1, "- 9223372036854775808i64");
+        let modes = Value::Array(vec![Value::Integer(0o644), Value::Integer(0o600)]);
+        let (ty, val) = convert("oct", modes);
+        assert_eq!((ty.as_str(), val.as_str()), ("& 'static [i64]", "& [0o644i64 , 0o600i64]"));
+        let err = annotation("oct").convert(&Value::from("755")).unwrap_err();
+        assert_eq!(err, "`oct` requires an integer or an array of integers");
+    }
+
+    #[test]
+    fn test_path() {
+        assert_eq!(annotation("path").kind, AnnotationKind::Path);
+        assert_eq!(annotation("path").to_string(), "path");
+        let (ty, val) = annotation("path").convert(&Value::from("data/dir")).unwrap();
+        assert_eq!(ty.to_string(), "& 'static str");
+        assert_eq!(val.to_string(), "\"data/dir\"");
+        let err = annotation("path").convert(&Value::Integer(1)).unwrap_err();
+        assert_eq!(err, "`path` requires a string");
+    }
+
+    #[test]
+    fn test_nonzero() {
+        assert_eq!(annotation("nonzero(usize)").kind, AnnotationKind::NonZero("usize".into()));
+        assert_eq!(annotation("nonzero(u32)").to_string(), "nonzero(u32)");
+        assert!(syn::parse_str::<Annotation>("nonzero").is_err());
+        assert!(syn::parse_str::<Annotation>("nonzero(f32)").is_err());
+        let (ty, val) = annotation("nonzero(usize)").convert(&Value::Integer(8)).unwrap();
+        assert_eq!(ty.to_string(), ":: core :: num :: NonZeroUsize");
+        assert!(val.to_string().contains("NonZeroUsize :: new (8usize)"), "{}", val);
+        let (_, val) = annotation("nonzero(i8)").convert(&Value::Integer(-128)).unwrap();
+        assert!(val.to_string().contains("new (- 128i8)"), "{}", val);
+        let err = annotation("nonzero(u16)").convert(&Value::Integer(0)).unwrap_err();
+        assert_eq!(err, "`nonzero(u16)` requires an integer other than 0");
+        let err = annotation("nonzero(u8)").convert(&Value::Integer(-1)).unwrap_err();
+        assert_eq!(err, "-1 is out of range for `u8`");
+        assert!(annotation("nonzero(u8)").convert(&Value::from("1")).is_err());
+    }
+
+    #[test]
+    fn test_expr() {
+        assert_eq!(annotation("expr(i64)").kind, AnnotationKind::Expr("i64".into()));
+        assert_eq!(annotation("expr(&'static str)").to_string(), "expr(& 'static str)");
+        assert!(syn::parse_str::<Annotation>("expr").is_err());
+        let (ty, val) = annotation("expr(i64)").convert(&Value::from("1 + 2")).unwrap();
+        assert_eq!((ty.to_string(), val.to_string()), ("i64".into(), "1 + 2".into()));
+        let (_, val) = annotation("expr(u64)").convert(&Value::from("u64::MAX / 2")).unwrap();
+        assert_eq!(val.to_string(), "u64 :: MAX / 2");
+        let err = annotation("expr(i64)").convert(&Value::from("1 +")).unwrap_err();
+        assert!(err.starts_with("`1 +` isn't a Rust expression: "), "{}", err);
+        let err = annotation("expr(i64)").convert(&Value::Integer(3)).unwrap_err();
+        assert_eq!(err, "`expr(i64)` requires a string");
+    }
+
+    #[test]
+    fn test_fixed_array() {
+        assert_eq!(annotation("array").kind, AnnotationKind::FixedArray);
+        assert_eq!(annotation("array").to_string(), "array");
+        let ints = Value::Array(vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)]);
+        let (ty, val) = annotation("array").convert(&ints).unwrap();
+        assert_eq!(ty.to_string(), "[i64 ; 3usize]");
+        assert_eq!(val.to_string(), "[1i64 , 2i64 , 3i64]");
+        let nested = Value::Array(vec![ints.clone(), Value::Array(vec![Value::Integer(4)])]);
+        let (ty, _) = annotation("array").convert(&nested).unwrap();
+        assert_eq!(ty.to_string(), "[& 'static [i64] ; 2usize]");
+        let (ty, _) = annotation("array").convert(&Value::Array(vec![])).unwrap();
+        assert_eq!(ty.to_string(), "[& 'static str ; 0]");
+
+        let mixed = Value::Array(vec![Value::Integer(8080), Value::from("http")]);
+        let err = annotation("array").convert(&mixed).unwrap_err();
+        assert!(err.starts_with("element 1 (of type string) isn't of the same type"), "{}", err);
+        let err = annotation("array").convert(&Value::Integer(1)).unwrap_err();
+        assert_eq!(err, "`array` requires an array");
+    }
+}
@@ -0,0 +1,70 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use crate::comments::extract_comments;
+use crate::input::{combine_modules, section_label, MacroInput};
+use crate::module::RootModule;
+use quote::ToTokens;
+use toml::Value;
+
+/// Generates the rust source the macros would expand to, for writing to `OUT_DIR` from a
+/// build script and `include!`ing it.
+///
+/// Takes the TOML document itself rather than a path, so it can come from anywhere a build
+/// script can get it from. The sections are written as in the macros, without the path.
+/// Like in the macros, the patterns of the crate's ignore file apply to every section, see
+/// `module::DEFAULT_IGNORE_FILE`.
+///
+/// # Parameters
+/// - `toml`: The TOML document to bind
+/// - `sections`: The sections with their patterns, e.g. `[app] app.* !app.secret`
+///
+/// # Returns
+/// The generated source, or the errors the macros would point at the input with.
+///
+/// # Example
+/// ```rust,ignore
+/// // build.rs
+/// let toml = std::fs::read_to_string("config.toml").unwrap();
+/// let source = tomlfuse_build::build::generate(&toml, "[app] app.*").unwrap();
+/// let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("config.rs");
+/// std::fs::write(out, source).unwrap();
+///
+/// // main.rs
+/// include!(concat!(env!("OUT_DIR"), "/config.rs"));
+/// ```
+pub fn generate(toml: &str, sections: &str) -> Result<String, String> {
+    let value: Value = toml
+        .parse()
+        .map_err(|err| format!("Failed to parse the toml: {}", err))?;
+    let input: MacroInput = syn::parse_str(sections).map_err(|err| err.to_string())?;
+    if let Some(path) = input.toml_paths.first() {
+        return Err(format!(
+            "Sections are bound to the given toml, so the path `{}` has nothing to point to",
+            path
+        ));
+    }
+    let ignored = RootModule::read_ignore_file(input.ignore_file.as_ref())
+        .map_err(|err| err.to_string())?;
+    let comments = extract_comments(toml);
+    let mut modules = Vec::new();
+    let mut errors = Vec::new();
+    for mut source in input.root_module_sources {
+        source.comments.extend(comments.clone());
+        source.exclusion_pats.extend(ignored.iter().cloned());
+        let label = section_label(&source);
+        let module = RootModule::from(source).with_toml(value.clone()).build();
+        errors.extend(module.errors.iter().map(|err| format!("{}: {}", label, err)));
+        modules.push((label, module.to_token_stream()));
+    }
+    let (combined, collisions) = combine_modules(&modules);
+    errors.extend(collisions);
+    if errors.is_empty() {
+        Ok(combined.to_string())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
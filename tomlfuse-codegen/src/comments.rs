@@ -0,0 +1,709 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use crate::utils::key_segment;
+use std::collections::BTreeMap;
+
+/// State for tracking toml parsing context
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StringState {
+    None,
+    // SingleQuote,
+    // DoubleQuote,
+    MultiSingleQuote,
+    MultiDoubleQuote,
+}
+
+/// Extracts comments from TOML content and associates them with keys/sections.
+///
+/// Comment handling:
+/// - Associates preceding comments (above key/section)
+/// - Captures inline comments (same line)
+/// - Joins multi-line comments with newlines
+/// - Preserves empty comment lines as blank lines
+/// - Resets comment accumulation on blank lines
+/// - Ignores orphaned comments with no associated key
+/// - Associates comments on the lines of a multi-line array with the elements on those lines
+/// - Documents the table a dotted key like `item.subkey = 1` makes up with the first comment
+///   above one of its keys
+///
+/// # Parameters
+/// - `content`: toml document as a string slice.
+///
+/// # Returns
+/// A `BTreeMap` where each entry maps the full dotted path of a field or section
+/// (e.g. `section.subsection.key`) to its concatenated comment text. Comments of array
+/// elements are mapped to the array's path with the element's index, e.g. `section.tags[1]`
+/// (see `element_comment_key`).
+///
+/// ```rust
+/// # use std::collections::BTreeMap;
+/// # fn extract_comments(input: &'static str) -> BTreeMap<String, String> {
+/// #    // dummy impl because proc-macro crate can't export this function
+/// #    let mut out = BTreeMap::new();
+/// #    out.insert("package.version".to_string(), "header\ninline comment".to_string());
+/// #    out
+/// # }
+/// let toml = r#"
+/// ​# header
+/// [package]
+/// version = "0.1.0" # inline comment
+/// "#;
+/// let comments = extract_comments(toml);
+/// assert_eq!(
+///     comments.get("package.version"),
+///     Some(&"header\ninline comment".to_string())
+/// );
+/// ```
+#[cold]
+pub fn extract_comments(content: &str) -> BTreeMap<String, String> {
+    let mut comments = BTreeMap::new();
+    if content.is_empty() {
+        return comments;
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut string_state = StringState::None;
+    let mut current_comments = Vec::new();
+    let mut current_path = Vec::new();
+    // the multi-line array being read, if any
+    let mut array: Option<ArrayState> = None;
+
+    for (_i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            current_comments.clear();
+            continue;
+        }
+
+        if let Some(state) = array.as_mut() {
+            let (element, comment) = state.scan(trimmed);
+            match (element, comment) {
+                (Some(index), comment) => {
+                    current_comments.extend(comment);
+                    if !current_comments.is_empty() {
+                        comments.insert(
+                            element_comment_key(&state.path, index),
+                            current_comments.join("\n"),
+                        );
+                    }
+                    current_comments.clear();
+                },
+                // a comment of its own goes with the next element, like it would with a key
+                (None, Some(comment)) if trimmed.starts_with('#') => {
+                    current_comments.push(comment);
+                },
+                (None, _) => current_comments.clear(),
+            }
+            if state.depth == 0 {
+                array = None;
+                current_comments.clear();
+            }
+            continue;
+        }
+
+        // properly detect string context including escape sequences
+        if string_state != StringState::None {
+            match string_state {
+                StringState::MultiSingleQuote if trimmed.contains("'''") => {
+                    string_state = StringState::None;
+                },
+                StringState::MultiDoubleQuote if trimmed.contains("\"\"\"") => {
+                    string_state = StringState::None;
+                },
+                _ => continue, // still in string, skip line
+            }
+            continue;
+        }
+
+        // detect start of multiline string literals
+        if trimmed.contains("\"\"\"") && !trimmed.contains("\"\"\"\"\"\"") {
+            string_state = StringState::MultiDoubleQuote;
+        } else if trimmed.contains("'''") && !trimmed.contains("''''''") {
+            string_state = StringState::MultiSingleQuote;
+        }
+        if string_state != StringState::None {
+            continue;
+        }
+
+        // reset comment group on blank lines
+        if trimmed.is_empty() {
+            current_comments.clear();
+            continue;
+        }
+
+        // section headers [section.subsection]
+        if trimmed.starts_with('[') {
+            if let Some(section_end) = trimmed.find(']') {
+                // extract section path
+                let section_path = &trimmed[1..section_end];
+                current_path.clear();
+                current_path = split_key(section_path);
+                let section_str = section_path.to_string();
+
+                // start with any preceding comments
+                let mut all_comments = current_comments.clone();
+
+                // check for inline comment
+                if let Some(inline) = extract_inline_comment(trimmed, section_end) {
+                    all_comments.push(inline);
+                }
+
+                // add combined comments
+                if !all_comments.is_empty() {
+                    comments.insert(section_str, all_comments.join("\n"));
+                }
+                current_comments.clear();
+                continue;
+            }
+        }
+
+        // comments
+        if let Some(comment_text) = trimmed.strip_prefix('#') {
+            let comment_text = comment_text.trim();
+
+            // preserve empty comments as empty strings to create double newlines
+            if comment_text.is_empty() {
+                current_comments.push("".to_string());
+            } else {
+                current_comments.push(comment_text.to_string());
+            }
+            continue;
+        }
+
+        // key-value pairs
+        if let Some(pos) = trimmed.find('=') {
+            if !trimmed.is_empty() {
+                let key = trimmed[..pos].trim();
+                // support dotted keys in assignments
+                let mut full_path = current_path.clone();
+                full_path.extend(split_key(key));
+                let path_str = full_path.join(".");
+
+                // inline comment if present
+                let mut key_comments = current_comments.clone();
+                if let Some(inline) = extract_inline_comment(trimmed, pos) {
+                    key_comments.push(inline);
+                }
+
+                // add comments if we have any
+                if !key_comments.is_empty() {
+                    comments.insert(path_str.clone(), key_comments.join("\n"));
+                }
+
+                // a dotted key makes up the tables before its last segment, which have no
+                // header to be documented at, so the first comment above one of their keys
+                // documents the deepest of them (but doesn't put it in a group)
+                if full_path.len() > current_path.len() + 1 {
+                    let (_, table_comment) = take_group_marker(&current_comments.join("\n"));
+                    if !table_comment.is_empty() {
+                        let table = full_path[..full_path.len() - 1].join(".");
+                        comments.entry(table).or_insert(table_comment);
+                    }
+                }
+
+                // reset comment accumulator
+                current_comments.clear();
+
+                // an array left open continues on the next lines
+                let mut state = ArrayState {
+                    path: path_str,
+                    depth: 0,
+                    index: 0,
+                };
+                state.scan(&trimmed[pos + 1..]);
+                if state.depth > 0 {
+                    array = Some(state);
+                }
+            }
+        }
+        // other line types - reset state
+        else if !trimmed.starts_with('#') {
+            current_comments.clear();
+        }
+    }
+
+    comments
+}
+
+// splits a dotted key into its segments, leaving dots inside quoted keys to the segment
+// (see `utils::key_segment`) and stripping the quotes, e.g. `a."b.c"` to `["a", "b_c"]`
+fn split_key(key: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut segment = String::new();
+    let mut quote = None;
+    for c in key.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '.') => segments.push(std::mem::take(&mut segment)),
+            _ => segment.push(c),
+        }
+    }
+    segments.push(segment);
+    segments
+        .iter()
+        .map(|seg| key_segment(seg.trim()))
+        .collect()
+}
+
+/// The key an array element's comment is mapped to by `extract_comments`, e.g. `tags[1]`.
+#[inline]
+pub fn element_comment_key(path: &str, index: usize) -> String {
+    format!("{}[{}]", path, index)
+}
+
+// where reading a multi-line array is at
+struct ArrayState {
+    path: String,
+    // how many brackets are open, the array's own included
+    depth: usize,
+    // the index of the element being read
+    index: usize,
+}
+
+impl ArrayState {
+    // scans a line of the array for brackets and the commas between elements, skipping strings,
+    // and returns the last element that had something on the line and the line's comment
+    fn scan(&mut self, line: &str) -> (Option<usize>, Option<String>) {
+        let mut element = None;
+        let mut quote: Option<char> = None;
+        let mut escaped = false;
+        for (pos, c) in line.char_indices() {
+            if self.depth > 0 && !c.is_whitespace() && !matches!(c, ',' | ']' | '#') {
+                element = Some(self.index);
+            }
+            match (quote, c) {
+                // only basic strings (`"..."`) have escapes
+                (Some('"'), '\\') if !escaped => {
+                    escaped = true;
+                    continue;
+                },
+                (Some(q), c) if c == q && !escaped => quote = None,
+                (Some(_), _) => {},
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '[') => self.depth += 1,
+                (None, ']') => self.depth = self.depth.saturating_sub(1),
+                (None, ',') if self.depth == 1 => self.index += 1,
+                (None, '#') => {
+                    let comment = line[pos + 1..].trim();
+                    return (element, (!comment.is_empty()).then(|| comment.to_string()));
+                },
+                _ => {},
+            }
+            escaped = false;
+        }
+        (element, None)
+    }
+}
+
+// helper function to extract inline comments, skipping `#`s inside strings so that e.g.
+// `point = { label = "#1" } # comment` gets `comment` rather than part of the value
+#[inline(always)]
+fn extract_inline_comment(line: &str, after_pos: usize) -> Option<String> {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (pos, c) in line.char_indices().skip_while(|(pos, _)| *pos <= after_pos) {
+        match (quote, c) {
+            // only basic strings (`"..."`) have escapes
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            },
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => {
+                let comment = line[pos + 1..].trim();
+                return (!comment.is_empty()).then(|| comment.to_string());
+            },
+            _ => {},
+        }
+        escaped = false;
+    }
+    None
+}
+
+/// Takes a `@group <name>` marker out of a comment.
+///
+/// The marker is a comment line of its own (or the inline comment), e.g. `# @group network`.
+///
+/// # Returns
+/// The group name if there was a marker, and the rest of the comment without the marker line.
+pub fn take_group_marker(comment: &str) -> (Option<String>, String) {
+    let mut group = None;
+    let mut rest = Vec::new();
+    for line in comment.lines() {
+        match line.trim().strip_prefix("@group") {
+            Some(name) if group.is_none() && name.starts_with(char::is_whitespace) => {
+                group = Some(name.trim().to_string());
+            },
+            _ => rest.push(line),
+        }
+    }
+    (group.filter(|g| !g.is_empty()), rest.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{element_comment_key, extract_comments, take_group_marker};
+
+    #[test]
+    fn test_preceding_and_inline_comments() {
+        let toml = r#"
+# header comment
+[package]
+# version doc
+version = "0.1.0" # inline comment
+"#;
+        let comments = extract_comments(toml);
+        assert_eq!(
+            comments.get("package.version"),
+            Some(&"version doc\ninline comment".to_string())
+        );
+    }
+
+    #[test]
+    fn test_blank_line_resets_preceding_comments() {
+        let toml = r#"
+# first comment
+
+# second comment
+key = true
+"#;
+        let comments = extract_comments(toml);
+        assert_eq!(comments.get("key"), Some(&"second comment".to_string()));
+    }
+
+    #[test]
+    fn test_ignore_hash_in_multiline_string() {
+        let toml = r#"
+value = """
+line1 # not a comment
+line2
+"""
+# real comment
+other = 123
+"#;
+        let comments = extract_comments(toml);
+        assert_eq!(comments.get("other"), Some(&"real comment".to_string()));
+        assert!(!comments.contains_key("value"));
+    }
+
+    #[test]
+    fn test_dotted_keys_and_section_headers() {
+        let toml = r#"
+[section.sub]
+# doc for item
+item.subkey = "x"
+"#;
+        let comments = extract_comments(toml);
+        assert_eq!(
+            comments.get("section.sub.item.subkey"),
+            Some(&"doc for item".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dotted_keys_document_their_table() {
+        let toml = r#"
+[section.sub]
+# the item
+item.subkey = "x" # the subkey
+# the other key
+item.other = "y"
+deep.er.key = 1 # inline only
+
+# @group network
+# the server
+server.host = "localhost"
+"#;
+        let comments = extract_comments(toml);
+        assert_eq!(comments["section.sub.item.subkey"], "the item\nthe subkey");
+        // the first comment sticks, and the inline one is the key's alone
+        assert_eq!(comments["section.sub.item"], "the item");
+        assert_eq!(comments["section.sub.item.other"], "the other key");
+        assert!(!comments.contains_key("section.sub.deep.er"));
+        assert!(!comments.contains_key("section.sub.deep"));
+        // the group marker is for the key
+        assert_eq!(comments["section.sub.server"], "the server");
+        assert_eq!(comments["section.sub.server.host"], "@group network\nthe server");
+    }
+
+    #[test]
+    fn test_quoted_keys_with_dots() {
+        let toml = r#"
+# top level
+"foo.bar" = 1
+
+[section."with.dot"]
+'single.quoted'.key = "x" # nested
+"#;
+        let comments = extract_comments(toml);
+        assert_eq!(comments.get("foo_bar"), Some(&"top level".to_string()));
+        assert_eq!(
+            comments.get("section.with_dot.single_quoted.key"),
+            Some(&"nested".to_string())
+        );
+    }
+
+    #[test]
+    fn test_inline_only_comment() {
+        let toml = r#"
+key = 10 # just inline
+"#;
+        let comments = extract_comments(toml);
+        assert_eq!(comments.get("key"), Some(&"just inline".to_string()));
+    }
+
+    #[test]
+    fn test_empty_comment_lines() {
+        let toml = r#"
+    # first line
+    #
+    # third line
+    key = true
+    "#;
+        let comments = extract_comments(toml);
+        // empty comment line should create double newline
+        assert_eq!(
+            comments.get("key"),
+            Some(&"first line\n\nthird line".to_string())
+        );
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let comments = extract_comments("");
+        assert!(comments.is_empty());
+    }
+
+    #[test]
+    fn test_orphaned_comments() {
+        let toml = r#"
+    # orphaned comment at top
+    [section]
+    key = "value"
+    # trailing comment with no key
+    "#;
+        let comments = extract_comments(toml);
+        // trailing comment should be discarded
+        assert_eq!(comments.len(), 1);
+        assert_eq!(
+            comments.get("section"),
+            Some(&"orphaned comment at top".to_string())
+        );
+    }
+
+    #[test]
+    fn test_multiple_consecutive_comments() {
+        let toml = r#"
+    # first line
+    # second line
+    # third line
+    key = true
+    "#;
+        let comments = extract_comments(toml);
+        assert_eq!(
+            comments.get("key"),
+            Some(&"first line\nsecond line\nthird line".to_string())
+        );
+    }
+
+    #[test]
+    fn test_single_quote_multiline() {
+        let toml = r#"
+    value = '''
+    # not a comment
+    '''
+    # real comment
+    key = true
+    "#;
+        let comments = extract_comments(toml);
+        assert_eq!(comments.get("key"), Some(&"real comment".to_string()));
+    }
+
+    #[test]
+    fn test_no_multiline_inline_comments() {
+        let toml = r#"
+    key = true # first inline comment
+    # this is an orphaned comment
+    # that spans multiple lines
+    next_key = false
+    "#;
+
+        let comments = extract_comments(toml);
+
+        // verify first inline comment is captured
+        assert_eq!(
+            comments.get("key"),
+            Some(&"first inline comment".to_string())
+        );
+        // orphaned multi-line comment is associated with next_key, not merged with previous inline
+        assert_eq!(
+            comments.get("next_key"),
+            Some(&"this is an orphaned comment\nthat spans multiple lines".to_string())
+        );
+    }
+
+    #[test]
+    fn test_deeply_nested_paths_and_tables() {
+        let toml = r#"
+        # top level comment for section1
+        [section1] # inline comment for section1
+        key1 = "value1"
+
+        # comment for nested section
+        [section1.subsection] # inline comment for subsection
+        key2 = "value2"
+
+        # deeply nested section comment
+        [section1.subsection.deep.nesting]
+        # comment for nested key
+        nested.key = "nested value" # inline nested key comment
+        
+        # another section
+        [section2]
+        
+        # subsection with no inline comment
+        [section2.config]
+        setting = true
+        "#;
+
+        let comments = extract_comments(toml);
+
+        // check section comments
+        assert_eq!(
+            comments.get("section1"),
+            Some(&"top level comment for section1\ninline comment for section1".to_string())
+        );
+
+        assert_eq!(
+            comments.get("section1.subsection"),
+            Some(&"comment for nested section\ninline comment for subsection".to_string())
+        );
+
+        assert_eq!(
+            comments.get("section1.subsection.deep.nesting"),
+            Some(&"deeply nested section comment".to_string())
+        );
+
+        // check deep nested key comments
+        assert_eq!(
+            comments.get("section1.subsection.deep.nesting.nested.key"),
+            Some(&"comment for nested key\ninline nested key comment".to_string())
+        );
+
+        // verify simple subsection comment
+        assert_eq!(
+            comments.get("section2.config"),
+            Some(&"subsection with no inline comment".to_string())
+        );
+    }
+
+    #[test]
+    fn test_inline_tables() {
+        let toml = r##"
+# a point at the top level
+point = { x = 1, y = 2 } # where it all starts
+
+[shapes]
+origin = { x = 0, label = "#0", note = 'a # b', quoted = "\"#\"" } # the origin
+"##;
+        let comments = extract_comments(toml);
+        assert_eq!(
+            comments.get("point"),
+            Some(&"a point at the top level\nwhere it all starts".to_string())
+        );
+        assert_eq!(comments.get("shapes.origin"), Some(&"the origin".to_string()));
+    }
+
+    #[test]
+    fn test_array_element_comments() {
+        let toml = r##"
+# what to build
+targets = [
+    "linux", # the main one
+    # built on request
+    "macos",
+    "windows", # has the most users
+] # after the array
+next = 1 # not an element
+
+nested = [[1, 2], # a pair, with a "#" in it
+    [3],
+]
+"##;
+        let comments = extract_comments(toml);
+        assert_eq!(comments.get("targets"), Some(&"what to build".to_string()));
+        assert_eq!(comments.get("targets[0]"), Some(&"the main one".to_string()));
+        assert_eq!(comments.get("targets[1]"), Some(&"built on request".to_string()));
+        assert_eq!(comments.get("targets[2]"), Some(&"has the most users".to_string()));
+        assert_eq!(comments.get("targets[3]"), None);
+        assert_eq!(comments.get("next"), Some(&"not an element".to_string()));
+        // the line the array starts on is the key's
+        assert_eq!(comments.get("nested"), Some(&"a pair, with a \"#\" in it".to_string()));
+        assert_eq!(comments.get("nested[1]"), None);
+    }
+
+    #[test]
+    fn test_group_marker() {
+        let toml = r#"
+# the host to bind to
+# @group network
+host = "localhost"
+port = 8080 # @group network
+timeout = 5 # @grouping is not a marker
+"#;
+        let comments = extract_comments(toml);
+        assert_eq!(
+            take_group_marker(&comments["host"]),
+            (Some("network".to_string()), "the host to bind to".to_string())
+        );
+        assert_eq!(
+            take_group_marker(&comments["port"]),
+            (Some("network".to_string()), String::new())
+        );
+        assert_eq!(take_group_marker(&comments["timeout"]).0, None);
+        assert_eq!(take_group_marker("@group").0, None);
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let toml = r#"# the package
+[package] # inline on a header
+# the name
+#
+# of the package
+name = "demo" # inline on a key
+description = """
+# not a comment, but a line of the string
+and = "not a key" # nor this
+"""
+literal = '''
+# nor this '''
+# the tags
+tags = [
+    "a", # the first
+    # the second
+    "b",
+]
+# the item
+item.key = 'x#y' # after a hash in a string
+"#;
+        let lf = extract_comments(toml);
+        let crlf = extract_comments(&toml.replace('\n', "\r\n"));
+        assert_eq!(lf, crlf);
+        assert!(crlf.values().all(|comment| !comment.contains('\r')), "{:?}", crlf);
+        // and the same as the lines of the file have them
+        assert_eq!(crlf["package"], "the package\ninline on a header");
+        assert_eq!(crlf["package.name"], "the name\n\nof the package\ninline on a key");
+        assert!(!crlf.contains_key("package.and"));
+        assert_eq!(crlf[&element_comment_key("package.tags", 1)], "the second");
+        assert_eq!(crlf["package.item.key"], "the item\nafter a hash in a string");
+        assert_eq!(crlf.len(), 7, "{:?}", crlf);
+    }
+}
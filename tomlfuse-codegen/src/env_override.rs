@@ -0,0 +1,143 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use crate::utils::is_table_array;
+use toml::Value;
+
+/// Prefix of the environment variables that `env_override` sections read.
+pub const ENV_PREFIX: &str = "TOMLFUSE_";
+
+/// Mangles a dotted TOML path into the name of the environment variable that overrides it.
+///
+/// The path keeps its casing, and both `.` and `-` become `_`, so e.g. `app.version` is
+/// overridden by `TOMLFUSE_app_version` and `app.log-level` by `TOMLFUSE_app_log_level`.
+/// Elements of arrays of tables are indexed like their modules, e.g. `TOMLFUSE_bin__0_name`.
+pub fn env_var_name(path: &str) -> String {
+    format!("{}{}", ENV_PREFIX, path.replace(['.', '-'], "_"))
+}
+
+/// Replaces the leaves of `value` with the environment variables overriding them, if set.
+///
+/// The variables are looked up with `lookup`, which is `std::env::var` outside of tests.
+///
+/// # Returns
+/// A message for each variable that is set but can't be parsed as the type of the value
+/// it overrides.
+pub fn apply_env_overrides(
+    value: &mut Value,
+    path: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    apply_env_overrides_at(value, path, lookup, &mut errors);
+    errors
+}
+
+fn apply_env_overrides_at(
+    value: &mut Value,
+    path: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+    errors: &mut Vec<String>,
+) {
+    let child_path = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match value {
+        Value::Table(table) => {
+            for (key, val) in table.iter_mut() {
+                apply_env_overrides_at(val, &child_path(key), lookup, errors);
+            }
+        },
+        Value::Array(arr) if is_table_array(arr) => {
+            for (i, val) in arr.iter_mut().enumerate() {
+                apply_env_overrides_at(val, &child_path(&format!("_{}", i)), lookup, errors);
+            }
+        },
+        _ => {
+            let name = env_var_name(path);
+            let Some(raw) = lookup(&name) else {
+                return;
+            };
+            match parse_override(value, &raw) {
+                Ok(overridden) => *value = overridden,
+                Err(msg) => {
+                    errors.push(format!("Can't override `{}` with `{}`: {}", path, name, msg))
+                },
+            }
+        },
+    }
+}
+
+/// Parses an environment variable as the same TOML type as the value it overrides.
+///
+/// Strings are taken as is, everything else is parsed as a TOML value (e.g. `8080`, `true`,
+/// `["a", "b"]`), so the generated const keeps its type either way.
+fn parse_override(original: &Value, raw: &str) -> Result<Value, String> {
+    if original.is_str() {
+        return Ok(Value::String(raw.to_string()));
+    }
+    let parsed = format!("value = {}", raw)
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"));
+    match (original, parsed) {
+        (Value::Float(_), Some(Value::Integer(int))) => Ok(Value::Float(int as f64)),
+        (_, Some(parsed)) if parsed.same_type(original) => Ok(parsed),
+        _ => Err(format!("expected {}, got `{}`", original.type_str(), raw)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_var_name() {
+        assert_eq!(env_var_name("app.version"), "TOMLFUSE_app_version");
+        assert_eq!(env_var_name("app.log-level"), "TOMLFUSE_app_log_level");
+        assert_eq!(env_var_name("bin._0.name"), "TOMLFUSE_bin__0_name");
+    }
+
+    #[test]
+    fn test_parse_override() {
+        assert_eq!(parse_override(&Value::from("a"), "1").unwrap(), Value::from("1"));
+        assert_eq!(parse_override(&Value::from(1), "42").unwrap(), Value::from(42));
+        assert_eq!(parse_override(&Value::from(1.5), "2").unwrap(), Value::from(2.0));
+        assert_eq!(parse_override(&Value::from(false), "true").unwrap(), Value::from(true));
+        assert_eq!(
+            parse_override(&Value::from(vec![1]), "[2, 3]").unwrap(),
+            Value::from(vec![2, 3])
+        );
+        let err = parse_override(&Value::from(1), "many").unwrap_err();
+        assert_eq!(err, "expected integer, got `many`");
+        assert!(parse_override(&Value::from(true), "1").is_err());
+    }
+
+    #[test]
+    fn test_apply_env_overrides() {
+        let mut value: Value = "[env_test]\nport = 1\nname = \"x\"\nbad = 2".parse().unwrap();
+        let lookup = |name: &str| match name {
+            "TOMLFUSE_env_test_port" => Some("8080".to_string()),
+            "TOMLFUSE_env_test_bad" => Some("nope".to_string()),
+            _ => None,
+        };
+        let errors = apply_env_overrides(&mut value, "", &lookup);
+        assert_eq!(value["env_test"]["port"].as_integer(), Some(8080));
+        // not set, so it keeps the toml value
+        assert_eq!(value["env_test"]["name"].as_str(), Some("x"));
+        assert_eq!(value["env_test"]["bad"].as_integer(), Some(2));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0],
+            "Can't override `env_test.bad` with `TOMLFUSE_env_test_bad`: \
+             expected integer, got `nope`"
+        );
+    }
+}
@@ -0,0 +1,2477 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use crate::annotation::{Annotation, AnnotationKind};
+use crate::comments::{element_comment_key, take_group_marker};
+use crate::env_override::env_var_name;
+use crate::options::SectionOptions;
+use crate::pattern::{Pattern, ValuePredicate};
+use crate::select_os::OsSelect;
+use crate::utils::{
+    convert_annotated_value_to_tokens, convert_value_to_narrowed_tokens, get_doc_comment,
+    get_inner_doc_comment, is_table_array, key_segment, rename_last_segment, snake_to_kebab,
+    snake_to_pascal, to_fn_ident, to_glob_path, to_owned_tokens, to_serde_tokens,
+    to_snake_case, to_valid_ident, try_convert_value_to_tokens, value_to_string_token,
+};
+use globset::{GlobMatcher, GlobSet};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, ToTokens};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::string::ToString;
+use syn::Result as SynResult;
+use toml::Value;
+
+pub const ROOT: &str = "";
+
+/// A value field converted into what it's bound as, see `TomlFields::bind_value`.
+struct BoundValue {
+    /// The type of the const or struct field
+    ty: TokenStream2,
+    /// The value of the const or struct field
+    val: TokenStream2,
+    /// Attributes the binding needs, like the `#[cfg]` of a `select_os` value
+    cfg: TokenStream2,
+    /// The doc comment of the binding
+    comment: TokenStream2,
+}
+
+/// Represents a single field extracted from a TOML document.
+///
+/// Maps a TOML node to its path, value and metadata required for code generation.
+/// Fields can represent either leaf values (constants) or tables (modules).
+/// Tracks both path and relationship information for hierarchical organization.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlField {
+    /// Name of the field, used as identifier in generated code
+    pub name: String,
+    /// The actual TOML value, owned so the fields don't borrow from the document
+    pub value: Value,
+    /// Full dot-separated path to this field in normalized form
+    pub path: String,
+    /// Path relative to the matching pattern, for hierarchical organization
+    pub relative_path: Option<String>, // path relative to matched pattern
+    /// Original path in the TOML file before normalization (preserves case)
+    pub toml_path: Option<String>, // path in the actual toml file
+    /// Optional alias for the field, if specified in the macro
+    pub alias: Option<String>,
+    /// Index of parent field in the fields collection
+    pub parent: Option<usize>,
+    /// Comment associated with this field from the TOML file
+    pub comment: Option<String>,
+    /// Comments of the elements of an array field, by index, for listing in its docs
+    pub element_comments: Vec<(usize, String)>,
+    /// Type annotation from the pattern that matched this field, if any
+    pub annotation: Option<Annotation>,
+}
+
+impl Default for TomlField {
+    fn default() -> Self {
+        TomlField {
+            name: String::default(),
+            value: Value::from(""),
+            path: String::default(),
+            relative_path: None,
+            toml_path: None,
+            alias: None,
+            parent: None,
+            comment: None,
+            element_comments: Vec::new(),
+            annotation: None,
+        }
+    }
+}
+
+impl From<Value> for TomlField {
+    fn from(value: Value) -> Self {
+        Self::default().with_value(value)
+    }
+}
+
+impl TomlField {
+    pub fn new(name: &str, path: &str, value: Value, parent: Option<usize>) -> Self {
+        TomlField {
+            name: name.to_string(),
+            value,
+            path: path.to_string(),
+            relative_path: None,
+            toml_path: None,
+            alias: None,
+            parent,
+            comment: None,
+            element_comments: Vec::new(),
+            annotation: None,
+        }
+    }
+
+    pub fn root(value: Value) -> Self {
+        TomlField {
+            name: ROOT.to_string(),
+            value,
+            path: ROOT.to_string(),
+            relative_path: None,
+            toml_path: None,
+            alias: None,
+            parent: None,
+            comment: None,
+            element_comments: Vec::new(),
+            annotation: None,
+        }
+    }
+    // FIXME: unify construction to use builder pattern instead of whatever we do above and in From impls
+    #[allow(dead_code)] // NOTE: might be useful later
+    pub fn with_relative_path(mut self, relative_path: &str) -> Self {
+        self.relative_path = Some(relative_path.to_string());
+        self
+    }
+    pub fn with_toml_path(mut self, toml_path: &str) -> Self {
+        self.toml_path = Some(toml_path.to_string());
+        self
+    }
+    #[allow(dead_code)] // NOTE: might be useful later
+    pub fn with_comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+    pub fn with_alias(mut self, alias: &str) -> Self {
+        if alias == ROOT {
+            return self;
+        }
+        self.alias = Some(alias.to_string());
+        self
+    }
+    #[allow(dead_code)] // NOTE: might be useful later
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = path.to_string();
+        self
+    }
+    #[allow(dead_code)] // NOTE: might be useful later
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+    pub fn with_value(mut self, value: Value) -> Self {
+        self.value = value;
+        self
+    }
+    #[allow(dead_code)] // NOTE: might be useful later
+    pub fn with_parent(mut self, parent: usize) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Determines if this field represents a TOML table.
+    ///
+    /// # Returns
+    /// `true` if the field's value is a TOML table or an array of tables, `false` otherwise.
+    ///
+    /// This helps guide the module generation process during code generation.
+    pub fn is_table(&self) -> bool {
+        match &self.value {
+            Value::Table(_) => true,
+            Value::Array(arr) => is_table_array(arr),
+            _ => false,
+        }
+    }
+
+    /// The key this field is generated under in the module tree.
+    ///
+    /// This is the last segment of the effective module path, which differs from the TOML key
+    /// when the field was re-rooted under a subtree alias, and falls back to the last segment of
+    /// the path when the pattern consumed the whole path.
+    pub fn module_key(&self) -> String {
+        self.effective_module_path()
+            .pop()
+            .or_else(|| self.path.split('.').last().map(|s| s.to_string()))
+            .unwrap_or_default()
+    }
+
+    // get effective module path based on section/pattern matching
+    pub fn effective_module_path(&self) -> Vec<String> {
+        // println!(" >> Resolving effective module path for: {}", self.path);
+        let output =
+        // use relative path if available (pattern matching)
+        if let Some(ref rel_path) = self.relative_path {
+            rel_path
+                .split('.')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect()
+        } else {
+            // if no relative path, use full path
+            self
+                .path
+                .split('.')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect()
+        };
+        // println!("    >> Effective module path: {:?}", output);
+        output
+    }
+}
+
+/// Container for pattern matchers used to filter TOML fields.
+///
+/// Filtering logic:
+/// - Inclusion patterns specify which fields to include
+/// - Exclusion patterns override inclusions for specific fields
+/// - Inclusion patterns with a `depth == N` constraint are matched one by one along with
+///   their constraint, instead of as a part of the inclusion set
+/// - Exclusion patterns with a value predicate (`!*.optional == true`) leave out the tables
+///   holding a matching key, see `Patterns::excludes_by_value`
+///
+/// The literals are included for improved heuristics downstream.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Patterns {
+    pub inclusions: Option<GlobSet>,
+    pub exclusions: Option<GlobSet>,
+    pub constrained: Vec<(GlobMatcher, Pattern)>,
+    /// Exclusion patterns with a value predicate, see `Pattern::parse_predicate`
+    pub by_value: Vec<(GlobMatcher, ValuePredicate)>,
+    /// Inclusion patterns matching a suffix anywhere (`**.version`), see `Pattern::is_anywhere`
+    pub anywhere: Option<GlobSet>,
+    /// Inclusion patterns collecting a key of each match (`dependencies.*.version`), with the
+    /// position of their wildcard segment, see `Pattern::collecting_segment`
+    pub collecting: Vec<(GlobMatcher, usize)>,
+    /// Every inclusion pattern along with its matcher, for pointing errors about a field at the
+    /// pattern that matched it, see `TomlFields::field_error`
+    pub matchers: Vec<(GlobMatcher, Pattern)>,
+    pub literals: Vec<String>,
+    /// Array elements selected by index (`package.authors[0]`), as the path of the array and
+    /// the index, resolved to count from the start
+    pub indices: Vec<(String, usize)>,
+}
+impl Patterns {
+    pub fn new() -> Self {
+        Patterns::default()
+    }
+    pub fn with_inclusions(mut self, inclusions: Option<GlobSet>) -> Self {
+        self.inclusions = inclusions;
+        self
+    }
+    pub fn with_exclusions(mut self, exclusions: Option<GlobSet>) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+    pub fn with_constrained(mut self, constrained: Vec<(GlobMatcher, Pattern)>) -> Self {
+        self.constrained = constrained;
+        self
+    }
+    pub fn with_by_value(mut self, by_value: Vec<(GlobMatcher, ValuePredicate)>) -> Self {
+        self.by_value = by_value;
+        self
+    }
+    pub fn with_anywhere(mut self, anywhere: Option<GlobSet>) -> Self {
+        self.anywhere = anywhere;
+        self
+    }
+    pub fn with_literals(mut self, literals: Vec<String>) -> Self {
+        self.literals = literals;
+        self
+    }
+    pub fn with_collecting(mut self, collecting: Vec<(GlobMatcher, usize)>) -> Self {
+        self.collecting = collecting;
+        self
+    }
+    pub fn with_matchers(mut self, matchers: Vec<(GlobMatcher, Pattern)>) -> Self {
+        self.matchers = matchers;
+        self
+    }
+    pub fn with_indices(mut self, indices: Vec<(String, usize)>) -> Self {
+        self.indices = indices;
+        self
+    }
+    // whether a leaf path is matched by an inclusion pattern, constrained or not
+    fn is_included(&self, path: &str, glob_path: &str) -> bool {
+        self.inclusions
+            .as_ref()
+            .map_or(true, |inclusions| inclusions.is_match(glob_path))
+            || self
+                .constrained
+                .iter()
+                .any(|(glob, pattern)| glob.is_match(glob_path) && pattern.matches_depth(path))
+    }
+    // whether an exclusion pattern matches a leaf path, or one of the tables it's in, so that
+    // `!app.logging` leaves out the whole table like `!app.logging.**` does
+    fn is_excluded(&self, path: &str) -> bool {
+        let Some(exclusions) = self.exclusions.as_ref() else {
+            return false;
+        };
+        let segments = path.split('.').collect::<Vec<_>>();
+        (1..=segments.len())
+            .any(|len| exclusions.is_match(to_glob_path(&segments[..len].join("."))))
+    }
+    // whether an exclusion pattern with a value predicate matches the key at `path` and holds
+    // for its value, which leaves out the table the key is in (or the key at the top level)
+    fn excludes_by_value(&self, path: &str, value: &Value) -> bool {
+        let glob_path = to_glob_path(path);
+        self.by_value
+            .iter()
+            .any(|(glob, predicate)| glob.is_match(&glob_path) && predicate.matches(value))
+    }
+    // pub fn add_literal(&mut self, literal: String) {
+    //     if self.literals.is_empty() {
+    //         self.literals = Vec::new();
+    //     }
+    //     self.literals.push(literal);
+    // }
+}
+
+/// Collection of TOML fields with pattern matching capabilities.
+///
+/// Central structure responsible for:
+/// 1. Extracting fields from TOML data matching specified patterns
+/// 2. Associating comments with their respective fields
+/// 3. Resolving both absolute and relative paths
+/// 4. Managing parent-child relationships between fields
+/// 5. Generating the Rust module structure
+///
+/// Fields can be accessed by index, name, or parent-child relationships.
+#[derive(Clone, Debug)]
+// TODO: should probably rethink the name to better signal what it is. `ModuleImpl` maybe?
+pub struct TomlFields {
+    /// The TOML document the fields are extracted from, which `build` moves into the root field
+    pub root_value: Option<Value>,
+    pub fields: Vec<TomlField>,
+    pub patterns: Patterns,
+    pub aliases: Option<BTreeMap<Pattern, Pattern>>,
+    pub subtree_aliases: Vec<(String, String)>,
+    /// Templated aliases (`alias d_* = dependencies.*`) as the template, the matcher of the
+    /// pattern it renames the matches of, and how many keys long the matches are, `None` for
+    /// any length with a `**`
+    pub alias_templates: Vec<(String, GlobMatcher, Option<usize>)>,
+    pub annotations: Vec<(GlobSet, Annotation)>,
+    pub options: SectionOptions,
+    pub comments: Option<BTreeMap<String, String>>,
+    /// Values picked by the target OS, keyed by the TOML path of the const they make up
+    pub os_selects: BTreeMap<String, OsSelect>,
+    /// Aliases (by their name) whose original path was found while extracting fields
+    pub used_aliases: BTreeSet<String>,
+    /// For `intern_strings`, the strings bound more than once, by the index of the pooled const
+    /// they're bound as, see `TomlFields::collect_string_pool`
+    pub string_pool: BTreeMap<String, usize>,
+    /// The files the TOML was read from, for `provenance_docs`
+    pub source_files: Vec<String>,
+}
+impl TomlFields {
+    pub fn new() -> Self {
+        TomlFields {
+            root_value: None,
+            fields: Vec::new(),
+            patterns: Patterns::new(),
+            aliases: None,
+            subtree_aliases: Vec::new(),
+            alias_templates: Vec::new(),
+            annotations: Vec::new(),
+            options: SectionOptions::default(),
+            comments: None,
+            os_selects: BTreeMap::new(),
+            used_aliases: BTreeSet::new(),
+            string_pool: BTreeMap::new(),
+            source_files: Vec::new(),
+        }
+    }
+
+    /// Builds the fields collection by extracting matched paths from the TOML document.
+    ///
+    /// Processing steps:
+    /// 1. Extracts all fields matching configured patterns, with their aliases applied
+    /// 2. Resolves relative paths for hierarchical organization
+    /// 3. Associates comments with the corresponding fields
+    pub fn build(mut self) -> Self {
+        // println!("Building TomlFields...");
+        let root = self
+            .root_value
+            .take()
+            .expect("Expected a root value when building TomlFields");
+        self.extract_matched_paths_from_value(&root, ROOT, 0);
+
+        // `flatten(hierarchy)` keeps the full paths, which is what fields without one get
+        let keeps_hierarchy = self.options.keeps_hierarchy();
+        if !keeps_hierarchy {
+            for i in 0..self.fields.len() {
+                if let Some(rel_path) = self.get_relative_path(&self.fields[i].path) {
+                    self.set_relative_path(i, rel_path);
+                }
+            }
+            self.name_collected_fields();
+        }
+
+        // `nested` fields mirror the toml hierarchy below the pattern's literal prefix, so their
+        // toml ancestors are re-rooted along with them to keep the module tree consistent;
+        // so do fields matched anywhere (`**.version`), which would mostly collide otherwise
+        for i in 0..self.fields.len() {
+            let anywhere = self.patterns.anywhere.as_ref().is_some_and(|anywhere| {
+                anywhere.is_match(to_glob_path(&self.fields[i].path))
+            });
+            let nested = matches!(
+                self.fields[i].annotation,
+                Some(Annotation {
+                    kind: AnnotationKind::Nested,
+                    ..
+                })
+            );
+            if keeps_hierarchy || !anywhere && !nested {
+                continue;
+            }
+            let Some(rel_path) = self.get_prefix_relative_path(&self.fields[i].path) else {
+                continue;
+            };
+            self.set_relative_path(i, rel_path);
+            let rel_path = self.fields[i].effective_module_path().join(".");
+            let rel_segs = rel_path.split('.').collect::<Vec<_>>();
+            let mut ancestor = self.fields[i].parent;
+            for depth in (1..rel_segs.len()).rev() {
+                let Some(idx) = ancestor else {
+                    break;
+                };
+                self.fields[idx].relative_path = Some(rel_segs[..depth].join("."));
+                ancestor = self.fields[idx].parent;
+            }
+        }
+
+        // subtree aliases re-root everything under the aliased table, the table included
+        for (alias, root) in &self.subtree_aliases {
+            let prefix = format!("{}.", root);
+            for field in &mut self.fields {
+                if field.path == *root {
+                    field.relative_path = Some(alias.clone());
+                } else if let Some(rest) = field.path.strip_prefix(&prefix) {
+                    field.relative_path = Some(format!("{}.{}", alias, rest));
+                }
+            }
+        }
+
+        if let Some(comments) = &self.comments {
+            for field in &mut self.fields {
+                if let Some(comment) = find_comment(comments, field, "") {
+                    // println!(" >> Found comment for field {}: {}", field.path, comment);
+                    field.comment = Some(comment.to_string());
+                }
+                let len = match &field.value {
+                    Value::Array(arr) if !is_table_array(arr) => arr.len(),
+                    _ => 0,
+                };
+                field.element_comments = (0..len)
+                    .filter_map(|i| {
+                        let suffix = element_comment_key("", i);
+                        find_comment(comments, field, &suffix).map(|c| (i, c.to_string()))
+                    })
+                    .collect();
+            }
+        }
+
+        let groups = self.apply_group_markers();
+        self.collapse_singletons(&groups);
+        if self.options.intern_strings {
+            self.string_pool = self.collect_string_pool();
+        }
+
+        self
+    }
+
+    /// Sets the path of a field relative to the section, from the path the patterns leave.
+    ///
+    /// With `flatten(joined)`, the segments the patterns consumed are kept by joining them into
+    /// the first segment that's left, so `nested.inner.value` under `nested.inner.*` is
+    /// `nested_inner_value` rather than `value`, and the value is named after that too. Aliased
+    /// fields are already named as asked, so they're left as is.
+    fn set_relative_path(&mut self, idx: usize, rel_path: String) {
+        let field = &mut self.fields[idx];
+        let sep = match self.options.flatten_separator() {
+            Some(sep) if field.alias.is_none() && !rel_path.is_empty() => sep,
+            _ => {
+                field.relative_path = Some(rel_path);
+                return;
+            },
+        };
+        let path_segs = field.path.split('.').collect::<Vec<_>>();
+        let consumed = path_segs.len().saturating_sub(rel_path.split('.').count());
+        if consumed == 0 {
+            field.relative_path = Some(rel_path);
+            return;
+        }
+        let joined = format!("{}{}{}", path_segs[..consumed].join(sep), sep, rel_path);
+        if !joined.contains('.') && !field.is_table() {
+            field.name = joined.clone();
+        }
+        field.relative_path = Some(joined);
+    }
+
+    /// Names the values a collecting pattern like `dependencies.*.version` matches after what its
+    /// wildcard matched, so `dependencies.serde.version` binds as `SERDE` rather than as the
+    /// `VERSION` of a `serde` module, for `name_by_wildcard`. Aliased fields are already named as
+    /// asked.
+    fn name_collected_fields(&mut self) {
+        if !self.options.name_by_wildcard {
+            return;
+        }
+        for field in &mut self.fields {
+            if field.is_table() || field.alias.is_some() {
+                continue;
+            }
+            let glob_path = to_glob_path(&field.path);
+            let Some(segment) = self
+                .patterns
+                .collecting
+                .iter()
+                .find(|(glob, _)| glob.is_match(&glob_path))
+                .map(|(_, segment)| *segment)
+            else {
+                continue;
+            };
+            let segments = field.path.split('.').collect::<Vec<_>>();
+            // `flatten(joined)` keeps the consumed segments in the name, like it does elsewhere
+            let name = match self.options.flatten_separator() {
+                Some(sep) => segments[..=segment].join(sep),
+                None => segments[segment].to_string(),
+            };
+            field.name = name.clone();
+            field.relative_path = Some(name);
+        }
+    }
+
+    /// Moves fields marked with a `# @group name` comment into a `name` submodule of the section.
+    ///
+    /// The group ignores where the field is in the TOML hierarchy, and a grouped table takes its
+    /// descendants along. A group that shares its name with a table at the section root merges
+    /// into it, otherwise an empty table is made up to hold the group.
+    fn apply_group_markers(&mut self) -> Vec<String> {
+        let mut groups = Vec::new();
+        for i in 0..self.fields.len() {
+            let Some(comment) = self.fields[i].comment.clone() else {
+                continue;
+            };
+            let (Some(group), rest) = take_group_marker(&comment) else {
+                continue;
+            };
+            // the marker is for us, not for the docs
+            self.fields[i].comment = if rest.is_empty() { None } else { Some(rest) };
+            if self.fields[i].path == ROOT {
+                continue;
+            }
+
+            let group_path = vec![group.clone()];
+            let group_idx = match self
+                .fields
+                .iter()
+                .position(|f| f.is_table() && f.effective_module_path() == group_path)
+            {
+                Some(idx) if idx == i => continue,
+                Some(idx) => idx,
+                None => {
+                    self.fields.push(
+                        TomlField::new(&group, &group, Value::Table(Default::default()), Some(0))
+                            .with_relative_path(&group),
+                    );
+                    self.fields.len() - 1
+                },
+            };
+
+            let old_prefix = format!("{}.", self.fields[i].effective_module_path().join("."));
+            let new_path = format!("{}.{}", group, self.fields[i].module_key());
+            let descendant_prefix = format!("{}.", self.fields[i].path);
+            for field in &mut self.fields {
+                if !field.path.starts_with(&descendant_prefix) {
+                    continue;
+                }
+                let effective_path = field.effective_module_path().join(".");
+                if let Some(rest) = effective_path.strip_prefix(&old_prefix) {
+                    field.relative_path = Some(format!("{}.{}", new_path, rest));
+                }
+            }
+            self.fields[i].relative_path = Some(new_path);
+            self.fields[i].parent = Some(group_idx);
+            groups.push(group);
+        }
+        groups
+    }
+
+    /// Folds tables holding a single value into their parents for `collapse_singletons`, the
+    /// value taking the joined name (`database.url` as `database_url`).
+    ///
+    /// Tables are folded deepest first, so chains of single-key tables fold all the way up.
+    /// Tables a pattern's literal prefix names (`app.database` of `app.database.*`), groups,
+    /// arrays of tables and their elements are left as they are, and so are values with an
+    /// alias, which already picked their own name.
+    fn collapse_singletons(&mut self, groups: &[String]) {
+        if !self.options.collapse_singletons {
+            return;
+        }
+        let explicit = self
+            .inclusion_prefixes()
+            .map(|prefix| prefix.join("."))
+            .collect::<BTreeSet<_>>();
+
+        let mut tables = (0..self.fields.len())
+            .filter(|&idx| {
+                let field = &self.fields[idx];
+                let path = field.effective_module_path();
+                let in_table_array = field
+                    .parent
+                    .is_some_and(|parent| matches!(self.fields[parent].value, Value::Array(_)));
+                let is_group = matches!(path.as_slice(), [key] if groups.contains(key));
+                matches!(field.value, Value::Table(_))
+                    && !path.is_empty()
+                    && !in_table_array
+                    && !explicit.contains(&field.path)
+                    && !field.toml_path.as_ref().is_some_and(|p| explicit.contains(p))
+                    && !is_group
+            })
+            .collect::<Vec<_>>();
+        let depth = |field: &TomlField| field.effective_module_path().len();
+        tables.sort_by_key(|&idx| std::cmp::Reverse(depth(&self.fields[idx])));
+
+        for idx in tables {
+            let children = self.get_relative_children_of(idx).fields;
+            let [child] = children.as_slice() else {
+                continue;
+            };
+            if child.is_table() || child.alias.is_some() {
+                continue;
+            }
+            let child_idx = self.index_of(child).expect("Expected a valid index of a child");
+            let mut path = self.fields[idx].effective_module_path();
+            let key = path.pop().unwrap_or_default();
+            let name = format!("{}_{}", key, child.name);
+            path.push(name.clone());
+            self.fields[child_idx].relative_path = Some(path.join("."));
+            self.fields[child_idx].name = name;
+            // like the tables a pattern prefix consumes, so it's nobody's child anymore
+            self.fields[idx].relative_path = Some(String::new());
+        }
+    }
+    /// Collects the strings bound more than once for `intern_strings`, numbered in the order
+    /// they're first found in. Only values bound as they are count, so not the annotated or
+    /// OS-selected ones.
+    fn collect_string_pool(&self) -> BTreeMap<String, usize> {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for field in self.fields.iter().skip(1) {
+            let Value::String(s) = &field.value else {
+                continue;
+            };
+            let os_selected = self
+                .os_selects
+                .contains_key(field.toml_path.as_deref().unwrap_or(&field.path));
+            if field.annotation.is_some() || os_selected {
+                continue;
+            }
+            match counts.iter_mut().find(|(pooled, _)| pooled == s) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((s, 1)),
+            }
+        }
+        counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .enumerate()
+            .map(|(idx, (s, _))| (s.to_string(), idx))
+            .collect()
+    }
+
+    /// The path to the pooled const of a string for `intern_strings`, from the module the
+    /// field's const is in up to the section root that holds the pool.
+    fn pooled_string(&self, field: &TomlField) -> Option<TokenStream2> {
+        let Value::String(s) = &field.value else {
+            return None;
+        };
+        let ident = format_ident!("__STR_{}", self.string_pool.get(s)?);
+        let depth = field.effective_module_path().len().saturating_sub(1);
+        let supers = std::iter::repeat(quote! { super:: }).take(depth);
+        Some(quote! { #(#supers)* #ident })
+    }
+
+    /// Generates the private consts of the `intern_strings` pool, see `collect_string_pool`.
+    fn generate_string_pool(&self) -> TokenStream2 {
+        let mut pool = self.string_pool.iter().collect::<Vec<_>>();
+        pool.sort_by_key(|(_, idx)| **idx);
+        let consts = pool.into_iter().map(|(s, idx)| {
+            let ident = format_ident!("__STR_{}", idx);
+            quote! { const #ident: &str = #s; }
+        });
+        quote! { #(#consts)* }
+    }
+
+    pub fn with_root(mut self, value: Value) -> Self {
+        self.root_value = Some(value);
+        self
+    }
+    pub fn with_aliases(mut self, aliases: Option<BTreeMap<Pattern, Pattern>>) -> Self {
+        self.aliases = aliases;
+        self
+    }
+    pub fn with_subtree_aliases(mut self, subtree_aliases: Vec<(String, String)>) -> Self {
+        self.subtree_aliases = subtree_aliases;
+        self
+    }
+    pub fn with_alias_templates(
+        mut self,
+        alias_templates: Vec<(String, GlobMatcher, Option<usize>)>,
+    ) -> Self {
+        self.alias_templates = alias_templates;
+        self
+    }
+    pub fn with_annotations(mut self, annotations: Vec<(GlobSet, Annotation)>) -> Self {
+        self.annotations = annotations;
+        self
+    }
+    pub fn with_options(mut self, options: SectionOptions) -> Self {
+        self.options = options;
+        self
+    }
+    pub fn with_inclusion_globs(mut self, inclusion_globs: Option<GlobSet>) -> Self {
+        self.patterns = self.patterns.with_inclusions(inclusion_globs);
+        self
+    }
+    pub fn with_exclusion_globs(mut self, exclusion_globs: Option<GlobSet>) -> Self {
+        self.patterns = self.patterns.with_exclusions(exclusion_globs);
+        self
+    }
+    pub fn with_value_exclusions(mut self, by_value: Vec<(GlobMatcher, ValuePredicate)>) -> Self {
+        self.patterns = self.patterns.with_by_value(by_value);
+        self
+    }
+    pub fn with_constrained_globs(mut self, constrained: Vec<(GlobMatcher, Pattern)>) -> Self {
+        self.patterns = self.patterns.with_constrained(constrained);
+        self
+    }
+    pub fn with_anywhere_globs(mut self, anywhere_globs: Option<GlobSet>) -> Self {
+        self.patterns = self.patterns.with_anywhere(anywhere_globs);
+        self
+    }
+    pub fn with_collecting_globs(mut self, collecting: Vec<(GlobMatcher, usize)>) -> Self {
+        self.patterns = self.patterns.with_collecting(collecting);
+        self
+    }
+    pub fn with_pattern_matchers(mut self, matchers: Vec<(GlobMatcher, Pattern)>) -> Self {
+        self.patterns = self.patterns.with_matchers(matchers);
+        self
+    }
+    pub fn with_pat_literals(mut self, patterns: Vec<String>) -> Self {
+        self.patterns = self.patterns.with_literals(patterns);
+        self
+    }
+    pub fn with_indices(mut self, indices: Vec<(String, usize)>) -> Self {
+        self.patterns = self.patterns.with_indices(indices);
+        self
+    }
+    // pub fn with_pat_literal(mut self, pattern: String) -> Self {
+    //     self.patterns.add_literal(pattern);
+    //     self
+    // }
+    pub fn with_comments(mut self, comments: BTreeMap<String, String>) -> Self {
+        self.comments = Some(comments);
+        self
+    }
+    pub fn with_os_selects(mut self, os_selects: BTreeMap<String, OsSelect>) -> Self {
+        self.os_selects = os_selects;
+        self
+    }
+    pub fn with_source_files(mut self, source_files: Vec<String>) -> Self {
+        self.source_files = source_files;
+        self
+    }
+
+    // find the relative path of a path within the section, below the longest literal prefix of
+    // the patterns it falls under; ancestors of a prefix are consumed by it entirely, so with
+    // both `config.*` and `config.settings.*`, `config.settings.timeout` is relative to the latter
+    fn get_relative_path(&self, path: &str) -> Option<String> {
+        let path_segs = path.split('.').collect::<Vec<_>>();
+        self.inclusion_prefixes()
+            .filter_map(|prefix| {
+                if path_segs.starts_with(&prefix) {
+                    Some((prefix.len(), path_segs[prefix.len()..].join(".")))
+                } else if prefix.starts_with(&path_segs) {
+                    Some((path_segs.len(), String::new()))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(matched, _)| *matched)
+            .map(|(_, rel_path)| rel_path)
+    }
+
+    // strip the longest literal (wildcard-free) pattern prefix from a path, keeping the rest as is
+    fn get_prefix_relative_path(&self, path: &str) -> Option<String> {
+        let path_segs = path.split('.').collect::<Vec<_>>();
+        self.inclusion_prefixes()
+            .filter(|prefix| prefix.len() < path_segs.len() && path_segs.starts_with(prefix))
+            .max_by_key(|prefix| prefix.len())
+            .map(|prefix| path_segs[prefix.len()..].join("."))
+    }
+
+    // the literal (wildcard-free) leading segments of each inclusion pattern
+    fn inclusion_prefixes(&self) -> impl Iterator<Item = Vec<&str>> {
+        self.patterns
+            .literals
+            .iter()
+            .filter(|pat| !pat.starts_with('!'))
+            .map(|pat| {
+                pat.split('.')
+                    .take_while(|s| !s.contains(['*', '?', '{', '[']))
+                    .collect::<Vec<_>>()
+            })
+    }
+
+    #[allow(dead_code)] // NOTE: might be useful later
+    /// Gets direct child fields based on the original TOML structure.
+    ///
+    /// # Parameters
+    /// - `this_idx`: Index of the parent field
+    ///
+    /// # Returns
+    /// A new `TomlFields` containing only direct children of the specified field
+    pub fn get_toml_children_of(&self, this_idx: usize) -> TomlFields {
+        TomlFields {
+            fields: self
+                .fields
+                .iter()
+                .filter(|field| {
+                    field.parent == Some(this_idx)
+                        && self.index_of(field).unwrap_or(usize::MAX) != this_idx
+                })
+                .cloned()
+                .collect::<Vec<_>>(),
+            patterns: self.patterns.clone(),
+            root_value: self.root_value.clone(),
+            aliases: self.aliases.clone(),
+            subtree_aliases: self.subtree_aliases.clone(),
+            alias_templates: self.alias_templates.clone(),
+            annotations: self.annotations.clone(),
+            options: self.options.clone(),
+            comments: self.comments.clone(),
+            os_selects: self.os_selects.clone(),
+            used_aliases: self.used_aliases.clone(),
+            string_pool: self.string_pool.clone(),
+            source_files: self.source_files.clone(),
+        }
+    }
+
+    #[allow(dead_code)] // NOTE: useful api for future
+    pub fn get_relative_parent_of(&self, this_idx: usize) -> &TomlField {
+        let this_field = self.get_field(this_idx).expect("Expected a valid field");
+        self.get_relative_parent_of_field(this_field)
+    }
+
+    /// Finds the relative parent field based on effective module paths.
+    ///
+    /// Uses pattern-based heuristics to determine parent-child relationships in
+    /// the generated module structure, which will in most cases differ from the original TOML hierarchy.
+    /// The closest TOML ancestor with a matching effective path is preferred, since names alone
+    /// are ambiguous when the same key appears at several depths.
+    ///
+    /// # Parameters
+    /// - `this_field`: Field to find the relative parent for
+    ///
+    /// # Returns
+    /// Reference to the module parent field based on effective path
+    /// (NOTE: not necessarily the same as the TOML document parent)
+    pub fn get_relative_parent_of_field(
+        &self,
+        this_field: &TomlField,
+    ) -> &TomlField {
+        let _effective_path = this_field.effective_module_path();
+        let effective_path = _effective_path[.._effective_path.len().saturating_sub(1)].to_vec();
+        let relative_parent_name = if !effective_path.is_empty() {
+            effective_path[effective_path.len().saturating_sub(1)].to_string()
+        } else {
+            ROOT.to_string()
+        };
+        if !effective_path.is_empty() {
+            let mut ancestor = this_field.parent;
+            while let Some(idx) = ancestor {
+                let field = self.get_field(idx).expect("Expected a valid parent index");
+                if field.effective_module_path() == effective_path {
+                    return field;
+                }
+                ancestor = field.parent;
+            }
+        }
+        let relative_parent_field = self.get_by_name(&relative_parent_name).unwrap_or_else(|| {
+            panic!(
+                "Expected a valid relative parent field ({} didn't exist, processing {})",
+                &relative_parent_name, this_field.name
+            )
+        });
+        // println!("    >> Found relative parent for {}; field: {}", this_field.name, relative_parent_field.name);
+        relative_parent_field
+    }
+
+    /// Gets children fields in the module hierarchy (not TOML hierarchy).
+    ///
+    /// Different from `get_toml_children_of` as this returns fields that will appear
+    /// in the generated module rather than following the original TOML structure.
+    /// # Parameters
+    /// - `this_idx`: Index of the parent field
+    ///
+    /// # Returns
+    pub fn get_relative_children_of(&self, this_idx: usize) -> TomlFields {
+        let this_field = self.get_field(this_idx).expect("Expected a valid field");
+        let children = self
+            .fields
+            .iter()
+            .filter(|field| {
+                let idx = self
+                    .index_of(field)
+                    .expect("Expected a valid index of a field that exists");
+                idx != this_idx && self.get_relative_parent_of_field(field) == this_field
+            })
+            .cloned()
+            .collect::<Vec<TomlField>>();
+        TomlFields {
+            fields: children,
+            patterns: self.patterns.clone(),
+            root_value: self.root_value.clone(),
+            aliases: self.aliases.clone(),
+            subtree_aliases: self.subtree_aliases.clone(),
+            alias_templates: self.alias_templates.clone(),
+            annotations: self.annotations.clone(),
+            options: self.options.clone(),
+            comments: self.comments.clone(),
+            os_selects: self.os_selects.clone(),
+            used_aliases: self.used_aliases.clone(),
+            string_pool: self.string_pool.clone(),
+            source_files: self.source_files.clone(),
+        }
+    }
+
+    pub fn get_field(&self, idx: usize) -> Option<&TomlField> {
+        self.fields.get(idx)
+    }
+
+    /// The TOML document the fields were extracted from, which the root field holds once built.
+    pub fn document(&self) -> Option<&Value> {
+        self.get_field(0).map(|root| &root.value)
+    }
+
+    /// Finds a field by its name.
+    ///
+    /// # Parameters
+    /// - `name`: Name of the field to find
+    ///
+    /// # Returns
+    /// Reference to the found field, or None if no field with that name exists
+    pub fn get_by_name(&self, name: &str) -> Option<&TomlField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    pub fn index_of(&self, field: &TomlField) -> Option<usize> {
+        self.fields.iter().position(|f| f == field)
+    }
+
+    /// Extracts fields from a TOML value that match configured patterns.
+    ///
+    /// Recursively walks the TOML document structure, filtering fields based on:
+    /// - Inclusion patterns
+    /// - Exclusion patterns
+    /// - Aliases
+    ///
+    /// # Parameters
+    /// - `value`: TOML value to extract fields from
+    /// - `_path`: Current path in the TOML hierarchy
+    /// - `parent_idx`: Index of the parent field (for recursion)
+    ///
+    pub fn extract_matched_paths_from_value(
+        &mut self,
+        value: &Value,
+        _path: &str,
+        parent_idx: usize,
+    ) {
+        let orig_path = _path.to_string();
+        let mut aliases = self
+            .aliases
+            .as_ref()
+            .map(|aliases| {
+                let _processed_path = to_valid_ident(_path);
+                aliases
+                    .iter()
+                    // NOTE: checking both because this all got a bit messy and needs a bit of
+                    //       cleanup, and unsure presently if we clean these up before we pass
+                    //       them here or not
+                    .filter(|(_, orig)| {
+                        _path == orig.to_string() || _processed_path == orig.to_string()
+                    })
+                    .map(|(alias, _)| alias.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        self.used_aliases.extend(aliases.iter().cloned());
+        // the aliases written out bring their values in regardless of the patterns, while the
+        // matches of templated ones only get renamed
+        let explicit = !aliases.is_empty();
+        // a templated alias renames each match, with the `*` standing in for its key
+        if let Some((_, key)) = _path.rsplit_once('.') {
+            let glob_path = to_glob_path(&to_valid_ident(_path));
+            let depth = _path.split('.').count();
+            aliases.extend(
+                self.alias_templates
+                    .iter()
+                    .filter(|(_, _, len)| len.map_or(true, |len| len == depth))
+                    .filter(|(_, glob, _)| glob.is_match(&glob_path))
+                    .map(|(template, _, _)| template.replacen('*', key, 1)),
+            );
+        }
+        // every alias past the first fans the same value out into a field of its own
+        let extra_aliases = if aliases.len() > 1 { aliases.split_off(1) } else { Vec::new() };
+        let alias = aliases.pop();
+        let alias_name = alias
+            .as_ref()
+            .and_then(|alias| if alias == "*" { None } else { Some(alias) })
+            .map(|alias| alias.to_string());
+        let is_alias = alias.is_some();
+        let aliased_path = if let Some(alias) = alias {
+            rename_last_segment(_path, &alias)
+        } else {
+            _path.to_string()
+        };
+        let path = to_valid_ident(&aliased_path);
+        // let path = aliased_path;
+        // if path.contains('-') {
+        //     println!("    >> Found unprocessed toml path in kebab-case: {}", path);
+        // }
+        // println!(" >> Extracting paths from: {}", path);
+        let (field, field_idx) = if parent_idx == 0 && self.fields.is_empty() {
+            (TomlField::root(value.clone()), 0)
+        } else {
+            let field = TomlField::new(
+                path.split('.')
+                    .last()
+                    .expect("Expected a valid path to extract name from"),
+                // path.split_once('.').unwrap_or((path, path)).1, // FIXME: this wont work with patterns like * or ** or **.** etc.
+                &path,
+                value.clone(),
+                Some(parent_idx),
+            )
+            .with_alias(alias_name.as_deref().unwrap_or(ROOT))
+            .with_toml_path(&orig_path);
+
+            (field, self.fields.len()) // idx where this field will be placed
+        };
+
+        match value {
+            Value::Table(table) => {
+                // NOTE: this is good for some additional logic we might want to add to tables (<=> modules)
+                // println!("    >> Pushed table `{}`, recursing into it... ", &field.name);
+                let child_path = |key: &str| {
+                    let key = key_segment(key);
+                    if path.is_empty() { key } else { format!("{}.{}", path, key) }
+                };
+                // a value predicate leaves out the table holding the key it holds for, e.g. a
+                // dependency with `optional = true`, and at the top level the key itself
+                if path != ROOT
+                    && table.iter().any(|(key, val)| {
+                        self.patterns.excludes_by_value(&child_path(key), val)
+                    })
+                {
+                    return;
+                }
+                self.fields.push(field);
+                for (key, val) in table.iter() {
+                    let new_path = child_path(key);
+                    if path == ROOT && self.patterns.excludes_by_value(&new_path, val) {
+                        continue;
+                    }
+                    self.extract_matched_paths_from_value(val, &new_path, field_idx);
+                }
+            },
+            Value::Array(arr) if is_table_array(arr) => {
+                // arrays of tables (`[[bin]]`) recurse per element, which are named by their
+                // index with a leading underscore to make them valid idents (`bin._0`, `bin._1`)
+                self.fields.push(field);
+                for (i, val) in arr.iter().enumerate() {
+                    let new_path = format!("{}._{}", path, i);
+                    self.extract_matched_paths_from_value(val, &new_path, field_idx);
+                }
+            },
+            _ => {
+                // NOTE: this is good for some additional logic we might want to add to actual values (<=> consts)
+                // the elements selected by index are values of their own, `authors[0]` as
+                // `authors_0` next to `authors`
+                if let Value::Array(arr) = value {
+                    let array_path = to_valid_ident(&orig_path);
+                    let indices = self
+                        .patterns
+                        .indices
+                        .iter()
+                        .filter(|(path, _)| *path == array_path)
+                        .map(|(_, index)| *index)
+                        .collect::<Vec<_>>();
+                    for index in indices {
+                        let path = format!("{}_{}", path, index);
+                        let element = TomlField::new(
+                            path.split('.').last().expect("Expected a valid path"),
+                            &path,
+                            arr[index].clone(),
+                            Some(parent_idx),
+                        )
+                        .with_toml_path(&format!("{}[{}]", orig_path, index));
+                        self.push_leaf(element, false);
+                    }
+                }
+                self.push_leaf(field, is_alias && explicit);
+                for alias in extra_aliases {
+                    let path = to_valid_ident(&rename_last_segment(_path, &alias));
+                    let field = TomlField::new(
+                        path.split('.')
+                            .last()
+                            .expect("Expected a valid path to extract name from"),
+                        &path,
+                        value.clone(),
+                        Some(parent_idx),
+                    )
+                    .with_alias(&alias)
+                    .with_toml_path(&orig_path);
+                    self.push_leaf(field, explicit);
+                }
+            },
+        }
+    }
+
+    /// Pushes a leaf field if its path is included and not excluded, or if it's aliased, in
+    /// which case the alias brings it in by name regardless.
+    fn push_leaf(&mut self, mut field: TomlField, is_alias: bool) {
+        let glob_path = to_glob_path(&field.path);
+        // the matches of templated aliases are excluded by what they were, too
+        let excluded = self.patterns.is_excluded(&field.path)
+            || field
+                .toml_path
+                .as_deref()
+                .is_some_and(|path| self.patterns.is_excluded(&to_valid_ident(path)));
+        let mut skip = !((field.path == ROOT)
+            || (self.patterns.is_included(&field.path, &glob_path) && !excluded));
+        if is_alias && skip {
+            field.path = field.name.clone();
+            skip = false;
+        }
+        if !skip {
+            // first annotated pattern matching this leaf wins
+            field.annotation = self
+                .annotations
+                .iter()
+                .find(|(globs, _)| globs.is_match(&glob_path))
+                .map(|(_, annotation)| annotation.clone());
+            // println!("    >> Pushed field `{}` (path: {})", &field.name, &field.path);
+            self.fields.push(field);
+        } else {
+            // println!("    >> Skipping field: {}", field.path);
+        }
+    }
+}
+
+// looks up the comment of a field by its path, or by the path in the toml, and in kebab-case
+// too, with `suffix` appended to the path (see `comments::element_comment_key`)
+fn find_comment<'c>(
+    comments: &'c BTreeMap<String, String>,
+    field: &TomlField,
+    suffix: &str,
+) -> Option<&'c String> {
+    std::iter::once(&field.path)
+        .chain(field.toml_path.as_ref())
+        .map(|path| format!("{}{}", path, suffix))
+        .flat_map(|path| {
+            let kebab = snake_to_kebab(&path);
+            [path, kebab]
+        })
+        .find_map(|path| comments.get(&path))
+}
+
+impl From<Value> for TomlFields {
+    fn from(value: Value) -> Self {
+        TomlFields::new().with_root(value)
+    }
+}
+
+impl ToTokens for TomlFields {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        self.generate_modules(tokens);
+    }
+}
+
+impl TomlFields {
+    /// Generates modules from the fields collection.
+    ///
+    /// Starts the code generation process from the root field.
+    fn generate_modules(&self, tokens: &mut TokenStream2) {
+        // start from root
+        self.generate_module(0, tokens);
+    }
+
+    /// Generates a single module from a field and its children.
+    ///
+    /// Recursively generates modules for table fields and constants for value fields.
+    /// The structure of the generated code reflects the effective module paths
+    /// derived from the TOML structure and the applied patterns.
+    ///
+    /// With `#flatten-exports`, each module also re-exports the items of its submodules with
+    /// `pub use self::<child>::*;`, and items that would collide when flattened are reported.
+    /// With `#count`, each module gets a `__COUNT` of the value consts in it and below it.
+    ///
+    /// # Returns
+    /// The names the module exports, as `(kind, name, field)`, for the parent to flatten, and
+    /// the number of value consts in the module and its submodules.
+    fn generate_module(
+        &self,
+        idx: usize,
+        tokens: &mut TokenStream2,
+    ) -> (Vec<(&'static str, String, TomlField)>, usize) {
+        // get module name (last component of path)
+        let module_name = self
+            .get_field(idx)
+            .expect("Expected a valid index to an existing field")
+            .module_key();
+
+        let mod_ident: Option<syn::Ident> = if !module_name.is_empty() {
+            Some(format_ident!("{}", self.module_name(&module_name)))
+        } else {
+            None
+        };
+
+        let mut mod_tokens = TokenStream2::new();
+        let relative_children_fields_iter = self.get_relative_children_of(idx).fields;
+
+        // items are emitted sorted by name, since the order the fields were found in depends
+        // on aliases, groups and merged sources; the sort is stable so collisions still point
+        // at the same field as the first one. With `preserve-order` the fields are found in the
+        // order the file has them, which is kept instead
+        let mut const_fields = relative_children_fields_iter
+            .iter()
+            .filter(|f| !f.is_table())
+            .collect::<Vec<_>>();
+        let mut submod_fields = relative_children_fields_iter
+            .iter()
+            .filter(|f| f.is_table())
+            .collect::<Vec<_>>();
+        if !cfg!(feature = "preserve-order") {
+            const_fields.sort_by_cached_key(|f| self.const_name(&f.name));
+            submod_fields.sort_by_cached_key(|f| self.module_name(&f.module_key()));
+        }
+
+        // add constants for this module
+        let mut consts: BTreeMap<String, &TomlField> = BTreeMap::new();
+        // the consts emitted next to a value, like `<NAME>_DOC`, can collide with the others too
+        let mut companions: HashMap<String, &TomlField> = HashMap::new();
+        for field in const_fields {
+            let const_name = self.const_name(&field.name);
+            let names = self.companion_names(field, &const_name);
+            let taken = std::iter::once(&const_name).chain(&names).find_map(|name| {
+                let first = consts.get(name).or_else(|| companions.get(name))?;
+                Some((name, *first))
+            });
+            if let Some((name, first)) = taken {
+                mod_tokens.extend(Self::collision_error("const", name, first, field));
+                continue;
+            }
+            companions.extend(names.into_iter().map(|name| (name, field)));
+            consts.insert(const_name, field);
+            let const_tokens = self.generate_const(field, "", &mut mod_tokens);
+            mod_tokens.extend(const_tokens);
+        }
+        if idx == 0 {
+            mod_tokens.extend(self.generate_string_pool());
+            mod_tokens.extend(self.generate_entries(&relative_children_fields_iter));
+            mod_tokens.extend(self.generate_lookup_fn(&relative_children_fields_iter));
+            #[cfg(feature = "phf")]
+            if self.options.as_phf {
+                mod_tokens.extend(self.generate_phf_map(&consts));
+            }
+            #[cfg(feature = "bitflags")]
+            if self.options.as_bitflags {
+                mod_tokens.extend(self.generate_bitflags(&consts));
+            }
+        }
+
+        // generate submodules for recursive hierarchy
+        let mut submods: BTreeMap<String, &TomlField> = BTreeMap::new();
+        let mut flattened: Vec<(&'static str, String, TomlField)> = Vec::new();
+        let mut count = consts.len();
+        for submod in submod_fields {
+            // println!("    >> Generating submodule {} for: {}", submod.name, module_name);
+            let mut submod_tokens = TokenStream2::new();
+            let (submod_exports, submod_count) = self.generate_module(
+                self.index_of(submod)
+                    .expect("Expected a valid child that exists and thus has an index"),
+                &mut submod_tokens,
+            );
+            // only modules that actually get generated can collide
+            if submod_tokens.is_empty() {
+                continue;
+            }
+            let submod_name = self.module_name(&submod.module_key());
+            if let Some(first) = submods.get(&submod_name) {
+                mod_tokens.extend(Self::collision_error("module", &submod_name, first, submod));
+                continue;
+            }
+            if self.options.flatten_exports {
+                let submod_ident = format_ident!("{}", submod_name);
+                let vis = self.options.item_visibility();
+                submod_tokens.extend(quote! {
+                    #vis use self::#submod_ident::*;
+                });
+                flattened.extend(submod_exports);
+            }
+            submods.insert(submod_name, submod);
+            mod_tokens.extend(submod_tokens);
+            count += submod_count;
+        }
+        if self.options.count && !mod_tokens.is_empty() {
+            mod_tokens.extend(Self::generate_count(count, &self.options.item_visibility()));
+        }
+
+        // local items would silently shadow flattened ones, and flattened ones would be
+        // ambiguous among themselves, so both are pointed out instead
+        let mut exports: Vec<(&'static str, String, TomlField)> = consts
+            .iter()
+            .map(|(name, field)| ("const", name.clone(), (*field).clone()))
+            .chain(
+                submods
+                    .iter()
+                    .map(|(name, field)| ("module", name.clone(), (*field).clone())),
+            )
+            .collect();
+        for (kind, name, field) in flattened {
+            match exports.iter().find(|(k, n, _)| *k == kind && *n == name) {
+                Some((_, _, first)) => {
+                    mod_tokens.extend(Self::collision_error(kind, &name, first, &field))
+                },
+                None => exports.push((kind, name, field)),
+            }
+        }
+
+        if !mod_tokens.is_empty() {
+            tokens.extend(if let Some(_mod_ident) = mod_ident {
+                let field = self
+                    .get_field(idx)
+                    .expect("Expected this to be a valid field");
+                let (comment, inner_comment) = if self.options.inner_docs {
+                    (quote! {}, get_inner_doc_comment(field))
+                } else {
+                    (get_doc_comment(field), quote! {})
+                };
+                let allow_case = if self.options.keep_case {
+                    quote! { #[allow(non_snake_case)] }
+                } else {
+                    quote! {}
+                };
+                let vis = self.options.item_visibility();
+                let attrs = self.options.extra_attrs();
+                quote! {
+                    #comment
+                    #allow_case
+                    #attrs
+                    #vis mod #_mod_ident {
+                        #inner_comment
+                        #mod_tokens
+                    }
+                }
+            } else {
+                quote! {
+                    #mod_tokens
+                }
+            });
+        }
+        (exports, count)
+    }
+
+    /// Generates the `__COUNT` const for `#count`.
+    fn generate_count(count: usize, vis: &TokenStream2) -> TokenStream2 {
+        quote! {
+            /// The number of values bound here, including those in nested items
+            #vis const __COUNT: usize = #count;
+        }
+    }
+
+    /// Reports two fields that would generate items with the same name in the same module.
+    ///
+    /// Without this, rustc would report a duplicate definition somewhere in the macro output,
+    /// which doesn't tell which TOML keys are to blame.
+    fn collision_error(
+        kind: &str,
+        name: &str,
+        first: &TomlField,
+        second: &TomlField,
+    ) -> TokenStream2 {
+        let source = |field: &TomlField| field.toml_path.clone().unwrap_or(field.path.clone());
+        syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!(
+                "`{}` and `{}` both generate the {} `{}`, alias one of them to tell them apart",
+                source(first),
+                source(second),
+                kind,
+                name
+            ),
+        )
+        .to_compile_error()
+    }
+
+    /// Turns a TOML key into a module name, lowercased unless the section keeps case.
+    fn module_name(&self, key: &str) -> String {
+        if self.options.keep_case {
+            to_valid_ident(key)
+        } else {
+            to_valid_ident(key).to_lowercase()
+        }
+    }
+
+    /// Turns a TOML key into a const name, uppercased unless the section keeps case.
+    fn const_name(&self, key: &str) -> String {
+        if self.options.keep_case {
+            to_valid_ident(key)
+        } else {
+            to_valid_ident(key).to_uppercase()
+        }
+    }
+
+    /// Allows `non_upper_case_globals` on a const whose name `keep_case` left with lowercase
+    /// letters, so the lint still applies to the rest of the caller's consts.
+    fn allow_case(const_name: &str) -> TokenStream2 {
+        if const_name.chars().any(char::is_lowercase) {
+            quote! { #[allow(non_upper_case_globals)] }
+        } else {
+            quote! {}
+        }
+    }
+
+    /// Creates an error about a field pointing at the pattern that matched it, or at the whole
+    /// macro call for fields no pattern matched as written, like those of subtree aliases.
+    fn field_error(&self, field: &TomlField, message: impl std::fmt::Display) -> syn::Error {
+        let glob_path = to_glob_path(field.toml_path.as_deref().unwrap_or(&field.path));
+        match self.patterns.matchers.iter().find(|(glob, _)| glob.is_match(&glob_path)) {
+            Some((_, pattern)) => pattern.error(message),
+            None => syn::Error::new(proc_macro2::Span::call_site(), message),
+        }
+    }
+
+    /// Checks whether any of the bound value fields matches the given pattern.
+    pub fn matches_any(&self, pattern: &Pattern) -> bool {
+        self.matching(pattern).next().is_some()
+    }
+
+    /// The bound value fields the given pattern matches.
+    pub fn matching<'f>(&'f self, pattern: &Pattern) -> impl Iterator<Item = &'f TomlField> {
+        let alternatives = pattern
+            .alternatives()
+            .into_iter()
+            .map(|alternative| (alternative.to_glob().compile_matcher(), alternative))
+            .collect::<Vec<_>>();
+        self.fields
+            .iter()
+            .filter(|f| !f.is_table() && f.path != ROOT)
+            .filter(move |f| {
+                let glob_path = to_glob_path(&f.path);
+                alternatives
+                    .iter()
+                    .any(|(glob, alt)| glob.is_match(&glob_path) && alt.matches_depth(&f.path))
+            })
+    }
+
+    /// The keys a field's items are named after, as they are in the TOML, for `raw_idents`.
+    ///
+    /// These are the field's own key unless it's aliased, and the keys of the tables that
+    /// become the modules it's in, leaving out the names that don't come from the TOML, like
+    /// aliases and groups.
+    pub fn raw_keys(&self, field: &TomlField) -> Vec<String> {
+        let toml_key = |field: &TomlField| {
+            let path = field.toml_path.as_deref().unwrap_or(&field.path);
+            path.rsplit('.').next().unwrap_or(path).trim_matches('"').to_string()
+        };
+        let mut keys = Vec::new();
+        if field.alias.is_none() {
+            keys.push(toml_key(field));
+        }
+        let module_path = field.effective_module_path();
+        let modules = &module_path[..module_path.len().saturating_sub(1)];
+        let mut ancestor = field.parent;
+        while let Some(table) = ancestor.and_then(|idx| self.get_field(idx)) {
+            if table.path == ROOT {
+                break;
+            }
+            let key = toml_key(table);
+            if modules.contains(&to_valid_ident(&key)) {
+                keys.push(key);
+            }
+            ancestor = table.parent;
+        }
+        keys
+    }
+
+    /// Computes a deterministic checksum over the bound value fields.
+    ///
+    /// Only the fields selected by the patterns are hashed (by path and value, in path order),
+    /// so the checksum changes exactly when something the section binds changes.
+    /// Uses 64-bit FNV-1a, which is stable across compiler versions unlike `DefaultHasher`.
+    pub fn checksum(&self) -> u64 {
+        let mut values = self
+            .fields
+            .iter()
+            .filter(|f| !f.is_table() && f.path != ROOT)
+            .map(|f| (f.path.as_str(), f.value.to_string()))
+            .collect::<Vec<_>>();
+        values.sort();
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for (path, value) in values {
+            // the zero bytes keep e.g. `ab` + `c` apart from `a` + `bc`
+            for byte in path.bytes().chain([0]).chain(value.bytes()).chain([0]) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        hash
+    }
+
+    /// Converts a single value field into what it's bound as, shared by consts and the fields
+    /// of `derive(..)` structs.
+    ///
+    /// A field made up by `select_os` picks its value by the target OS instead of binding it.
+    /// Items the value depends on, like the enum of an `enum` annotated field, go to `items`,
+    /// with `prefix` prepended to their names. With `strict_types`, a value that has no typed
+    /// constant is an error rather than bound as a string.
+    fn bind_value(
+        &self,
+        field: &TomlField,
+        prefix: &str,
+        items: &mut TokenStream2,
+    ) -> SynResult<BoundValue> {
+        if self.options.strict_types {
+            try_convert_value_to_tokens(&field.value).map_err(|what| {
+                let msg = format!(
+                    "`{}` is {}, which `strict_types` doesn't bind as a string",
+                    field.toml_path.as_deref().unwrap_or(&field.path),
+                    what
+                );
+                self.field_error(field, msg)
+            })?;
+        }
+        let mut os_cfg = quote! {};
+        let os_select = self
+            .os_selects
+            .get(field.toml_path.as_deref().unwrap_or(&field.path));
+        let converted = match (os_select, &field.annotation) {
+            (Some(select), annotation) => {
+                select.generate(annotation.as_ref()).map(|(ty, val, cfg)| {
+                    os_cfg = cfg;
+                    (ty, val)
+                })
+            },
+            (
+                None,
+                Some(
+                    annotation @ Annotation {
+                        kind:
+                            AnnotationKind::Enum {
+                                non_exhaustive,
+                                variants,
+                            },
+                        ..
+                    },
+                ),
+            ) => self.generate_enum(field, prefix, *non_exhaustive).and_then(|(ident, tokens)| {
+                items.extend(tokens);
+                if variants.is_empty() {
+                    return Ok((quote! { &'static [#ident] }, quote! { #ident::ALL }));
+                }
+                let variant = annotation.enum_value(&field.value).map_err(|msg| {
+                    syn::Error::new(
+                        annotation.span(),
+                        format!("Can't bind value as `{}`: {}", annotation, msg),
+                    )
+                })?;
+                Ok((quote! { #ident }, quote! { #ident::#variant }))
+            }),
+            (None, None) if self.pooled_string(field).is_some() => Ok((
+                quote! { &'static str },
+                self.pooled_string(field).expect("Expected a pooled string"),
+            )),
+            (None, None) if self.options.narrow_ints => {
+                Ok(convert_value_to_narrowed_tokens(&field.value))
+            },
+            (None, _) => convert_annotated_value_to_tokens(&field.value, field.annotation.as_ref()),
+        };
+        let (ty, val) = converted?;
+        let mut comment = get_doc_comment(field);
+        if self.options.env_override {
+            let var = env_var_name(field.toml_path.as_deref().unwrap_or(&field.path));
+            // `option_env!` has rustc track the variable, so setting or changing it rebuilds
+            items.extend(quote! {
+                const _: ::core::option::Option<&str> = ::core::option_env!(#var);
+            });
+            if std::env::var_os(&var).is_some() {
+                // builds no longer follow the toml alone, so make that visible in the docs
+                let note =
+                    format!("Overridden at compile time by the `{}` environment variable", var);
+                comment.extend(quote! {
+                    #[doc = ""]
+                    #[doc = #note]
+                });
+            }
+        }
+        if let Some(select) = os_select {
+            let note = format!("Selected by the target OS from `{}`", select.source);
+            comment.extend(quote! {
+                #[doc = ""]
+                #[doc = #note]
+            });
+        }
+        if self.options.provenance_docs {
+            comment.extend(self.provenance_doc(field));
+        }
+        Ok(BoundValue {
+            ty,
+            val,
+            cfg: os_cfg,
+            comment,
+        })
+    }
+
+    /// The `provenance_docs` footer of a value's docs, naming the key it's from and the files
+    /// it was read from, or just the key when the TOML didn't come from files.
+    fn provenance_doc(&self, field: &TomlField) -> TokenStream2 {
+        let key = field.toml_path.as_deref().unwrap_or(&field.path);
+        let note = if self.source_files.is_empty() {
+            format!("Source: `{}`", key)
+        } else {
+            let files = self
+                .source_files
+                .iter()
+                .map(|file| format!("`{}`", file))
+                .collect::<Vec<_>>();
+            format!("Source: `{}` in {}", key, files.join(", "))
+        };
+        quote! {
+            #[doc = ""]
+            #[doc = #note]
+        }
+    }
+
+    /// Whether `generate_const` emits a `<NAME>_LEN` next to the field's const.
+    fn has_len_const(&self, field: &TomlField) -> bool {
+        // the values of a `select_os` differ by target, so its length isn't known here
+        let os_selected = self
+            .os_selects
+            .contains_key(field.toml_path.as_deref().unwrap_or(&field.path));
+        self.options.array_lens && field.value.is_array() && !os_selected
+    }
+
+    /// The names of the consts `generate_const` emits next to the field's own `const_name`.
+    fn companion_names(&self, field: &TomlField, const_name: &str) -> Vec<String> {
+        let mut names = Vec::new();
+        if self.options.with_original_names {
+            names.push(format!("{}_ORIGINAL", const_name));
+        }
+        if self.options.with_docs {
+            names.push(format!("{}_DOC", const_name));
+        }
+        if self.has_len_const(field) {
+            names.push(format!("{}_LEN", const_name));
+        }
+        names
+    }
+
+    /// Generates a `pub const` for a single value field.
+    ///
+    /// Emits a compile error instead if the value can't be bound as its annotated type.
+    /// With `#with-original-names`, also emits `<NAME>_ORIGINAL` holding the exact TOML key,
+    /// and with `#with-docs`, `<NAME>_DOC` holding the field's TOML comment.
+    /// Items the const depends on go to `items`, see `bind_value`.
+    fn generate_const(
+        &self,
+        field: &TomlField,
+        prefix: &str,
+        items: &mut TokenStream2,
+    ) -> TokenStream2 {
+        let BoundValue {
+            ty,
+            val,
+            cfg: os_cfg,
+            comment,
+        } = match self.bind_value(field, prefix, items) {
+            Ok(bound) => bound,
+            Err(err) => return err.to_compile_error(),
+        };
+        let const_name = format_ident!("{}", self.const_name(&field.name));
+        let vis = self.options.item_visibility();
+        let allow_case = Self::allow_case(&const_name.to_string());
+        let original_name = if self.options.with_original_names {
+            let original_const_name = format_ident!("{}_ORIGINAL", const_name);
+            let original = field
+                .toml_path
+                .as_deref()
+                .and_then(|path| path.split('.').last())
+                .unwrap_or(&field.name);
+            let doc = format!("The original TOML key of `{}`", const_name);
+            quote! {
+                #[doc = #doc]
+                #allow_case
+                #vis const #original_const_name: &str = #original;
+            }
+        } else {
+            quote! {}
+        };
+        let docs = if self.options.with_docs {
+            let docs_const_name = format_ident!("{}_DOC", const_name);
+            let text = field.comment.as_deref().unwrap_or_default();
+            let doc = format!("The TOML comment of `{}`, empty if it has none", const_name);
+            quote! {
+                #[doc = #doc]
+                #allow_case
+                #vis const #docs_const_name: &str = #text;
+            }
+        } else {
+            quote! {}
+        };
+        let len = match &field.value {
+            Value::Array(arr) if self.has_len_const(field) => {
+                let len_const_name = format_ident!("{}_LEN", const_name);
+                let len = arr.len();
+                let doc = format!("The number of elements in `{}`", const_name);
+                quote! {
+                    #[doc = #doc]
+                    #allow_case
+                    #vis const #len_const_name: usize = #len;
+                }
+            },
+            _ => quote! {},
+        };
+        let attrs = self.options.extra_attrs();
+        let is_path = field
+            .annotation
+            .as_ref()
+            .is_some_and(|annotation| annotation.kind == AnnotationKind::Path);
+        let owned_fn = if self.options.as_fns || is_path {
+            let fn_name = to_snake_case(&field.name);
+            if const_name == fn_name {
+                let what = if self.options.as_fns { "`as_fns`" } else { "`path`" };
+                return self
+                    .field_error(
+                        field,
+                        format!(
+                            "{} can't add a fn `{}` next to the const of the same name, which \
+                             `keep_case` gives it",
+                            what, fn_name
+                        ),
+                    )
+                    .to_compile_error();
+            }
+            let fn_ident = to_fn_ident(&fn_name);
+            // struct consts are associated ones
+            let bound = if prefix.is_empty() {
+                quote! { #const_name }
+            } else {
+                quote! { Self::#const_name }
+            };
+            let (fn_ty, fn_val, doc) = match (self.options.as_fns, is_path) {
+                // an owned copy of a path is a `PathBuf`
+                (true, true) => (
+                    quote! { ::std::path::PathBuf },
+                    quote! { ::std::path::PathBuf::from(#bound) },
+                    format!("An owned copy of `{}`, as a path", const_name),
+                ),
+                (true, false) => {
+                    let (owned_ty, owned_val) = match syn::parse2::<syn::Type>(ty.clone()) {
+                        Ok(ty) => to_owned_tokens(&ty, bound),
+                        Err(err) => return err.to_compile_error(),
+                    };
+                    (owned_ty, owned_val, format!("An owned copy of `{}`", const_name))
+                },
+                (false, _) => (
+                    quote! { &'static ::std::path::Path },
+                    quote! { ::std::path::Path::new(#bound) },
+                    format!("`{}` as a path", const_name),
+                ),
+            };
+            quote! {
+                #[doc = #doc]
+                #os_cfg
+                #attrs
+                #vis fn #fn_ident() -> #fn_ty {
+                    #fn_val
+                }
+            }
+        } else {
+            quote! {}
+        };
+        quote! {
+            #comment
+            #allow_case
+            #os_cfg
+            #attrs
+            #vis const #const_name: #ty = #val;
+            #original_name
+            #docs
+            #len
+            #owned_fn
+        }
+    }
+
+    /// Generates the `entries()` fn for `as_map`, listing the given value fields.
+    ///
+    /// Each entry pairs the TOML key with the value as a string, in key order, so a section can be
+    /// iterated at runtime without knowing its keys. Only the direct values are listed; tables
+    /// are left out.
+    fn generate_entries(&self, fields: &[TomlField]) -> TokenStream2 {
+        if !self.options.as_map {
+            return quote! {};
+        }
+        let entries = Self::string_entries(fields);
+        if entries.is_empty() {
+            return quote! {};
+        }
+        let keys = entries.iter().map(|(key, _)| key);
+        let values = entries.iter().map(|(_, value)| value);
+        let vis = self.options.item_visibility();
+        quote! {
+            /// The values of this section as `(key, value)` pairs, with the values as strings
+            #vis fn entries() -> &'static [(&'static str, &'static str)] {
+                &[#((#keys, #values)),*]
+            }
+        }
+    }
+
+    /// Generates the `get()` fn for `lookup_fn`, a `match` from the TOML keys of the given
+    /// value fields to the values as strings, the same pairs `entries()` lists.
+    ///
+    /// Unlike `entries()` a section without values still gets the fn, so calling it doesn't
+    /// depend on what the TOML happens to have.
+    fn generate_lookup_fn(&self, fields: &[TomlField]) -> TokenStream2 {
+        if !self.options.lookup_fn {
+            return quote! {};
+        }
+        let entries = Self::string_entries(fields);
+        let keys = entries.iter().map(|(key, _)| key);
+        let values = entries.iter().map(|(_, value)| value);
+        let vis = self.options.item_visibility();
+        quote! {
+            /// The value of the given TOML key in this section as a string, if it has one
+            #vis fn get(key: &str) -> ::core::option::Option<&'static str> {
+                match key {
+                    #(#keys => ::core::option::Option::Some(#values),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    }
+
+    /// The direct values among the given fields as their TOML keys and stringified values,
+    /// sorted by key.
+    fn string_entries(fields: &[TomlField]) -> Vec<(String, TokenStream2)> {
+        let mut entries = fields
+            .iter()
+            .filter(|f| !f.is_table())
+            .map(|f| {
+                let key = f
+                    .toml_path
+                    .as_deref()
+                    .and_then(|path| path.split('.').last())
+                    .unwrap_or(&f.name)
+                    .to_string();
+                (key, value_to_string_token(&f.value))
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+
+    /// Generates the `MAP` static for `as_phf`, a `phf::Map` from the TOML keys of the given
+    /// consts to their values, built with `phf_codegen` so lookups don't hash at runtime.
+    ///
+    /// The map has a single value type, so the consts must all bind to the same type, and a
+    /// `select_os` value differs by target so it can't be in the map at all. Nothing is
+    /// emitted for a section without values.
+    #[cfg(feature = "phf")]
+    fn generate_phf_map(&self, consts: &BTreeMap<String, &TomlField>) -> TokenStream2 {
+        let error = |msg: String| {
+            syn::Error::new(proc_macro2::Span::call_site(), msg).to_compile_error()
+        };
+        let mut map = phf_codegen::Map::new();
+        map.phf_path("::phf");
+        let mut keys: BTreeMap<&str, &TomlField> = BTreeMap::new();
+        let mut map_ty: Option<(String, TokenStream2, &TomlField)> = None;
+        for (const_name, field) in consts {
+            let source = field.toml_path.as_deref().unwrap_or(&field.path);
+            if self.os_selects.contains_key(source) {
+                let msg = format!("`{}` is bound by target, so it can't be in `as_phf`", source);
+                return error(msg);
+            }
+            let key = source.split('.').last().unwrap_or(&field.name);
+            if let Some(first) = keys.insert(key, field) {
+                return error(format!(
+                    "`{}` and `{}` both have the key `{}`, so they can't both be in `as_phf`",
+                    first.toml_path.as_deref().unwrap_or(&first.path),
+                    source,
+                    key
+                ));
+            }
+            let ty = match self.bind_value(field, "", &mut TokenStream2::new()) {
+                Ok(bound) => bound.ty,
+                Err(err) => return err.to_compile_error(),
+            };
+            match &map_ty {
+                Some((first_ty, _, first)) if *first_ty != ty.to_string() => {
+                    return error(format!(
+                        "`as_phf` needs values of one type, but `{}` is `{}` and `{}` is `{}`",
+                        first.toml_path.as_deref().unwrap_or(&first.path),
+                        first_ty,
+                        source,
+                        ty
+                    ));
+                },
+                Some(_) => {},
+                None => map_ty = Some((ty.to_string(), ty, field)),
+            }
+            map.entry(key, const_name);
+        }
+        let Some((_, ty, _)) = map_ty else {
+            return quote! {};
+        };
+        let built: TokenStream2 = map
+            .build()
+            .to_string()
+            .parse()
+            .expect("Expected phf_codegen to generate valid tokens");
+        let vis = self.options.item_visibility();
+        quote! {
+            /// The values of this section by their TOML keys, as a perfect hash map
+            #vis static MAP: ::phf::Map<&'static str, #ty> = #built;
+        }
+    }
+
+    /// Generates the `Flags` struct for `as_bitflags`, with a flag per given const named like it,
+    /// and the `ENABLED` const of the flags whose values are `true`.
+    ///
+    /// The bits are the smallest unsigned integer that has one for each flag, so there can be at
+    /// most 64 of them. The values must all be plain bools; one bound by target differs by
+    /// target, so it can't be a flag. Nothing is emitted for a section without values.
+    #[cfg(feature = "bitflags")]
+    fn generate_bitflags(&self, consts: &BTreeMap<String, &TomlField>) -> TokenStream2 {
+        let error = |msg: String| {
+            syn::Error::new(proc_macro2::Span::call_site(), msg).to_compile_error()
+        };
+        if consts.is_empty() {
+            return quote! {};
+        }
+        let bits = match consts.len() {
+            0..=8 => quote! { u8 },
+            9..=16 => quote! { u16 },
+            17..=32 => quote! { u32 },
+            33..=64 => quote! { u64 },
+            len => {
+                return error(format!("`as_bitflags` has room for 64 flags, but got {}", len));
+            },
+        };
+        if let Some(field) = consts.get("ENABLED") {
+            return error(format!(
+                "`{}` is bound as `ENABLED`, which `as_bitflags` binds the enabled flags as",
+                field.toml_path.as_deref().unwrap_or(&field.path)
+            ));
+        }
+        let mut flags = Vec::new();
+        let mut enabled = Vec::new();
+        for (i, (const_name, field)) in consts.iter().enumerate() {
+            let source = field.toml_path.as_deref().unwrap_or(&field.path);
+            if self.os_selects.contains_key(source) {
+                let msg =
+                    format!("`{}` is bound by target, so it can't be in `as_bitflags`", source);
+                return error(msg);
+            }
+            let Some(set) = field.value.as_bool() else {
+                return error(format!(
+                    "`as_bitflags` needs values that are `true` or `false`, but `{}` is of type {}",
+                    source,
+                    field.value.type_str()
+                ));
+            };
+            let flag = format_ident!("{}", const_name);
+            let shift = proc_macro2::Literal::usize_unsuffixed(i);
+            flags.push(quote! { const #flag = 1 << #shift; });
+            if set {
+                enabled.push(flag);
+            }
+        }
+        let vis = self.options.item_visibility();
+        quote! {
+            ::bitflags::bitflags! {
+                /// The values of this section as flags, one per TOML key
+                #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+                #vis struct Flags: #bits {
+                    #(#flags)*
+                }
+            }
+            /// The flags whose values are `true` in the TOML
+            #vis const ENABLED: Flags = Flags::empty()#(.union(Flags::#enabled))*;
+        }
+    }
+
+    /// Generates the enum for an `enum` annotated array of strings, or for a string annotated
+    /// with the variants to choose from.
+    ///
+    /// The enum is named after the field in PascalCase (prefixed with `prefix`), and has a variant
+    /// per string along with `ALL`, `as_str`, `Display` and `FromStr`, so the strings can be
+    /// matched on and round-tripped. With `non_exhaustive`, the enum is marked
+    /// `#[non_exhaustive]` so that adding strings later doesn't break downstream `match`es.
+    /// Listed variants are their own strings, which `FromStr` matches case-insensitively like
+    /// the value itself is matched.
+    ///
+    /// # Returns
+    /// The enum's identifier and the tokens for it and its impls.
+    fn generate_enum(
+        &self,
+        field: &TomlField,
+        prefix: &str,
+        non_exhaustive: bool,
+    ) -> SynResult<(syn::Ident, TokenStream2)> {
+        let annotation = field
+            .annotation
+            .as_ref()
+            .expect("Expected an enum annotation");
+        let variants = annotation.enum_variants(&field.value).map_err(|msg| {
+            syn::Error::new(
+                annotation.span(),
+                format!("Can't bind value as `{}`: {}", annotation, msg),
+            )
+        })?;
+        let enum_name = snake_to_pascal(&to_valid_ident(&field.name));
+        let enum_ident = format_ident!("{}{}", prefix, enum_name);
+        let strings = variants.iter().map(|(s, _)| s).collect::<Vec<_>>();
+        let idents = variants.iter().map(|(_, v)| v).collect::<Vec<_>>();
+        let docs = strings.iter().map(|s| format!("`{}`", s));
+        let comment = get_doc_comment(field);
+        let vis = self.options.item_visibility();
+        let non_exhaustive = if non_exhaustive {
+            quote! { #[non_exhaustive] }
+        } else {
+            quote! {}
+        };
+        let expected = strings
+            .iter()
+            .map(|s| format!("`{}`", s))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let unknown = quote! {
+            ::core::result::Result::Err(::std::format!(
+                "unknown variant `{}`, expected one of {}",
+                s,
+                #expected
+            ))
+        };
+        let from_str = if matches!(
+            annotation.kind,
+            AnnotationKind::Enum { ref variants, .. } if !variants.is_empty()
+        ) {
+            // the same forms `Annotation::enum_value` accepts, where dropping the separators
+            // is what `snake_to_pascal` does to the words once the case is ignored
+            quote! {
+                let pascal = s
+                    .chars()
+                    .filter(|c| c.is_alphanumeric() || *c == '.')
+                    .collect::<::std::string::String>();
+                #(if s.eq_ignore_ascii_case(#strings) || pascal.eq_ignore_ascii_case(#strings) {
+                    return ::core::result::Result::Ok(#enum_ident::#idents);
+                })*
+                #unknown
+            }
+        } else {
+            quote! {
+                match s {
+                    #(#strings => ::core::result::Result::Ok(#enum_ident::#idents),)*
+                    _ => #unknown,
+                }
+            }
+        };
+        let tokens = quote! {
+            #comment
+            #non_exhaustive
+            #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+            #vis enum #enum_ident {
+                #(#[doc = #docs] #idents,)*
+            }
+
+            impl #enum_ident {
+                /// All the variants, in the order they're listed in
+                pub const ALL: &'static [#enum_ident] = &[#(#enum_ident::#idents),*];
+
+                /// The string this variant was generated from
+                pub const fn as_str(&self) -> &'static str {
+                    match *self {
+                        #(#enum_ident::#idents => #strings,)*
+                    }
+                }
+            }
+
+            impl ::core::fmt::Display for #enum_ident {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+
+            impl ::core::str::FromStr for #enum_ident {
+                type Err = ::std::string::String;
+
+                fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                    #from_str
+                }
+            }
+        };
+        Ok((enum_ident, tokens))
+    }
+
+    /// Generates a zero-sized struct from a field and its children.
+    ///
+    /// Value fields become associated consts, and tables become nested structs named after
+    /// the parent struct and the table (e.g. `Settings` and `network` make `SettingsNetwork`),
+    /// which are also reachable through an associated const on the parent.
+    /// Unlike modules, the root struct is emitted even when it ends up empty,
+    /// since the name was asked for explicitly.
+    ///
+    /// # Returns
+    /// The number of value consts in the struct and its nested structs, as for `#count`.
+    pub fn generate_struct(
+        &self,
+        idx: usize,
+        struct_ident: &syn::Ident,
+        tokens: &mut TokenStream2,
+    ) -> usize {
+        let mut consts = TokenStream2::new();
+        let mut nested = TokenStream2::new();
+        let relative_children_fields_iter = self.get_relative_children_of(idx).fields;
+        // associated consts for values and substructs share a namespace
+        let mut const_names: HashMap<String, &TomlField> = HashMap::new();
+        let mut companions: HashMap<String, &TomlField> = HashMap::new();
+
+        for field in relative_children_fields_iter
+            .iter()
+            .filter(|f| !f.is_table())
+        {
+            let const_name = self.const_name(&field.name);
+            let names = self.companion_names(field, &const_name);
+            let taken = std::iter::once(&const_name).chain(&names).find_map(|name| {
+                let first = const_names.get(name).or_else(|| companions.get(name))?;
+                Some((name, *first))
+            });
+            if let Some((name, first)) = taken {
+                consts.extend(Self::collision_error("const", name, first, field));
+                continue;
+            }
+            companions.extend(names.into_iter().map(|name| (name, field)));
+            const_names.insert(const_name, field);
+            consts.extend(self.generate_const(field, &struct_ident.to_string(), &mut nested));
+        }
+        let mut count = const_names.len();
+        if idx == 0 {
+            consts.extend(self.generate_entries(&relative_children_fields_iter));
+            consts.extend(self.generate_lookup_fn(&relative_children_fields_iter));
+        }
+
+        for substruct in relative_children_fields_iter
+            .iter()
+            .filter(|f| f.is_table())
+        {
+            let sub_idx = self
+                .index_of(substruct)
+                .expect("Expected a valid child that exists and thus has an index");
+            let name = to_valid_ident(&substruct.module_key());
+            let sub_ident = format_ident!("{}{}", struct_ident, snake_to_pascal(&name));
+            let mut sub_tokens = TokenStream2::new();
+            let sub_count = self.generate_struct(sub_idx, &sub_ident, &mut sub_tokens);
+            if sub_tokens.is_empty() {
+                continue;
+            }
+            let const_name = self.const_name(&name);
+            let taken = const_names.get(&const_name).or_else(|| companions.get(&const_name));
+            if let Some(first) = taken {
+                consts.extend(Self::collision_error("const", &const_name, first, substruct));
+                continue;
+            }
+            count += sub_count;
+            let const_name_ident = format_ident!("{}", const_name);
+            const_names.insert(const_name, substruct);
+            let comment = get_doc_comment(substruct);
+            let allow_case = Self::allow_case(&const_name_ident.to_string());
+            consts.extend(quote! {
+                #comment
+                #allow_case
+                pub const #const_name_ident: #sub_ident = #sub_ident;
+            });
+            nested.extend(sub_tokens);
+        }
+
+        // skip tables that didn't end up with anything in them, like empty modules are
+        if consts.is_empty() && idx != 0 {
+            return 0;
+        }
+        if self.options.count {
+            consts.extend(Self::generate_count(count, &quote! { pub }));
+        }
+
+        let comment = get_doc_comment(
+            self.get_field(idx)
+                .expect("Expected this to be a valid field"),
+        );
+        // the root struct is what the section's visibility applies to, nested ones just follow
+        let vis = if idx == 0 {
+            self.options.root_visibility()
+        } else {
+            self.options.item_visibility()
+        };
+        tokens.extend(quote! {
+            #comment
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+            #vis struct #struct_ident;
+
+            impl #struct_ident {
+                #consts
+            }
+
+            #nested
+        });
+        count
+    }
+
+    /// Binds a value field for a data struct, like `bind_value`, but made deserializable in
+    /// `serde` sections.
+    ///
+    /// The enums of `enum` annotations don't derive `serde`'s traits, so those are errors there.
+    fn bind_serde_value(
+        &self,
+        field: &TomlField,
+        prefix: &str,
+        items: &mut TokenStream2,
+    ) -> SynResult<BoundValue> {
+        if !self.options.serde {
+            return self.bind_value(field, prefix, items);
+        }
+        if let Some(
+            annotation @ Annotation {
+                kind: AnnotationKind::Enum { .. },
+                ..
+            },
+        ) = &field.annotation
+        {
+            return Err(syn::Error::new(
+                annotation.span(),
+                format!(
+                    "`{}` can't be bound in a `serde` section, its enum doesn't derive `serde`'s \
+                     traits",
+                    annotation
+                ),
+            ));
+        }
+        let bound = self.bind_value(field, prefix, items)?;
+        let ty = syn::parse2::<syn::Type>(bound.ty)?;
+        let val = syn::parse2::<syn::Expr>(bound.val)?;
+        let (ty, val) = to_serde_tokens(&ty, &val);
+        Ok(BoundValue { ty, val, ..bound })
+    }
+
+    /// Generates a struct holding the values of a field and its children, for `derive(..)`.
+    ///
+    /// Unlike `generate_struct`, values become snake_case struct fields rather than consts,
+    /// and tables become fields of nested structs named like in `generate_struct`. The listed
+    /// traits are derived for all of them, except `Default`, which is implemented with the TOML
+    /// values since those can't be derived.
+    ///
+    /// With `serde`, `serde`'s traits are derived too, each struct gets a `DEFAULTS` const with
+    /// the TOML values, and `Default` is implemented with them for `#[serde(default)]`, so keys
+    /// missing from what's deserialized keep their TOML values. The fields are renamed back to
+    /// their TOML keys, and borrowed values become `Cow`s, see `utils::to_serde_tokens`.
+    pub fn generate_data_struct(
+        &self,
+        idx: usize,
+        struct_ident: &syn::Ident,
+        derives: &[String],
+        tokens: &mut TokenStream2,
+    ) {
+        let mut decls = TokenStream2::new();
+        let mut inits = TokenStream2::new();
+        let mut nested = TokenStream2::new();
+        let relative_children_fields_iter = self.get_relative_children_of(idx).fields;
+        let mut field_names: HashMap<String, &TomlField> = HashMap::new();
+        let vis = self.options.item_visibility();
+
+        let serde = self.options.serde;
+        for field in relative_children_fields_iter.iter() {
+            let name = self.module_name(&field.name);
+            let (ty, val, cfg, comment) = if field.is_table() {
+                let sub_idx = self
+                    .index_of(field)
+                    .expect("Expected a valid child that exists and thus has an index");
+                let sub_name = to_valid_ident(&field.module_key());
+                let sub_ident = format_ident!("{}{}", struct_ident, snake_to_pascal(&sub_name));
+                let mut sub_tokens = TokenStream2::new();
+                self.generate_data_struct(sub_idx, &sub_ident, derives, &mut sub_tokens);
+                if sub_tokens.is_empty() {
+                    continue;
+                }
+                nested.extend(sub_tokens);
+                // `DEFAULTS` is a const, so it can't call `Default::default()`
+                let val = if serde {
+                    quote! { #sub_ident::DEFAULTS }
+                } else {
+                    quote! { ::core::default::Default::default() }
+                };
+                (quote! { #sub_ident }, val, quote! {}, get_doc_comment(field))
+            } else {
+                match self.bind_serde_value(field, &struct_ident.to_string(), &mut nested) {
+                    Ok(bound) => (bound.ty, bound.val, bound.cfg, bound.comment),
+                    Err(err) => {
+                        nested.extend(err.to_compile_error());
+                        continue;
+                    },
+                }
+            };
+            // errors can't go in the field list, so they go next to the struct
+            if let Some(first) = field_names.get(&name) {
+                nested.extend(Self::collision_error("field", &name, first, field));
+                continue;
+            }
+            field_names.insert(name.clone(), field);
+            // deserializing reads the keys as they're written in the TOML
+            let key = field
+                .toml_path
+                .as_deref()
+                .filter(|_| field.alias.is_none())
+                .and_then(|path| path.split('.').last())
+                .unwrap_or(&field.name);
+            let rename = if serde && key != name {
+                quote! { #[serde(rename = #key)] }
+            } else {
+                quote! {}
+            };
+            let name = format_ident!("{}", name);
+            decls.extend(quote! {
+                #comment
+                #cfg
+                #rename
+                #vis #name: #ty,
+            });
+            inits.extend(quote! {
+                #cfg
+                #name: #val,
+            });
+        }
+
+        // skip tables that didn't end up with anything in them, like empty modules are
+        if decls.is_empty() && idx != 0 {
+            return;
+        }
+
+        let comment = get_doc_comment(
+            self.get_field(idx)
+                .expect("Expected this to be a valid field"),
+        );
+        let vis = if idx == 0 { self.options.root_visibility() } else { vis };
+        let mut derived = derives
+            .iter()
+            .filter(|derive| *derive != "Default")
+            .map(|derive| {
+                derive
+                    .parse::<TokenStream2>()
+                    .expect("Expected a derive to be a valid path")
+            })
+            .collect::<Vec<_>>();
+        if serde {
+            derived.push(quote! { ::serde::Deserialize });
+            derived.push(quote! { ::serde::Serialize });
+        }
+        let derive_attr = if derived.is_empty() {
+            quote! {}
+        } else if serde {
+            quote! {
+                #[derive(#(#derived),*)]
+                #[serde(default)]
+            }
+        } else {
+            quote! { #[derive(#(#derived),*)] }
+        };
+        let allow_case = if self.options.keep_case {
+            quote! { #[allow(non_snake_case)] }
+        } else {
+            quote! {}
+        };
+        let default_impl = if serde || derives.iter().any(|derive| derive == "Default") {
+            quote! {
+                impl ::core::default::Default for #struct_ident {
+                    fn default() -> Self {
+                        Self {
+                            #inits
+                        }
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let mut entries = if idx == 0 {
+            let mut entries = self.generate_entries(&relative_children_fields_iter);
+            entries.extend(self.generate_lookup_fn(&relative_children_fields_iter));
+            entries
+        } else {
+            quote! {}
+        };
+        if serde {
+            let vis = self.options.item_visibility();
+            entries.extend(quote! {
+                /// The values in the TOML, which deserializing falls back to for missing keys
+                #vis const DEFAULTS: Self = Self {
+                    #inits
+                };
+            });
+        }
+        tokens.extend(quote! {
+            #comment
+            #derive_attr
+            #allow_case
+            #vis struct #struct_ident {
+                #decls
+            }
+
+            #default_impl
+        });
+        if !entries.is_empty() {
+            tokens.extend(quote! {
+                impl #struct_ident {
+                    #entries
+                }
+            });
+        }
+        tokens.extend(nested);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use globset::GlobSetBuilder;
+
+    fn checksum_of(toml: &str, pattern: &str) -> u64 {
+        let value: Value = toml.parse().unwrap();
+        let pattern = syn::parse_str::<Pattern>(pattern).unwrap();
+        let mut globs = GlobSetBuilder::new();
+        globs.add(pattern.to_glob());
+        TomlFields::from(value)
+            .with_inclusion_globs(Some(globs.build().unwrap()))
+            .with_pat_literals(vec![pattern.to_string()])
+            .build()
+            .checksum()
+    }
+
+    #[test]
+    fn test_checksum_is_deterministic() {
+        let toml = "[a]\nx = 1\ny = \"two\"";
+        assert_eq!(checksum_of(toml, "a.*"), checksum_of(toml, "a.*"));
+    }
+
+    #[test]
+    fn test_checksum_changes_with_bound_values() {
+        let before = checksum_of("[a]\nx = 1\n[b]\nz = 3", "a.*");
+        assert_ne!(before, checksum_of("[a]\nx = 2\n[b]\nz = 3", "a.*"));
+        assert_ne!(before, checksum_of("[a]\nw = 1\n[b]\nz = 3", "a.*"));
+        // values that aren't bound don't affect it
+        assert_eq!(before, checksum_of("[a]\nx = 1\n[b]\nz = 4", "a.*"));
+    }
+}
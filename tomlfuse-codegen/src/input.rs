@@ -0,0 +1,314 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use crate::module::{RootModule, RootModuleKind, RootModuleSource};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt;
+use syn::parse::{Parse, ParseStream};
+use syn::{ImplItem, Item, LitStr, Result as SynResult, Token};
+
+mod kw {
+    syn::custom_keyword!(env);
+    syn::custom_keyword!(default);
+    syn::custom_keyword!(ignore_file);
+}
+
+/// The format of the source file the macro binds.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SourceFormat {
+    #[default]
+    Toml,
+    /// A workspace's `Cargo.toml`, bound as the `[package]`s of its members, see
+    /// `manifest::workspace_members`
+    WorkspaceMembers,
+    /// Converted into the same value tree as TOML, see `json::json_to_toml`
+    #[cfg(feature = "json")]
+    Json,
+    /// Converted into the same value tree as TOML, see `yaml::yaml_to_toml`
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+/// A source file path in the macro input, either written out or named by an environment
+/// variable for setups where it varies, like CI.
+#[derive(Clone)]
+pub enum SourcePath {
+    /// `"config.toml"`
+    Literal(LitStr),
+    /// `env = "APP_CONFIG"`, with `default = "config.toml"` to fall back to when it isn't set
+    Env {
+        var: LitStr,
+        default: Option<LitStr>,
+    },
+}
+
+impl SourcePath {
+    /// Resolves the path to read, as a literal spanning what it was written as.
+    ///
+    /// The variable is read at compile time, so it's the build environment that decides.
+    ///
+    /// # Returns
+    /// An error pointing at the variable when it isn't set (or isn't unicode) and there's no
+    /// default.
+    pub fn resolve(&self) -> SynResult<LitStr> {
+        let (var, default) = match self {
+            SourcePath::Literal(lit) => return Ok(lit.clone()),
+            SourcePath::Env { var, default } => (var, default),
+        };
+        match (env::var(var.value()), default) {
+            (Ok(path), _) => Ok(LitStr::new(&path, var.span())),
+            (Err(env::VarError::NotPresent), Some(default)) => Ok(default.clone()),
+            (Err(env::VarError::NotPresent), None) => Err(syn::Error::new(
+                var.span(),
+                format!(
+                    "Environment variable `{}` isn't set, and there's no path to fall back to \
+                     (add one with `env = \"{}\" default = \"config.toml\"`)",
+                    var.value(),
+                    var.value()
+                ),
+            )),
+            (Err(err), _) => Err(syn::Error::new(
+                var.span(),
+                format!("Can't read the path from `{}`: {}", var.value(), err),
+            )),
+        }
+    }
+}
+
+impl Parse for SourcePath {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        if !input.peek(kw::env) {
+            return Ok(SourcePath::Literal(input.parse()?));
+        }
+        let _kw: kw::env = input.parse()?;
+        let _eq: Token![=] = input.parse()?;
+        let var = input.parse()?;
+        let default = if input.peek(kw::default) {
+            let _kw: kw::default = input.parse()?;
+            let _eq: Token![=] = input.parse()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(SourcePath::Env { var, default })
+    }
+}
+
+impl fmt::Display for SourcePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourcePath::Literal(lit) => write!(f, "{}", lit.value()),
+            SourcePath::Env { var, .. } => write!(f, "env = \"{}\"", var.value()),
+        }
+    }
+}
+
+/// Parsed representation of the input to `tomlfuse` macros.
+///
+/// Stores the macro parameters:
+/// 1. Paths to the TOML files (optional for some convenience macros), merged in order, each
+///    written out or read from an environment variable
+/// 2. Module source configurations (patterns, sections, aliases)
+///
+/// This structure is created during macro parsing and used to drive
+/// the code generation process.
+pub struct MacroInput {
+    /// Paths to the TOML files, with later files deep-merged over earlier ones; kept as the
+    /// literals for pointing errors about the files at them
+    pub toml_paths: Vec<SourcePath>,
+    /// `ignore_file = ".."`, the file of exclusion patterns applied to every section, which is
+    /// `module::DEFAULT_IGNORE_FILE` at the crate root when not given
+    pub ignore_file: Option<LitStr>,
+    /// Collection of module configurations from the macro input
+    /// Each represents a separate module to generate
+    pub root_module_sources: Vec<RootModuleSource>,
+    /// Format of the source file, set by the macro rather than parsed
+    pub format: SourceFormat,
+}
+
+impl Parse for MacroInput {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        // e.g. `"base.toml", env = "OVERRIDE"`, a trailing comma is fine too
+        let mut toml_paths = Vec::new();
+        while input.peek(LitStr) || input.peek(kw::env) && input.peek2(Token![=]) {
+            toml_paths.push(input.parse()?);
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            } else {
+                break;
+            }
+        }
+
+        let ignore_file = if input.peek(kw::ignore_file) && input.peek2(Token![=]) {
+            let _kw: kw::ignore_file = input.parse()?;
+            let _eq: Token![=] = input.parse()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        let mut module_sources: Vec<RootModuleSource> = Vec::new();
+        while !input.is_empty() {
+            let module_source: RootModuleSource = input.parse()?;
+            // a section can be reopened to add to it, which makes one module rather than two
+            match module_sources
+                .iter_mut()
+                .find(|s| s.name == module_source.name && s.kind == module_source.kind)
+            {
+                Some(existing) => existing.merge(module_source)?,
+                None => module_sources.push(module_source),
+            }
+        }
+
+        Ok(MacroInput {
+            toml_paths,
+            ignore_file,
+            root_module_sources: module_sources,
+            format: SourceFormat::default(),
+        })
+    }
+}
+
+impl ToTokens for MacroInput {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        // `option_env!` has rustc track the variables, so setting or changing one rebuilds
+        for path in &self.toml_paths {
+            if let SourcePath::Env { var, .. } = path {
+                tokens.extend(quote! {
+                    const _: ::core::option::Option<&str> = ::core::option_env!(#var);
+                });
+            }
+        }
+        let ignored = match RootModule::read_ignore_file(self.ignore_file.as_ref()) {
+            Ok(ignored) => ignored,
+            Err(err) => return tokens.extend(err.to_compile_error()),
+        };
+        let mut modules = Vec::new();
+        for source in &self.root_module_sources {
+            let mut source = source.clone();
+            source.exclusion_pats.extend(ignored.iter().cloned());
+            let label = section_label(&source);
+            match RootModule::new(source, &self.toml_paths, self.format) {
+                Ok(module) => modules.push((label, module.to_token_stream())),
+                // every section reads the same files, so the others would fail the same way
+                Err(err) => return tokens.extend(err.to_compile_error()),
+            }
+        }
+        let (combined, collisions) = combine_modules(&modules);
+        for message in collisions {
+            tokens.extend(syn::Error::new(Span::call_site(), message).to_compile_error());
+        }
+        tokens.extend(combined);
+    }
+}
+
+/// Combines the generated modules of the sections, labeled by their headers.
+///
+/// This is a safety net for collisions the per-module checks can't see, like two sections
+/// generating the same top level item or a generated const shadowing a bound one.
+/// A section with collisions is left out, so rustc doesn't pile on its own errors.
+///
+/// # Returns
+/// The combined modules, and a message for each collision.
+pub fn combine_modules(modules: &[(String, TokenStream2)]) -> (TokenStream2, Vec<String>) {
+    let mut combined = TokenStream2::new();
+    let mut messages = Vec::new();
+    let mut seen: HashMap<(&str, String), &str> = HashMap::new();
+    for (label, module_tokens) in modules {
+        let mut items: HashSet<(&str, String)> = HashSet::new();
+        let mut collisions: Vec<String> = Vec::new();
+        for item in generated_items(module_tokens) {
+            // everything below an item that already collided collides too
+            if collisions
+                .iter()
+                .any(|path| item.1.starts_with(&format!("{}::", path)))
+            {
+                continue;
+            }
+            let message = if let Some(first) = seen.get(&item) {
+                format!(
+                    "`{}` is generated by both section `{}` and section `{}`",
+                    item.1, first, label
+                )
+            } else if items.contains(&item) {
+                format!("`{}` is generated twice by section `{}`", item.1, label)
+            } else {
+                items.insert(item);
+                continue;
+            };
+            messages.push(message);
+            collisions.push(item.1);
+        }
+        if collisions.is_empty() {
+            seen.extend(items.into_iter().map(|item| (item, label.as_str())));
+            combined.extend(module_tokens.clone());
+        }
+    }
+    (combined, messages)
+}
+
+/// The section header as written in the macro input, e.g. `[struct Config]`, for diagnostics.
+pub fn section_label(source: &RootModuleSource) -> String {
+    match source.kind {
+        RootModuleKind::Module => format!("[{}]", source.name),
+        RootModuleKind::Struct => format!("[struct {}]", source.name),
+    }
+}
+
+/// Collects the fully qualified paths of the named items in generated code, along with the
+/// namespace they live in (`"type"` or `"value"`), since e.g. a module and a const can share
+/// a name. Associated items are included under their type, e.g. `Config::TIMEOUT`.
+///
+/// Code that doesn't parse as items yields nothing, leaving it to rustc to report.
+fn generated_items(tokens: &TokenStream2) -> Vec<(&'static str, String)> {
+    fn collect(items: &[Item], prefix: &str, out: &mut Vec<(&'static str, String)>) {
+        let path = |ident: &dyn ToString| format!("{}{}", prefix, ident.to_string());
+        for item in items {
+            match item {
+                Item::Mod(item) => {
+                    out.push(("type", path(&item.ident)));
+                    if let Some((_, content)) = &item.content {
+                        collect(content, &format!("{}::", path(&item.ident)), out);
+                    }
+                },
+                Item::Struct(item) => {
+                    out.push(("type", path(&item.ident)));
+                    // unit structs are values too
+                    if matches!(item.fields, syn::Fields::Unit) {
+                        out.push(("value", path(&item.ident)));
+                    }
+                },
+                Item::Enum(item) => out.push(("type", path(&item.ident))),
+                Item::Const(item) if item.ident != "_" => out.push(("value", path(&item.ident))),
+                Item::Fn(item) => out.push(("value", path(&item.sig.ident))),
+                Item::Impl(item) if item.trait_.is_none() => {
+                    let ty = item.self_ty.to_token_stream().to_string();
+                    for impl_item in &item.items {
+                        match impl_item {
+                            ImplItem::Const(c) => {
+                                out.push(("value", path(&format!("{}::{}", ty, c.ident))))
+                            },
+                            ImplItem::Fn(f) => {
+                                out.push(("value", path(&format!("{}::{}", ty, f.sig.ident))))
+                            },
+                            _ => {},
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    if let Ok(file) = syn::parse2::<syn::File>(tokens.clone()) {
+        collect(&file.items, "", &mut out);
+    }
+    out
+}
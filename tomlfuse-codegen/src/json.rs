@@ -0,0 +1,82 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use toml::Value;
+
+/// Parses a json document into the TOML value tree the rest of the pipeline works with.
+///
+/// An empty document is treated as an empty table, like an empty toml file would be.
+pub fn parse_json(content: &str) -> Result<Value, serde_json::Error> {
+    if content.trim().is_empty() {
+        return Ok(Value::Table(Default::default()));
+    }
+    let json: serde_json::Value = serde_json::from_str(content)?;
+    Ok(json_to_toml(json).unwrap_or(Value::Table(Default::default())))
+}
+
+/// Converts a json value into the closest TOML value.
+///
+/// - `null` has no TOML counterpart, so it's dropped (from objects and arrays alike)
+/// - Numbers become integers when they fit in an `i64`, and floats otherwise
+/// - Objects become tables, the rest map one to one
+///
+/// # Returns
+/// `None` only for `null`.
+pub fn json_to_toml(json: serde_json::Value) -> Option<Value> {
+    use serde_json::Value as Json;
+    Some(match json {
+        Json::Null => return None,
+        Json::Bool(b) => Value::Boolean(b),
+        Json::Number(n) => match n.as_i64() {
+            Some(int) => Value::Integer(int),
+            None => Value::Float(n.as_f64()?),
+        },
+        Json::String(s) => Value::String(s),
+        Json::Array(arr) => Value::Array(arr.into_iter().filter_map(json_to_toml).collect()),
+        Json::Object(obj) => Value::Table(
+            obj.into_iter()
+                .filter_map(|(key, value)| Some((key, json_to_toml(value)?)))
+                .collect(),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_to_toml() {
+        let value = parse_json(
+            r#"{
+                "name": "app",
+                "port": 8080,
+                "ratio": 0.5,
+                "big": 18446744073709551615,
+                "enabled": true,
+                "missing": null,
+                "tags": ["a", null, "b"],
+                "nested": { "inner": { "value": 1 } }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(value["name"].as_str(), Some("app"));
+        assert_eq!(value["port"].as_integer(), Some(8080));
+        assert_eq!(value["ratio"].as_float(), Some(0.5));
+        // doesn't fit in an `i64`
+        assert!(value["big"].is_float());
+        assert_eq!(value["enabled"].as_bool(), Some(true));
+        assert!(value.get("missing").is_none());
+        assert_eq!(value["tags"].as_array().unwrap().len(), 2);
+        assert_eq!(value["nested"]["inner"]["value"].as_integer(), Some(1));
+    }
+
+    #[test]
+    fn test_empty_json() {
+        assert_eq!(parse_json("  ").unwrap(), Value::Table(Default::default()));
+        assert!(parse_json("{").is_err());
+    }
+}
@@ -0,0 +1,33 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+//! The code generation behind the `tomlfuse` macros, shared with `tomlfuse-build`.
+//!
+//! Not meant to be depended on directly; use the macros of `tomlfuse`, or `tomlfuse-build`
+//! from build scripts.
+
+mod annotation;
+pub mod build;
+mod comments;
+mod env_override;
+mod field;
+mod input;
+#[cfg(feature = "json")]
+mod json;
+mod manifest;
+mod module;
+mod options;
+mod pattern;
+mod select_os;
+mod utils;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+// what the macros of `tomlfuse` parse their input into and locate the manifests with
+#[doc(hidden)]
+pub use input::{MacroInput, SourceFormat};
+#[doc(hidden)]
+pub use utils::{find_workspace_root, is_package_manifest};
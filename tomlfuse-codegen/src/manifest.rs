@@ -0,0 +1,518 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use crate::utils::is_workspace_root;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::map::Map;
+use toml::Value;
+
+/// The `[package]` keys a member can inherit from `[workspace.package]`, with e.g.
+/// `version.workspace = true`.
+pub const INHERITABLE_PACKAGE_KEYS: &[&str] = &[
+    "version",
+    "authors",
+    "description",
+    "documentation",
+    "readme",
+    "homepage",
+    "repository",
+    "license",
+    "license-file",
+    "keywords",
+    "categories",
+    "edition",
+    "rust-version",
+    "exclude",
+    "include",
+    "publish",
+];
+
+/// The tables of dependencies that can inherit from `[workspace.dependencies]`.
+const DEPENDENCY_TABLES: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Replaces the `workspace = true` keys of a member's manifest with what they inherit from the
+/// workspace, like cargo does, so they're bound as the actual values.
+///
+/// The workspace manifest is the one `package.workspace` points to, or else the closest one
+/// with a `[workspace]` table from the manifest's directory upwards. Inherited package keys
+/// come from `[workspace.package]`, and inherited dependencies from `[workspace.dependencies]`
+/// with the member's `features` added and its other keys (e.g. `optional`) kept.
+///
+/// # Returns
+/// A message for the first key that can't be inherited, e.g. one the workspace doesn't have.
+pub fn inherit_from_workspace(manifest: &mut Value, manifest_path: &Path) -> Result<(), String> {
+    if !inherits_any(manifest) {
+        return Ok(());
+    }
+    let workspace_path = find_workspace_manifest(manifest, manifest_path).ok_or_else(|| {
+        format!(
+            "`{}` inherits from a workspace, but no workspace manifest was found for it",
+            manifest_path.display()
+        )
+    })?;
+    let workspace: Value = fs::read_to_string(&workspace_path)
+        .ok()
+        .and_then(|raw| raw.parse().ok())
+        .ok_or_else(|| format!("Failed to parse workspace manifest {}", workspace_path.display()))?;
+    let workspace = workspace
+        .get("workspace")
+        .cloned()
+        .unwrap_or(Value::Table(Map::new()));
+    let empty = Value::Table(Map::new());
+
+    if let Some(Value::Table(package)) = manifest.get_mut("package") {
+        let inherited = workspace.get("package").unwrap_or(&empty);
+        for (key, value) in package.iter_mut() {
+            if !is_inherited(value) {
+                continue;
+            }
+            if !INHERITABLE_PACKAGE_KEYS.contains(&key.as_str()) {
+                return Err(format!("`package.{}` can't be inherited from a workspace", key));
+            }
+            *value = inherited.get(key).cloned().ok_or_else(|| {
+                format!(
+                    "`package.{}` inherits from the workspace, but `[workspace.package]` has no \
+                     `{}`",
+                    key, key
+                )
+            })?;
+        }
+    }
+
+    let inherited = workspace.get("dependencies").unwrap_or(&empty);
+    let mut dependency_tables = Vec::new();
+    if let Value::Table(root) = manifest {
+        for (key, value) in root.iter_mut() {
+            if DEPENDENCY_TABLES.contains(&key.as_str()) {
+                dependency_tables.push((key.clone(), value));
+            } else if key == "target" {
+                // `[target.'cfg(unix)'.dependencies]` and the like
+                for (target, value) in value.as_table_mut().into_iter().flatten() {
+                    for (key, value) in value.as_table_mut().into_iter().flatten() {
+                        if DEPENDENCY_TABLES.contains(&key.as_str()) {
+                            dependency_tables.push((format!("target.{}.{}", target, key), value));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    for (table_path, table) in dependency_tables {
+        for (name, dependency) in table.as_table_mut().into_iter().flatten() {
+            if !is_inherited(dependency) {
+                continue;
+            }
+            let base = inherited.get(name).ok_or_else(|| {
+                format!(
+                    "`{}.{}` inherits from the workspace, but `[workspace.dependencies]` has no \
+                     `{}`",
+                    table_path, name, name
+                )
+            })?;
+            *dependency = inherit_dependency(base, dependency);
+        }
+    }
+
+    if let Some(lints) = manifest.get_mut("lints").filter(|lints| is_inherited(lints)) {
+        *lints = workspace.get("lints").cloned().ok_or_else(|| {
+            "`lints` inherits from the workspace, but there's no `[workspace.lints]`".to_string()
+        })?;
+    }
+    Ok(())
+}
+
+// merges a member's `{ workspace = true, .. }` dependency over the workspace's one
+fn inherit_dependency(base: &Value, member: &Value) -> Value {
+    let mut merged = match base {
+        // `serde = "1.0"` is short for `serde = { version = "1.0" }`
+        Value::String(version) => {
+            let mut table = Map::new();
+            table.insert("version".to_string(), Value::String(version.clone()));
+            table
+        },
+        Value::Table(table) => table.clone(),
+        other => return other.clone(),
+    };
+    for (key, value) in member.as_table().into_iter().flatten() {
+        match (key.as_str(), merged.get_mut(key), value) {
+            ("workspace", ..) => {},
+            // features are additive, the member can only add to them
+            ("features", Some(Value::Array(features)), Value::Array(extra)) => {
+                for feature in extra {
+                    if !features.contains(feature) {
+                        features.push(feature.clone());
+                    }
+                }
+            },
+            _ => {
+                merged.insert(key.clone(), value.clone());
+            },
+        }
+    }
+    Value::Table(merged)
+}
+
+/// Keys the `[[package]]` array of a `Cargo.lock` by the packages' names, so a locked
+/// package is bound as e.g. `package::serde::VERSION` rather than by its position.
+///
+/// A crate locked at several versions is keyed by `name@version` instead for each of them,
+/// e.g. `syn@1.0.109` and `syn@2.0.48` (bound as `syn_1_0_109` and `syn_2_0_48`).
+pub fn key_locked_packages(lockfile: &mut Value) {
+    let packages = match lockfile.get("package") {
+        Some(Value::Array(packages)) => packages,
+        _ => return,
+    };
+    let name_of = |package: &Value| {
+        package
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let names: Vec<_> = packages.iter().filter_map(name_of).collect();
+    let mut keyed = Map::new();
+    for package in packages {
+        let name = match name_of(package) {
+            Some(name) => name,
+            None => continue,
+        };
+        let key = match package.get("version").and_then(Value::as_str) {
+            Some(version) if names.iter().filter(|other| **other == name).count() > 1 => {
+                format!("{}@{}", name, version)
+            },
+            _ => name,
+        };
+        keyed.insert(key, package.clone());
+    }
+    lockfile["package"] = Value::Table(keyed);
+}
+
+/// Gathers the `[package]` tables of a workspace's members, keyed by their names, so each
+/// member is bound as e.g. `tomlfuse_build::VERSION`.
+///
+/// The members are the paths in `workspace.members`, where a `*` or other glob in a path
+/// segment matches the directories it names that have a `Cargo.toml`, and the workspace's own
+/// package if it has one. Paths under any of `workspace.exclude` are left out. Keys a member
+/// inherits from the workspace are resolved like in `inherit_from_workspace`.
+///
+/// # Returns
+/// A message for the first member whose manifest can't be read, e.g. an explicitly listed one
+/// that doesn't exist, or for two members with the same `package.name`.
+pub fn workspace_members(workspace: &Value, workspace_path: &Path) -> Result<Value, String> {
+    let table = workspace
+        .get("workspace")
+        .and_then(Value::as_table)
+        .ok_or_else(|| {
+            format!("{} has no `[workspace]` to list the members of", workspace_path.display())
+        })?;
+    let root = workspace_path.parent().unwrap_or(Path::new(""));
+    let paths = |key: &str| {
+        table
+            .get(key)
+            .and_then(Value::as_array)
+            .map(|paths| paths.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+    let excluded: Vec<_> = paths("exclude").iter().map(|path| root.join(path)).collect();
+    let mut manifests = Vec::new();
+    if workspace.get("package").is_some() {
+        manifests.push(workspace_path.to_path_buf());
+    }
+    for member in paths("members") {
+        if is_glob(member) {
+            manifests.extend(
+                expand_member_glob(root, member)?
+                    .into_iter()
+                    .map(|dir| dir.join("Cargo.toml"))
+                    .filter(|manifest| manifest.is_file()),
+            );
+        } else {
+            manifests.push(root.join(member).join("Cargo.toml"));
+        }
+    }
+    let mut members = Map::new();
+    let mut member_paths = BTreeMap::new();
+    for path in manifests {
+        if excluded.iter().any(|dir| path.starts_with(dir)) {
+            continue;
+        }
+        let mut manifest: Value = fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| raw.parse().ok())
+            .ok_or_else(|| format!("Failed to parse workspace member {}", path.display()))?;
+        inherit_from_workspace(&mut manifest, &path)?;
+        let package = manifest
+            .get("package")
+            .ok_or_else(|| format!("Workspace member {} has no `[package]`", path.display()))?;
+        let name = package
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("Workspace member {} has no `package.name`", path.display()))?;
+        // a member listed both explicitly and by a glob is still the one member
+        match member_paths.insert(name.to_string(), path.clone()) {
+            Some(other) if other != path => {
+                return Err(format!(
+                    "Workspace members {} and {} are both named `{}`",
+                    other.display(),
+                    path.display(),
+                    name
+                ));
+            },
+            _ => {},
+        }
+        members.insert(name.to_string(), package.clone());
+    }
+    Ok(Value::Table(members))
+}
+
+// whether a member path has glob syntax to expand
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+// the directories a member path with globs in its segments matches
+fn expand_member_glob(root: &Path, member: &str) -> Result<Vec<PathBuf>, String> {
+    let mut dirs = vec![root.to_path_buf()];
+    for segment in member.split('/').filter(|segment| !segment.is_empty()) {
+        if !is_glob(segment) {
+            dirs = dirs.into_iter().map(|dir| dir.join(segment)).collect();
+            continue;
+        }
+        let matcher = globset::Glob::new(segment)
+            .map_err(|err| format!("Invalid workspace member `{}`: {}", member, err))?
+            .compile_matcher();
+        let mut matched = Vec::new();
+        for dir in dirs {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            matched.extend(
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir())
+                    .filter(|path| path.file_name().is_some_and(|name| matcher.is_match(name))),
+            );
+        }
+        // directory listings aren't in any particular order
+        matched.sort();
+        dirs = matched;
+    }
+    Ok(dirs)
+}
+
+// whether a value is `{ workspace = true }` (or `key.workspace = true`)
+fn is_inherited(value: &Value) -> bool {
+    value
+        .get("workspace")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+// whether anything in the manifest inherits from the workspace, to skip looking for it otherwise
+fn inherits_any(manifest: &Value) -> bool {
+    match manifest {
+        Value::Table(table) => table
+            .values()
+            .any(|value| is_inherited(value) || inherits_any(value)),
+        _ => false,
+    }
+}
+
+// finds the workspace manifest of a member manifest
+fn find_workspace_manifest(manifest: &Value, manifest_path: &Path) -> Option<PathBuf> {
+    let dir = manifest_path.parent()?;
+    if let Some(explicit) = manifest
+        .get("package")
+        .and_then(|package| package.get("workspace"))
+        .and_then(Value::as_str)
+    {
+        return Some(dir.join(explicit).join("Cargo.toml"));
+    }
+    dir.ancestors()
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|path| is_workspace_root(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const WORKSPACE: &str = r#"
+        [workspace]
+        members = ["member"]
+
+        [workspace.package]
+        version = "1.2.3"
+        authors = ["Someone <someone@example.com>"]
+        edition = "2021"
+        license = "MPL-2.0"
+
+        [workspace.dependencies]
+        serde = { version = "1.0", features = ["derive"] }
+        toml = "0.8"
+
+        [workspace.lints.rust]
+        unsafe_code = "forbid"
+    "#;
+
+    const MEMBER: &str = r#"
+        [package]
+        name = "member"
+        version.workspace = true
+        authors.workspace = true
+        edition = { workspace = true }
+        license.workspace = true
+
+        [dependencies]
+        serde = { workspace = true, features = ["rc"], optional = true }
+        toml.workspace = true
+        glob = "0.3"
+
+        [target.'cfg(unix)'.dev-dependencies]
+        toml = { workspace = true }
+
+        [lints]
+        workspace = true
+    "#;
+
+    fn setup(workspace: &str, member: &str) -> (TempDir, PathBuf) {
+        let temp = TempDir::new().expect("Expected a temp dir");
+        fs::create_dir_all(temp.path().join("member")).expect("Expected to create the member");
+        fs::write(temp.path().join("Cargo.toml"), workspace).expect("Expected to write");
+        let member_path = temp.path().join("member").join("Cargo.toml");
+        fs::write(&member_path, member).expect("Expected to write");
+        (temp, member_path)
+    }
+
+    #[test]
+    fn test_inherit_package_keys() {
+        let (_temp, path) = setup(WORKSPACE, MEMBER);
+        let mut manifest: Value = MEMBER.parse().unwrap();
+        inherit_from_workspace(&mut manifest, &path).unwrap();
+        let package = &manifest["package"];
+        assert_eq!(package["name"].as_str(), Some("member"));
+        assert_eq!(package["version"].as_str(), Some("1.2.3"));
+        assert_eq!(package["edition"].as_str(), Some("2021"));
+        assert_eq!(package["license"].as_str(), Some("MPL-2.0"));
+        assert_eq!(package["authors"][0].as_str(), Some("Someone <someone@example.com>"));
+        assert_eq!(manifest["lints"]["rust"]["unsafe_code"].as_str(), Some("forbid"));
+    }
+
+    #[test]
+    fn test_inherit_dependencies() {
+        let (_temp, path) = setup(WORKSPACE, MEMBER);
+        let mut manifest: Value = MEMBER.parse().unwrap();
+        inherit_from_workspace(&mut manifest, &path).unwrap();
+        let serde = &manifest["dependencies"]["serde"];
+        assert_eq!(serde["version"].as_str(), Some("1.0"));
+        assert_eq!(serde["features"].as_array().map(Vec::len), Some(2));
+        assert_eq!(serde["optional"].as_bool(), Some(true));
+        assert!(serde.get("workspace").is_none());
+        assert_eq!(manifest["dependencies"]["toml"]["version"].as_str(), Some("0.8"));
+        assert_eq!(manifest["dependencies"]["glob"].as_str(), Some("0.3"));
+        let unix = &manifest["target"]["cfg(unix)"]["dev-dependencies"];
+        assert_eq!(unix["toml"]["version"].as_str(), Some("0.8"));
+    }
+
+    #[test]
+    fn test_inherit_missing_key() {
+        let member = "[package]\nname = \"member\"\ndescription.workspace = true\n";
+        let (_temp, path) = setup(WORKSPACE, member);
+        let mut manifest: Value = member.parse().unwrap();
+        let err = inherit_from_workspace(&mut manifest, &path).unwrap_err();
+        assert!(err.contains("has no `description`"), "{}", err);
+    }
+
+    #[test]
+    fn test_nothing_to_inherit() {
+        // without a workspace around, a manifest that doesn't inherit is left alone
+        let temp = TempDir::new().unwrap();
+        let mut manifest: Value = "[package]\nname = \"alone\"".parse().unwrap();
+        let expected = manifest.clone();
+        inherit_from_workspace(&mut manifest, &temp.path().join("Cargo.toml")).unwrap();
+        assert_eq!(manifest, expected);
+    }
+
+    #[test]
+    fn test_key_locked_packages() {
+        let mut lockfile: Value = r#"
+            version = 3
+
+            [[package]]
+            name = "serde"
+            version = "1.0.197"
+
+            [[package]]
+            name = "syn"
+            version = "1.0.109"
+
+            [[package]]
+            name = "syn"
+            version = "2.0.48"
+        "#
+        .parse()
+        .unwrap();
+        key_locked_packages(&mut lockfile);
+        let packages = &lockfile["package"];
+        assert_eq!(packages["serde"]["version"].as_str(), Some("1.0.197"));
+        assert_eq!(packages["syn@1.0.109"]["version"].as_str(), Some("1.0.109"));
+        assert_eq!(packages["syn@2.0.48"]["version"].as_str(), Some("2.0.48"));
+        assert!(packages.get("syn").is_none());
+        assert_eq!(lockfile["version"].as_integer(), Some(3));
+    }
+
+    #[test]
+    fn test_workspace_members() {
+        let temp = TempDir::new().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = temp.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        let workspace = r#"
+            [workspace]
+            members = ["crates/*", "tools/cli"]
+            exclude = ["crates/skipped"]
+
+            [workspace.package]
+            version = "1.2.3"
+        "#;
+        write("Cargo.toml", workspace);
+        write("crates/core/Cargo.toml", "[package]\nname = \"core\"\nversion = \"0.1.0\"");
+        write("crates/macros/Cargo.toml", "[package]\nname = \"macros\"\nversion.workspace = true");
+        write("crates/skipped/Cargo.toml", "[package]\nname = \"skipped\"");
+        write("crates/docs/README.md", "not a crate");
+        write("tools/cli/Cargo.toml", "[package]\nname = \"cli\"\nversion = \"2.0.0\"");
+
+        let path = temp.path().join("Cargo.toml");
+        let members = workspace_members(&workspace.parse().unwrap(), &path).unwrap();
+        let version = |name: &str| members[name]["version"].as_str();
+        assert_eq!(version("core"), Some("0.1.0"));
+        assert_eq!(version("macros"), Some("1.2.3"));
+        assert_eq!(version("cli"), Some("2.0.0"));
+        assert!(members.get("skipped").is_none());
+        assert_eq!(members.as_table().map(|members| members.len()), Some(3));
+
+        // an explicit member has to be there
+        let missing: Value = "[workspace]\nmembers = [\"tools/missing\"]".parse().unwrap();
+        let err = workspace_members(&missing, &path).unwrap_err();
+        assert!(err.contains("tools/missing"), "{}", err);
+
+        // listing a member twice is fine, two members with the same name aren't
+        let members_of = |paths: &str| {
+            let workspace: Value = format!("[workspace]\nmembers = {}", paths).parse().unwrap();
+            workspace_members(&workspace, &path)
+        };
+        assert!(members_of(r#"["crates/*", "crates/core"]"#).is_ok());
+        write("tools/core/Cargo.toml", "[package]\nname = \"core\"");
+        let err = members_of(r#"["crates/core", "tools/core"]"#).unwrap_err();
+        assert!(err.contains("both named `core`"), "{}", err);
+    }
+}
@@ -0,0 +1,1813 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use crate::annotation::Annotation;
+use crate::comments::extract_comments;
+use crate::field::TomlFields;
+use crate::input::{SourceFormat, SourcePath};
+use crate::options::SectionOptions;
+use crate::pattern::Pattern;
+use crate::utils;
+use globset::{GlobBuilder, GlobSetBuilder};
+use proc_macro2::{Ident, Span};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned, ToTokens};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::{env, fs};
+use syn::parse::discouraged::Speculative;
+use syn::parse::{Parse, ParseStream};
+use syn::{token, LitStr, Result as SynResult, Token};
+use toml::Value;
+
+mod kw {
+    syn::custom_keyword!(alias);
+    syn::custom_keyword!(allow_empty);
+    syn::custom_keyword!(from);
+    syn::custom_keyword!(require);
+}
+
+/// Prefix of source paths that are relative to cargo's target directory rather than the
+/// crate, e.g. `"target-dir:shared/config.toml"`, for config generated by other crates' builds.
+pub const TARGET_DIR_PREFIX: &str = "target-dir:";
+
+/// The file of exclusion patterns applied to every section, read from the crate root when the
+/// macro doesn't name one with `ignore_file = ".."`, see `RootModule::read_ignore_file`.
+pub const DEFAULT_IGNORE_FILE: &str = ".tomlfuseignore";
+
+/// The directories source paths are looked up in, see `RootModule::source_candidates`.
+#[derive(Clone, Debug)]
+pub struct SourceDirs {
+    /// The crate root, `CARGO_MANIFEST_DIR`
+    pub manifest_dir: PathBuf,
+    /// The workspace root, which is the crate root outside of workspaces
+    pub workspace_root: PathBuf,
+    /// `CARGO_WORKSPACE_DIR`, for builds that keep the sources elsewhere than the manifest
+    pub declared_workspace_dir: Option<PathBuf>,
+    /// Cargo's target directory, that `target-dir:` paths are relative to
+    pub target_dir: PathBuf,
+}
+
+impl SourceDirs {
+    /// The directories of the crate being built, from the environment cargo sets.
+    pub fn from_env() -> Self {
+        SourceDirs {
+            manifest_dir: PathBuf::from(
+                env::var("CARGO_MANIFEST_DIR").expect("Expected CARGO_MANIFEST_DIR to be in env"),
+            ),
+            workspace_root: utils::find_workspace_root(),
+            declared_workspace_dir: utils::declared_workspace_dir(),
+            target_dir: utils::find_target_dir(),
+        }
+    }
+}
+
+/// The kind of item a section header generates.
+///
+/// - `Module`: `[name]`, a `pub mod name` with `pub const`s
+/// - `Struct`: `[struct Name]`, a zero-sized `pub struct Name` with associated consts,
+///   and nested structs for subtables
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RootModuleKind {
+    #[default]
+    Module,
+    Struct,
+}
+
+/// Source configuration for a root module used in macro input.
+///
+/// Represents the parsed pattern declarations from macro input that define
+/// which TOML entries should be included in the generated code.
+///
+#[derive(Clone, Debug)]
+pub struct RootModuleSource {
+    /// Name of the module to be generated
+    pub name: Ident,
+    /// Whether a module or a struct is generated
+    pub kind: RootModuleKind,
+    /// Patterns for fields to include in the generated code
+    pub inclusion_pats: Vec<Pattern>,
+    /// Patterns for fields to exclude from the generated code
+    pub exclusion_pats: Vec<Pattern>,
+    /// Inclusion patterns (`allow_empty pattern`) that may match nothing without an error
+    pub optional_pats: Vec<Pattern>,
+    /// Paths that must be in the TOML (`require package.license`), bound or not
+    pub required: Vec<Pattern>,
+    /// Values bound for keys the TOML may not have (`package.rust_version ?= "1.70"`), as the
+    /// path and the value it gets when it's missing
+    pub defaults: Vec<(Pattern, Value)>,
+    /// Map of pattern aliases where key is the alias and value is the original pattern
+    pub aliases: BTreeMap<Pattern, Pattern>,
+    /// Subtree aliases (`alias cfg = app.*`) as the alias and the root of the aliased subtree
+    pub subtree_aliases: Vec<(Pattern, String)>,
+    /// Templated aliases (`alias d_* = dependencies.*`) as the template, whose `*` is replaced
+    /// by the key of each match, and the pattern they rename the matches of
+    pub alias_templates: Vec<(String, Pattern)>,
+    /// Type annotations (`pattern as annotation`) in declaration order
+    pub annotations: Vec<(Pattern, Annotation)>,
+    /// Values picked by the target OS (`select_os app.cache from app.cache_by_os`), as the
+    /// target and the table of values by OS
+    pub os_selects: Vec<(Pattern, Pattern)>,
+    /// Options set with `#directive`s in the section body
+    pub options: SectionOptions,
+    /// Comments extracted from the TOML file, keyed by field path
+    pub comments: BTreeMap<String, String>,
+    /// Directory of the first source file, that `path-relative-to-config` values are joined onto
+    pub config_dir: Option<PathBuf>,
+    /// The source files relative to the manifest directory, for `provenance_docs`
+    pub source_files: Vec<String>,
+}
+
+/// Root module that generates code from TOML data.
+///
+/// Combines the configuration from `RootModuleSource` with parsed TOML data
+/// to generate a Rust module with constants reflecting the TOML structure.
+///
+#[derive(Clone, Debug)]
+pub struct RootModule {
+    /// Source configuration from macro input
+    pub source: RootModuleSource,
+    pub toml: Value,
+    /// The fields bound from the TOML, with the values picked by target and the environment's
+    /// overrides applied to it; they own their values, so nothing outlives the module
+    pub fields: TomlFields,
+    /// Errors found while building, emitted as `compile_error!`s alongside the module
+    pub errors: Vec<syn::Error>,
+    /// Warnings found while building, emitted alongside the module, see `warning_tokens`
+    pub warnings: Vec<(Span, String)>,
+}
+
+impl RootModule {
+    /// Reads and parses the source files, deep-merging later files over earlier ones
+    /// (see `utils::merge_values`), and builds the module from the result. Paths named by
+    /// environment variables are resolved first, see `SourcePath::resolve`.
+    ///
+    /// # Returns
+    /// An error pointing at the path literal of a file that can't be found, read or parsed,
+    /// or at the variable of a path that can't be resolved.
+    pub fn new(
+        mut source: RootModuleSource,
+        toml_paths: &[SourcePath],
+        format: SourceFormat,
+    ) -> SynResult<Self> {
+        let mut toml = Value::Table(Default::default());
+        for toml_path in toml_paths {
+            let toml_path = toml_path.resolve()?;
+            let (value, comments, resolved) = Self::read_source(&toml_path, format)?;
+            source.source_files.push(utils::relative_to_manifest_dir(&toml_path.value()));
+            utils::merge_values(&mut toml, value, source.options.merge_arrays.as_deref());
+            source.comments.extend(comments);
+            // later files layer over the first, which is the config relative paths are written for
+            if source.config_dir.is_none() {
+                source.config_dir = resolved
+                    .canonicalize()
+                    .ok()
+                    .and_then(|path| path.parent().map(PathBuf::from));
+            }
+        }
+        Ok(RootModule::from(source).with_toml(toml).build())
+    }
+
+    /// Lists the paths a source file is looked for at, in order.
+    fn source_candidates(toml_path: &str, dirs: &SourceDirs) -> Vec<PathBuf> {
+        // attempt to read the TOML file from:
+        // 1. direct path
+        // 2. relative to workspace root
+        // 3. relative to CARGO_MANIFEST_DIR
+        // 4. relative to CARGO_WORKSPACE_DIR, if the build sets it
+        //
+        // this allows for flexibility in specifying the TOML path while
+        // still providing reasonable defaults without requiring absolute paths
+        // for common scenarios like referencing Cargo.toml.
+        // a `target-dir:` path is only looked up in the target dir, see `utils::find_target_dir`
+        if let Some(path) = toml_path.strip_prefix(TARGET_DIR_PREFIX) {
+            return vec![dirs.target_dir.join(path)];
+        }
+        let mut candidates = vec![
+            PathBuf::from(toml_path),
+            dirs.workspace_root.join(toml_path),
+            dirs.manifest_dir.join(toml_path),
+        ];
+        // vendored and non-cargo builds can have the sources elsewhere than the manifest
+        candidates.extend(dirs.declared_workspace_dir.as_ref().map(|dir| dir.join(toml_path)));
+        candidates
+    }
+
+    /// Finds the first of `source_candidates` that is a file.
+    ///
+    /// # Returns
+    /// The path, or an error listing every path that was tried, in order.
+    fn resolve_source(toml_path: &str, dirs: &SourceDirs) -> Result<PathBuf, String> {
+        let candidates = Self::source_candidates(toml_path, dirs);
+        match candidates.iter().find(|path| path.is_file()) {
+            Some(resolved) => Ok(resolved.clone()),
+            None => {
+                // the workspace root is the crate root outside of workspaces
+                let mut tried = Vec::with_capacity(candidates.len());
+                for candidate in candidates.iter().map(|path| path.display().to_string()) {
+                    if !tried.contains(&candidate) {
+                        tried.push(candidate);
+                    }
+                }
+                // an absolute path is just looked for where it points
+                if tried.len() == 1 {
+                    return Err(format!("Couldn't find `{}`", toml_path));
+                }
+                Err(format!("Couldn't find `{}`, tried:\n  {}", toml_path, tried.join("\n  ")))
+            },
+        }
+    }
+
+    /// Reads and parses a single source file, along with its comments if the format has any
+    /// and the path it was found at.
+    fn read_source(
+        toml_path: &LitStr,
+        format: SourceFormat,
+    ) -> SynResult<(Value, BTreeMap<String, String>, PathBuf)> {
+        let path = toml_path.value();
+        let error = |msg: String| syn::Error::new(toml_path.span(), msg);
+        let resolved = Self::resolve_source(&path, &SourceDirs::from_env()).map_err(error)?;
+        let raw = fs::read_to_string(&resolved)
+            .map_err(|err| error(format!("Failed to read {}: {}", resolved.display(), err)))?;
+        let parse_error = |err: &dyn std::fmt::Display| {
+            error(format!("Failed to parse {}: {}", resolved.display(), err))
+        };
+        match format {
+            SourceFormat::Toml => {
+                // a markdown file is bound by its front-matter, the rest of it is just text
+                let raw = if utils::is_markdown(&resolved) {
+                    utils::toml_front_matter(&raw).ok_or_else(|| {
+                        error(format!("{} has no `+++` fenced front-matter", resolved.display()))
+                    })?
+                } else {
+                    raw
+                };
+                let mut value: Value = raw.parse().map_err(|err| parse_error(&err))?;
+                // a member's manifest has `version.workspace = true` and the like, which only
+                // mean something along with the workspace's manifest
+                if resolved.ends_with("Cargo.toml") {
+                    let path = resolved.canonicalize().unwrap_or_else(|_| resolved.clone());
+                    crate::manifest::inherit_from_workspace(&mut value, &path).map_err(error)?;
+                }
+                // the locked packages are an array, keyed by name to bind them by it
+                if resolved.ends_with("Cargo.lock") {
+                    crate::manifest::key_locked_packages(&mut value);
+                }
+                Ok((value, extract_comments(&raw), resolved))
+            },
+            SourceFormat::WorkspaceMembers => {
+                let workspace: Value = raw.parse().map_err(|err| parse_error(&err))?;
+                let path = resolved.canonicalize().unwrap_or_else(|_| resolved.clone());
+                let members =
+                    crate::manifest::workspace_members(&workspace, &path).map_err(error)?;
+                Ok((members, BTreeMap::new(), resolved))
+            },
+            #[cfg(feature = "json")]
+            SourceFormat::Json => Ok((
+                crate::json::parse_json(&raw).map_err(|err| parse_error(&err))?,
+                BTreeMap::new(),
+                resolved,
+            )),
+            #[cfg(feature = "yaml")]
+            SourceFormat::Yaml => Ok((
+                crate::yaml::parse_yaml(&raw).map_err(|err| parse_error(&err))?,
+                BTreeMap::new(),
+                resolved,
+            )),
+        }
+    }
+
+    /// Reads the exclusion patterns of an ignore file, which every section gets on top of its
+    /// own. Without a path, `DEFAULT_IGNORE_FILE` is read from the crate root if it's there.
+    ///
+    /// # Returns
+    /// The patterns, or an error pointing at the path literal of a file that can't be found or
+    /// read, or that has a line that isn't a pattern.
+    pub fn read_ignore_file(ignore_file: Option<&LitStr>) -> SynResult<Vec<Pattern>> {
+        let (resolved, span) = match ignore_file {
+            Some(lit) => {
+                let resolved = Self::resolve_source(&lit.value(), &SourceDirs::from_env())
+                    .map_err(|msg| syn::Error::new(lit.span(), msg))?;
+                (resolved, lit.span())
+            },
+            None => {
+                let manifest_dir = env::var("CARGO_MANIFEST_DIR")
+                    .expect("Expected CARGO_MANIFEST_DIR to be in env");
+                let resolved = PathBuf::from(manifest_dir).join(DEFAULT_IGNORE_FILE);
+                if !resolved.is_file() {
+                    return Ok(Vec::new());
+                }
+                (resolved, Span::call_site())
+            },
+        };
+        let error = |msg: String| syn::Error::new(span, msg);
+        let raw = fs::read_to_string(&resolved)
+            .map_err(|err| error(format!("Failed to read {}: {}", resolved.display(), err)))?;
+        parse_ignore_patterns(&raw)
+            .map_err(|msg| error(format!("Failed to parse {}: {}", resolved.display(), msg)))
+    }
+
+    /// Sets the parsed TOML value for this module.
+    pub fn with_toml(self, toml: Value) -> Self {
+        RootModule {
+            toml,
+            ..self
+        }
+    }
+
+    /// Builds the final module by applying patterns and extracting fields.
+    ///
+    /// This method:
+    /// 1. Expands brace groups and converts patterns to glob matchers
+    /// 2. Extracts fields matching the patterns from the TOML data
+    /// 3. Reports inclusion patterns that matched nothing, unless marked `allow_empty`
+    ///
+    /// With `env_override`, the environment variables overriding values are applied first.
+    /// `select_os` tables are moved to their targets before anything is extracted, too.
+    pub fn build(self) -> Self {
+        let mut toml = self.toml.clone();
+        let mut errors: Vec<syn::Error> = Vec::new();
+        let mut comments = self.source.comments.clone();
+        let mut os_selects = BTreeMap::new();
+        for (target, source) in &self.source.os_selects {
+            match crate::select_os::apply_os_select(
+                &mut toml,
+                &target.to_string(),
+                &source.to_string(),
+            ) {
+                Ok((path, select)) => {
+                    // the table's comment documents the value it turned into
+                    if let Some(comment) = comments.get(&select.source).cloned() {
+                        comments.entry(path.clone()).or_insert(comment);
+                    }
+                    os_selects.insert(path, select);
+                },
+                Err(msg) => errors.push(source.error(msg)),
+            }
+        }
+        // defaults come before the overrides, so what's in the environment takes precedence
+        for (path, default) in &self.source.defaults {
+            if let Err(msg) = apply_default(&mut toml, &path.to_string(), default) {
+                errors.push(path.error(msg));
+            }
+        }
+        if self.source.options.env_override {
+            errors.extend(
+                crate::env_override::apply_env_overrides(&mut toml, "", &|name| env::var(name).ok())
+                    .into_iter()
+                    .map(|msg| syn::Error::new(proc_macro2::Span::call_site(), msg)),
+            );
+        }
+
+        // all of the missing keys are reported, not just the first
+        errors.extend(
+            self.source
+                .required
+                .iter()
+                .filter(|path| {
+                    utils::resolve_keys(&toml, &path.to_string())
+                        .and_then(|keys| utils::get_at(&toml, &keys))
+                        .is_none()
+                })
+                .map(|path| path.error(format!("Required key `{}` is missing", path))),
+        );
+
+        let mut inclusions = GlobSetBuilder::new();
+        let mut exclusions = GlobSetBuilder::new();
+        let mut constrained = Vec::new();
+        let mut anywhere = GlobSetBuilder::new();
+        let mut collecting = Vec::new();
+        let mut matchers = Vec::new();
+        let mut by_value = Vec::new();
+        let mut literals: Vec<String> = Vec::new();
+        let mut indices = Vec::new();
+
+        // an alias of a table renames the whole subtree, like `alias cfg = app.*` does, and
+        // keeps the structure below it
+        let mut aliases = self.source.aliases.clone();
+        let mut subtree_aliases = self
+            .source
+            .subtree_aliases
+            .iter()
+            .map(|(alias, root)| (alias.to_string(), root.clone()))
+            .collect::<Vec<_>>();
+        let mut subtree_pats = Vec::new();
+        for (alias, orig) in &self.source.aliases {
+            let root = orig.to_string();
+            let is_table = utils::resolve_keys(&toml, &root)
+                .and_then(|keys| utils::get_at(&toml, &keys))
+                .is_some_and(|value| match value {
+                    Value::Table(_) => true,
+                    Value::Array(arr) => utils::is_table_array(arr),
+                    _ => false,
+                });
+            if !is_table {
+                continue;
+            }
+            aliases.remove(alias);
+            subtree_pats.push(
+                syn::parse_str::<Pattern>(&format!("{}.**", root))
+                    .expect("Expected an alias path to make a valid pattern"),
+            );
+            subtree_aliases.push((alias.to_string(), root));
+        }
+
+        // brace groups expand into one glob per alternative, so e.g. `!foo.{a,b}` excludes both
+        let inclusion_pats = self.source.inclusion_pats.iter().chain(&subtree_pats);
+        for pattern in inclusion_pats.flat_map(Pattern::alternatives) {
+            // an index from the end only has a place once the array's length is known
+            let pattern = match pattern.index() {
+                Some((path, index)) => match resolve_index(&toml, &path, index) {
+                    Ok(index) => {
+                        indices.push((path, index));
+                        pattern.with_index(index)
+                    },
+                    Err(msg) => {
+                        if !self.source.optional_pats.contains(&pattern) {
+                            errors.push(pattern.error(msg));
+                        }
+                        continue;
+                    },
+                },
+                None => pattern,
+            };
+            // `depth == N` can't be put in a glob, so those are checked one by one
+            if pattern.depth().is_some() {
+                constrained.push((pattern.to_glob().compile_matcher(), pattern.clone()));
+            } else {
+                inclusions.add(pattern.to_glob());
+            }
+            if pattern.is_anywhere() {
+                anywhere.add(pattern.to_glob());
+            }
+            // the wildcard stands for a single key there, which is what the value is named by
+            if let Some(segment) = pattern.collecting_segment() {
+                let glob = GlobBuilder::new(pattern.to_glob().glob())
+                    .literal_separator(true)
+                    .build()
+                    .expect("Expected a valid glob");
+                collecting.push((glob.compile_matcher(), segment));
+            }
+            matchers.push((pattern.to_glob().compile_matcher(), pattern.clone()));
+            // println!("Added inclusion pattern: {}", pattern);
+            literals.push(pattern.to_string());
+        }
+        for pattern in self.source.exclusion_pats.iter().flat_map(Pattern::alternatives) {
+            // those comparing the value leave out what has it rather than the path itself
+            if let Some(predicate) = pattern.predicate() {
+                by_value.push((pattern.to_glob().compile_matcher(), predicate.clone()));
+                continue;
+            }
+            exclusions.add(pattern.to_glob());
+            // println!("Added exclusion pattern: {}", pattern);
+            literals.push(format!("!{}", pattern));
+        }
+        let config = TomlFields::new()
+            .with_inclusion_globs(Some(
+                inclusions
+                    .build()
+                    .expect("Expected a succesful glob set build"),
+            ))
+            .with_exclusion_globs(Some(
+                exclusions
+                    .build()
+                    .expect("Expected a succesful glob set build"),
+            ))
+            .with_constrained_globs(constrained)
+            .with_value_exclusions(by_value)
+            .with_anywhere_globs(Some(
+                anywhere
+                    .build()
+                    .expect("Expected a succesful glob set build"),
+            ))
+            .with_collecting_globs(collecting)
+            .with_pattern_matchers(matchers)
+            .with_pat_literals(literals)
+            .with_indices(indices)
+            .with_comments(comments)
+            .with_os_selects(os_selects)
+            .with_source_files(self.source.source_files.clone())
+            .with_aliases(Some(aliases.clone()))
+            .with_subtree_aliases(subtree_aliases)
+            .with_alias_templates(
+                self.source
+                    .alias_templates
+                    .iter()
+                    .map(|(template, pattern)| {
+                        let path = pattern.to_string();
+                        // a `*` spans one key, a `**` any number of them
+                        let depth = (!path.contains("**")).then(|| path.split('.').count());
+                        (template.clone(), pattern.to_glob().compile_matcher(), depth)
+                    })
+                    .collect(),
+            )
+            .with_options(self.source.options.clone())
+            .with_annotations(
+                self.source
+                    .annotations
+                    .iter()
+                    .map(|(pattern, annotation)| {
+                        let mut globs = GlobSetBuilder::new();
+                        for pattern in pattern.alternatives() {
+                            globs.add(pattern.to_glob());
+                        }
+                        (
+                            globs
+                                .build()
+                                .expect("Expected a succesful glob set build"),
+                            annotation
+                                .clone()
+                                .with_config_dir(self.source.config_dir.clone()),
+                        )
+                    })
+                    .collect(),
+            );
+        let fields = config.with_root(toml).build();
+
+        // a pattern that matches nothing is most likely a typo, so point it out
+        // (or that `package.*` was used with a virtual workspace manifest, which has no package)
+        let is_virtual_manifest =
+            self.toml.get("workspace").is_some() && self.toml.get("package").is_none();
+        errors.extend(
+            self.source
+                .inclusion_pats
+                .iter()
+                .filter(|pattern| !self.source.optional_pats.contains(pattern))
+                // those with an index were checked for being there as they were resolved
+                .filter(|pattern| pattern.index().is_none())
+                .filter(|pattern| !fields.matches_any(pattern))
+                .map(|pattern| {
+                    let section = pattern.to_string();
+                    if is_virtual_manifest && section.split('.').next() == Some("package") {
+                        return pattern.error(format!(
+                            "Pattern `{}` doesn't match any value, since the workspace's manifest \
+                             is a virtual one without a `[package]`",
+                            pattern
+                        ));
+                    }
+                    let scalar = fields.document().and_then(|toml| scalar_in_prefix(toml, pattern));
+                    if let Some((key, ty)) = scalar {
+                        return pattern.error(format!(
+                            "Pattern `{}` can't match anything, since `{}` is of type {} rather \
+                             than a table to go below",
+                            pattern, key, ty
+                        ));
+                    }
+                    pattern.error(format!(
+                        "Pattern `{}` doesn't match any value (use `allow_empty {}` if that's \
+                         intended)",
+                        pattern, pattern
+                    ))
+                }),
+        );
+
+        // `raw_idents` keeps the keys as they are, so the ones that can't be kept are errors
+        if self.source.options.raw_idents {
+            let mut reported = Vec::new();
+            for pattern in &self.source.inclusion_pats {
+                for field in fields.matching(pattern) {
+                    for key in fields.raw_keys(field) {
+                        if utils::is_ident_like(&key) || reported.contains(&key) {
+                            continue;
+                        }
+                        errors.push(pattern.error(format!(
+                            "Key `{}` matched by `{}` isn't a valid identifier, and `raw_idents` \
+                             doesn't convert it (alias it, or leave `raw_idents` out)",
+                            key, pattern
+                        )));
+                        reported.push(key);
+                    }
+                }
+            }
+        }
+
+        // an alias whose path isn't there is as likely a typo, but was silently ignored before,
+        // so it's only a warning unless the section asks for `strict_aliases`
+        let mut warnings = Vec::new();
+        for (alias, orig) in &aliases {
+            if fields.used_aliases.contains(&alias.to_string()) {
+                continue;
+            }
+            let message = format!("Alias `{}` is unused, since `{}` doesn't exist", alias, orig);
+            if self.source.options.strict_aliases {
+                errors.push(orig.error(message));
+            } else {
+                warnings.push((orig.span(), message));
+            }
+        }
+
+        RootModule {
+            fields,
+            errors,
+            warnings,
+            ..self
+        }
+    }
+}
+
+impl RootModule {
+    /// Generates the `NAMES` and `PATHS` consts for `#targets(kind)`, if set.
+    ///
+    /// Targets without an explicit `path` get cargo's conventional one (e.g. `src/bin/<name>.rs`),
+    /// and implicit targets (no `[[kind]]` at all) make for empty lists rather than an error.
+    fn targets_tokens(&self) -> TokenStream2 {
+        let Some(kind) = self.source.options.targets.as_deref() else {
+            return quote! {};
+        };
+        let default_dir = match kind {
+            "bin" => "src/bin",
+            "example" => "examples",
+            "test" => "tests",
+            _ => "benches",
+        };
+        let (names, paths): (Vec<_>, Vec<_>) = self
+            .toml
+            .get(kind)
+            .and_then(Value::as_array)
+            .map(|targets| targets.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|target| {
+                // cargo requires a name for every declared target anyway
+                let name = target.get("name")?.as_str()?.to_string();
+                let path = target
+                    .get("path")
+                    .and_then(Value::as_str)
+                    .map(|path| path.to_string())
+                    .unwrap_or_else(|| format!("{}/{}.rs", default_dir, name));
+                Some((name, path))
+            })
+            .unzip();
+        let names_doc = format!("Names of the declared `[[{}]]` targets", kind);
+        let paths_doc =
+            format!("Source paths of the declared `[[{}]]` targets, in `NAMES` order", kind);
+        let vis = self.source.options.item_visibility();
+        quote! {
+            #[doc = #names_doc]
+            #vis const NAMES: &[&str] = &[#(#names),*];
+            #[doc = #paths_doc]
+            #vis const PATHS: &[&str] = &[#(#paths),*];
+        }
+    }
+}
+
+impl From<RootModuleSource> for RootModule {
+    fn from(source: RootModuleSource) -> Self {
+        RootModule {
+            source,
+            toml: Value::Table(Default::default()),
+            fields: TomlFields::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+impl From<&RootModuleSource> for RootModule {
+    fn from(source: &RootModuleSource) -> Self {
+        RootModule {
+            source: source.clone(),
+            toml: Value::Table(Default::default()),
+            fields: TomlFields::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+impl RootModuleSource {
+    /// Merges a reopened section, i.e. one with the same header, into this one.
+    ///
+    /// Its patterns, aliases, annotations and `select_os` statements are added to these, but
+    /// options are only taken from the first occurrence, so setting them again is an error, as is
+    /// aliasing the same name to another path.
+    pub fn merge(&mut self, other: RootModuleSource) -> SynResult<()> {
+        if other.options != SectionOptions::default() {
+            return Err(syn::Error::new(
+                other.name.span(),
+                format!(
+                    "Options of a reopened section go on its first occurrence, `[{}]` only adds \
+                     patterns here",
+                    other.name
+                ),
+            ));
+        }
+        for (alias, path) in other.aliases {
+            match self.aliases.get(&alias) {
+                Some(existing) if *existing != path => {
+                    return Err(alias.error(format!(
+                        "Alias `{}` is already declared for `{}` in `[{}]`",
+                        alias, existing, self.name
+                    )));
+                },
+                _ => {
+                    self.aliases.insert(alias, path);
+                },
+            }
+        }
+        self.inclusion_pats.extend(other.inclusion_pats);
+        self.exclusion_pats.extend(other.exclusion_pats);
+        self.optional_pats.extend(other.optional_pats);
+        self.required.extend(other.required);
+        self.defaults.extend(other.defaults);
+        self.subtree_aliases.extend(other.subtree_aliases);
+        self.alias_templates.extend(other.alias_templates);
+        self.annotations.extend(other.annotations);
+        self.os_selects.extend(other.os_selects);
+        Ok(())
+    }
+}
+
+/// Parses the alias of a templated alias, like the `d_*` of `alias d_* = dependencies.*`,
+/// leaving the input as it is when the alias has no `*` in it.
+fn parse_alias_template(input: ParseStream) -> SynResult<Option<String>> {
+    let fork = input.fork();
+    let mut template = String::new();
+    while !fork.peek(Token![=]) {
+        if fork.peek(Token![*]) {
+            let star: Token![*] = fork.parse()?;
+            if template.contains('*') {
+                return Err(syn::Error::new(star.span, "A templated alias takes a single `*`"));
+            }
+            template.push('*');
+        } else if fork.peek(Token![-]) || fork.peek(Token![_]) {
+            // a lone `_` isn't an ident, and dashes join with one
+            if fork.parse::<Option<Token![-]>>()?.is_none() {
+                fork.parse::<Token![_]>()?;
+            }
+            template.push('_');
+        } else if fork.peek(syn::Ident) {
+            template.push_str(&fork.parse::<Ident>()?.to_string());
+        } else {
+            return Ok(None);
+        }
+    }
+    if !template.contains('*') {
+        return Ok(None);
+    }
+    input.advance_to(&fork);
+    Ok(Some(template))
+}
+
+/// Parses the lines of an ignore file as exclusion patterns, written with or without the `!`.
+/// Blank lines and lines starting with `#` are skipped.
+fn parse_ignore_patterns(raw: &str) -> Result<Vec<Pattern>, String> {
+    let parse_line = |input: ParseStream| {
+        let _negation: Option<Token![!]> = input.parse()?;
+        Pattern::parse(input)?.parse_predicate(input)
+    };
+    raw.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| {
+            syn::parse::Parser::parse_str(parse_line, line)
+                .map_err(|err| format!("line {} (`{}`) isn't a pattern: {}", number, line, err))
+        })
+        .collect()
+}
+
+/// Finds the key in the literal prefix of a pattern that isn't a table, like `name` of
+/// `name.*` for a `name = "x"`, which is why the pattern can't match anything below it.
+///
+/// # Returns
+/// The key as written in the pattern, and the type of its value in the TOML.
+fn scalar_in_prefix(toml: &Value, pattern: &Pattern) -> Option<(String, &'static str)> {
+    let path = pattern.to_string();
+    let segments = path.split('.').collect::<Vec<_>>();
+    let prefix = segments
+        .iter()
+        .take_while(|segment| !segment.contains(['*', '?', '{', '[']))
+        .count();
+    (1..=prefix.min(segments.len() - 1)).find_map(|len| {
+        let key = segments[..len].join(".");
+        let value = utils::resolve_keys(toml, &key).and_then(|keys| utils::get_at(toml, &keys))?;
+        let is_table = match value {
+            Value::Table(_) => true,
+            Value::Array(arr) => utils::is_table_array(arr),
+            _ => false,
+        };
+        (!is_table).then(|| (key, value.type_str()))
+    })
+}
+
+/// Resolves the index of a pattern like `package.authors[-1]` against the array at `path`,
+/// counting a negative one from the end.
+///
+/// # Returns
+/// The index from the start, or why there's no element to bind at it.
+fn resolve_index(toml: &Value, path: &str, index: i64) -> Result<usize, String> {
+    let array = utils::resolve_keys(toml, path)
+        .and_then(|keys| utils::get_at(toml, &keys))
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("`{}` isn't an array to index", path))?;
+    let resolved = if index < 0 { array.len() as i64 + index } else { index };
+    let Some(element) = usize::try_from(resolved).ok().and_then(|i| array.get(i)) else {
+        return Err(format!(
+            "Index {} is out of range for `{}`, which has {} elements",
+            index,
+            path,
+            array.len()
+        ));
+    };
+    if element.is_table() {
+        return Err(format!(
+            "`{}[{}]` is a table, whose values are selected with `{}._{}.*` instead",
+            path, index, path, resolved
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+impl Parse for RootModuleSource {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let bracket_stream;
+        let _bracket = syn::bracketed!(bracket_stream in input);
+        let kind = if bracket_stream.peek(Token![struct]) {
+            let _struct: Token![struct] = bracket_stream.parse()?;
+            RootModuleKind::Struct
+        } else {
+            RootModuleKind::Module
+        };
+        let root_mod_name: Ident = bracket_stream.parse()?;
+        let mut options = SectionOptions::default();
+        while !bracket_stream.is_empty() {
+            options.parse_flag(&bracket_stream)?;
+        }
+        let mut inclusion_pats = Vec::new();
+        let mut exclusion_pats = Vec::new();
+        let mut optional_pats = Vec::new();
+        let mut required = Vec::new();
+        let mut defaults = Vec::new();
+        let mut aliases: BTreeMap<Pattern, Pattern> = BTreeMap::new();
+        let mut subtree_aliases = Vec::new();
+        let mut alias_templates = Vec::new();
+        let mut annotations = Vec::new();
+        let mut os_selects = Vec::new();
+
+        while !input.peek(token::Bracket) && !input.is_empty() {
+            if input.peek(Token![#]) {
+                options.parse_directive(input)?;
+            } else if input.peek(kw::alias) {
+                let _kw: kw::alias = input.parse()?;
+                if let Some(template) = parse_alias_template(input)? {
+                    let _eq: token::Eq = input.parse()?;
+                    let path: Pattern = input.parse()?;
+                    if !path.to_string().contains('*') {
+                        return Err(path.error(format!(
+                            "`{}` has a `*` for the key of each match, so the path needs a `*` \
+                             or `**` to match with",
+                            template
+                        )));
+                    }
+                    // the matches are bound by the alias itself, like with a subtree alias
+                    inclusion_pats.push(path.clone());
+                    alias_templates.push((template, path));
+                    continue;
+                }
+                let alias: Pattern = input.parse()?;
+                let _eq: token::Eq = input.parse()?;
+                let path: Pattern = input.parse()?;
+                if let Some(root) = path.subtree_root() {
+                    // re-roots the whole subtree under the alias, and since the subtree is
+                    // included like any other pattern, exclusions (`!app.secret`) apply within it
+                    subtree_aliases.push((alias, root));
+                    inclusion_pats.push(path);
+                } else {
+                    aliases.insert(alias, path);
+                }
+            } else if peek_select_os(input) {
+                let _kw = utils::parse_kebab_name(input)?;
+                let target: Pattern = input.parse()?;
+                let _from: kw::from = input.parse()?;
+                let source: Pattern = input.parse()?;
+                for path in [&target, &source] {
+                    if path.to_string().contains(['*', '?', '{', '[']) {
+                        return Err(path.error("`select_os` takes plain paths, not patterns"));
+                    }
+                }
+                // the target is bound by the statement itself, any errors come from selecting
+                optional_pats.push(target.clone());
+                inclusion_pats.push(target.clone());
+                os_selects.push((target, source));
+            } else if input.peek(kw::require) && !input.peek2(Token![.]) {
+                let _kw: kw::require = input.parse()?;
+                let path: Pattern = input.parse()?;
+                if path.to_string().contains(['*', '?', '{', '[']) {
+                    return Err(path.error("`require` takes plain paths, not patterns"));
+                }
+                required.push(path);
+            } else if input.peek(kw::allow_empty) {
+                let _kw: kw::allow_empty = input.parse()?;
+                let pattern = Pattern::parse(input)?.parse_depth(input)?;
+                optional_pats.push(pattern.clone());
+                inclusion_pats.push(pattern);
+            } else if input.peek(Token![!]) {
+                let _negation: Token![!] = input.parse()?;
+                let pattern = Pattern::parse(input)?.parse_predicate(input)?;
+                exclusion_pats.push(pattern)
+            } else {
+                let pattern = Pattern::parse(input)?.parse_depth(input)?;
+                if input.peek(Token![?]) && input.peek2(Token![=]) {
+                    if pattern.to_string().contains(['*', '?', '{', '[']) {
+                        return Err(pattern.error("Defaults are for plain paths, not patterns"));
+                    }
+                    defaults.push((pattern.clone(), parse_default(input)?));
+                }
+                if input.peek(Token![as]) {
+                    let _as: Token![as] = input.parse()?;
+                    let annotation: Annotation = input.parse()?;
+                    annotations.push((pattern.clone(), annotation));
+                }
+                inclusion_pats.push(pattern);
+            }
+        }
+        if options.derives.is_some() && kind != RootModuleKind::Struct {
+            return Err(syn::Error::new(
+                root_mod_name.span(),
+                "`derive(..)` only applies to struct sections, e.g. `[struct Name derive(Default)]`",
+            ));
+        }
+        if options.flatten_sep.is_some()
+            && matches!(options.flatten.as_deref(), Some("full" | "hierarchy"))
+        {
+            return Err(syn::Error::new(
+                root_mod_name.span(),
+                "`flatten_sep(..)` is the separator of `flatten(joined)`, so it can't be used \
+                 with another `flatten(..)`",
+            ));
+        }
+        if options.serde && kind != RootModuleKind::Struct {
+            return Err(syn::Error::new(
+                root_mod_name.span(),
+                "`serde` only applies to struct sections, e.g. `[struct Name serde]`",
+            ));
+        }
+        if options.intern_strings && kind == RootModuleKind::Struct {
+            return Err(syn::Error::new(
+                root_mod_name.span(),
+                "`intern_strings` only applies to module sections, where the pool can be private",
+            ));
+        }
+        if options.as_bitflags && kind == RootModuleKind::Struct {
+            return Err(syn::Error::new(
+                root_mod_name.span(),
+                "`as_bitflags` only applies to module sections, the `Flags` can't be an associated \
+                 item",
+            ));
+        }
+        if options.as_phf && kind == RootModuleKind::Struct {
+            return Err(syn::Error::new(
+                root_mod_name.span(),
+                "`as_phf` only applies to module sections, the `MAP` can't be an associated item",
+            ));
+        }
+        Ok(RootModuleSource {
+            name: root_mod_name,
+            kind,
+            inclusion_pats,
+            exclusion_pats,
+            optional_pats,
+            required,
+            defaults,
+            aliases,
+            subtree_aliases,
+            alias_templates,
+            annotations,
+            os_selects,
+            options,
+            comments: BTreeMap::new(),
+            config_dir: None,
+            source_files: Vec::new(),
+        })
+    }
+}
+
+// the `?= literal` after a path, as the TOML value of the literal
+fn parse_default(input: ParseStream) -> SynResult<Value> {
+    let _question: Token![?] = input.parse()?;
+    let _eq: Token![=] = input.parse()?;
+    let negative = input.parse::<Option<Token![-]>>()?.is_some();
+    let sign = if negative { "-" } else { "" };
+    let value = match input.parse::<syn::Lit>()? {
+        syn::Lit::Str(lit) if !negative => Value::String(lit.value()),
+        syn::Lit::Bool(lit) if !negative => Value::Boolean(lit.value),
+        syn::Lit::Int(lit) => Value::Integer(
+            format!("{}{}", sign, lit.base10_digits())
+                .parse()
+                .map_err(|_| syn::Error::new(lit.span(), "Expected an `i64` integer"))?,
+        ),
+        syn::Lit::Float(lit) => Value::Float(
+            format!("{}{}", sign, lit.base10_digits())
+                .parse()
+                .map_err(|_| syn::Error::new(lit.span(), "Expected an `f64` float"))?,
+        ),
+        lit => {
+            return Err(syn::Error::new(
+                lit.span(),
+                "Expected a string, integer, float or bool literal as the default",
+            ))
+        },
+    };
+    Ok(value)
+}
+
+/// Puts `default` at the normalized dotted `path` (see `utils::resolve_keys`) if there's
+/// nothing there, making the tables on the way that are missing too.
+///
+/// # Returns
+/// Why the default can't be used, if the value that's there is of another type than it, or
+/// a key on the way isn't a table.
+fn apply_default(toml: &mut Value, path: &str, default: &Value) -> Result<(), String> {
+    let segments = path.split('.').collect::<Vec<_>>();
+    let not_table = |len: usize| {
+        let parent = segments[..len].join(".");
+        format!("`{}` isn't a table, so `{}` can't have a default", parent, path)
+    };
+    let (last, parents) = segments.split_last().expect("Expected a path with a key");
+    let mut current = toml;
+    for (i, segment) in parents.iter().enumerate() {
+        let Value::Table(table) = current else {
+            return Err(not_table(i));
+        };
+        let key = utils::find_key(table, segment).unwrap_or_else(|| segment.to_string());
+        current = table
+            .entry(key)
+            .or_insert_with(|| Value::Table(Default::default()));
+    }
+    let Value::Table(table) = current else {
+        return Err(not_table(parents.len()));
+    };
+    let key = utils::find_key(table, last).unwrap_or_else(|| last.to_string());
+    match table.get(&key) {
+        Some(existing) if !existing.same_type(default) => Err(format!(
+            "`{}` is of type `{}` in the TOML, but its default `{}` is of type `{}`",
+            path,
+            existing.type_str(),
+            default,
+            default.type_str()
+        )),
+        Some(_) => Ok(()),
+        None => {
+            table.insert(key, default.clone());
+            Ok(())
+        },
+    }
+}
+
+// there's no stable way for proc macros to emit warnings, so using a deprecated item stands in
+fn warning_tokens(span: Span, message: &str) -> TokenStream2 {
+    let usage = quote_spanned! {span=> let _ = TomlfuseWarning; };
+    quote! {
+        const _: () = {
+            #[deprecated(note = #message)]
+            struct TomlfuseWarning;
+            #usage
+        };
+    }
+}
+
+// `select_os` isn't a keyword like `alias` is, since it's also accepted as `select-os`
+fn peek_select_os(input: ParseStream) -> bool {
+    let fork = input.fork();
+    utils::parse_kebab_name(&fork).is_ok_and(|name| utils::kebab_to_snake(&name) == "select_os")
+        && fork.peek(syn::Ident)
+}
+
+impl ToTokens for RootModule {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let fields = &self.fields;
+        let root_mod_name = &self.source.name;
+        tokens.extend(self.errors.iter().map(syn::Error::to_compile_error));
+        tokens.extend(self.warnings.iter().map(|(span, message)| warning_tokens(*span, message)));
+        let targets = self.targets_tokens();
+        let vis = self.source.options.item_visibility();
+        let checksum = if self.source.options.checksum {
+            let checksum = fields.checksum();
+            quote! {
+                /// Checksum of the values bound by this section, changes whenever any of them do
+                #vis const CHECKSUM: u64 = #checksum;
+            }
+        } else {
+            quote! {}
+        };
+        // a section that ended up with nothing in it (only `allow_empty` patterns can match
+        // nothing without an error) isn't generated at all, like empty tables within it aren't
+        let generated = fields.to_token_stream();
+        if targets.is_empty() && generated.is_empty() {
+            return;
+        }
+        if self.source.kind == RootModuleKind::Struct {
+            let options = &self.source.options;
+            if options.derives.is_some() || options.serde {
+                let derives = options.derives.as_deref().unwrap_or_default();
+                fields.generate_data_struct(0, root_mod_name, derives, tokens);
+            } else {
+                fields.generate_struct(0, root_mod_name, tokens);
+            }
+            if !checksum.is_empty() || !targets.is_empty() {
+                tokens.extend(quote! {
+                    impl #root_mod_name {
+                        #targets
+                        #checksum
+                    }
+                });
+            }
+            return;
+        }
+        let root_vis = self.source.options.root_visibility();
+        let attrs = self.source.options.extra_attrs();
+        tokens.extend(quote! {
+            #attrs
+            #root_vis mod #root_mod_name {
+                #generated
+                #targets
+                #checksum
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(source: &str, toml: &str) -> String {
+        let source = syn::parse_str::<RootModuleSource>(source).expect("Expected a valid section");
+        RootModule::from(source)
+            .with_toml(toml.parse().expect("Expected valid toml"))
+            .build()
+            .to_token_stream()
+            .to_string()
+    }
+
+    /// Sets an environment variable for the rest of a test, and removes it when dropped, so
+    /// the other tests of the binary don't see it even if the test fails.
+    struct EnvVarGuard(&'static str);
+
+    impl EnvVarGuard {
+        fn set(name: &'static str, value: &str) -> Self {
+            env::set_var(name, value);
+            EnvVarGuard(name)
+        }
+    }
+
+    impl Drop for EnvVarGuard {
+        fn drop(&mut self) {
+            env::remove_var(self.0);
+        }
+    }
+
+    #[test]
+    fn test_generation_is_reproducible() {
+        let source = "[section]
+            a.*
+            alias first = b.x
+            alias second = b.y
+            alias third = b.z";
+        let toml = "[a]\nk = 1\nl = [1, 2]\n[b]\nx = 1\ny = 2\nz = 3\n[a.sub]\nm = \"m\"";
+        let first = generate(source, toml);
+        for _ in 0..10 {
+            assert_eq!(generate(source, toml), first);
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "preserve-order"))]
+    fn test_items_are_sorted_by_name() {
+        // the alias is found where `b` is, between `a` and `c`, but sorts last
+        let generated = generate(
+            "[section] x.* alias zeta = x.b",
+            "[x]\na = 1\nb = 2\nc = 3\n[x.beta]\nv = 1\n[x.alpha]\nv = 2",
+        );
+        let positions = ["const A", "const C", "const ZETA", "mod alpha", "mod beta"]
+            .map(|item| generated.find(item).unwrap_or_else(|| panic!("{}", generated)));
+        assert!(positions.windows(2).all(|w| w[0] < w[1]), "{}", generated);
+    }
+
+    #[test]
+    #[cfg(feature = "preserve-order")]
+    fn test_items_keep_the_order_of_the_file() {
+        // values still come before the modules of tables
+        let generated = generate(
+            "[section] x.**",
+            "[x]\nzeta = 1\nalpha = 2\n[x.inner]\nz = 1\ny = 2\n[x.first]\nmid = 3",
+        );
+        let positions = ["ZETA", "ALPHA", "mod inner", "Z :", "Y :", "mod first", "MID"]
+            .map(|item| generated.find(item).unwrap_or_else(|| panic!("{}", generated)));
+        assert!(positions.windows(2).all(|w| w[0] < w[1]), "{}", generated);
+    }
+
+    #[test]
+    fn test_keep_case_allows_only_lowercase_consts() {
+        let generated = generate("[section keep_case] x.*", "[x]\nlower = 1\nUPPER = 2");
+        let allow = "# [allow (non_upper_case_globals)]";
+        assert!(generated.contains(&format!("{} pub const lower", allow)), "{}", generated);
+        assert!(generated.contains("pub const UPPER"), "{}", generated);
+        assert!(!generated.contains(&format!("{} pub const UPPER", allow)), "{}", generated);
+    }
+
+    #[test]
+    fn test_as_fns_names() {
+        let toml = "[app]\ntype = \"a\"\nmatch = 1\nmaxRetries = 3";
+        let generated = generate("[app as_fns] app.*", toml);
+        assert!(generated.contains("pub fn r#type ()"), "{}", generated);
+        assert!(generated.contains("pub fn r#match ()"), "{}", generated);
+        assert!(generated.contains("pub fn max_retries ()"), "{}", generated);
+        let toml = "[app]\nmax_retries = 3";
+        let generated = generate("[app as_fns keep_case] app.max_retries", toml);
+        assert!(generated.contains("can't add a fn `max_retries`"), "{}", generated);
+    }
+
+    #[test]
+    fn test_collecting_pattern() {
+        let toml = "[dependencies]\nglob = \"0.3\"\nserde = { version = \"1.0\" }\n\
+                    [dependencies.toml]\nversion = \"0.8\"\nfeatures = [\"parse\"]";
+        let generated = generate("[deps name_by_wildcard] dependencies.*.version", toml);
+        assert!(generated.contains("pub const SERDE : & 'static str = \"1.0\""), "{}", generated);
+        assert!(generated.contains("pub const TOML : & 'static str = \"0.8\""), "{}", generated);
+        // there's nothing left for the modules of the dependencies to have
+        assert!(!generated.contains("mod serde"), "{}", generated);
+        assert!(!generated.contains("VERSION"), "{}", generated);
+        assert!(!generated.contains("GLOB"), "{}", generated);
+
+        // the wildcard stands for a single key, so deeper matches keep their modules
+        let source = "[deps name_by_wildcard] dependencies.*.version";
+        let generated = generate(source, "[dependencies.a.b]\nversion=1");
+        assert!(generated.contains("mod b"), "{}", generated);
+
+        let source = "[deps name_by_wildcard flatten(joined)] dependencies.*.version";
+        let generated = generate(source, toml);
+        assert!(generated.contains("pub const DEPENDENCIES_SERDE"), "{}", generated);
+
+        // without the flag, each match keeps its module, so patterns sharing the wildcard don't
+        // collide
+        let toml = "[dependencies.serde]\nversion = \"1.0\"\noptional = true";
+        let generated = generate("[deps] dependencies.*.version dependencies.*.optional", toml);
+        assert!(generated.contains("mod serde"), "{}", generated);
+        assert!(generated.contains("pub const VERSION"), "{}", generated);
+        assert!(generated.contains("pub const OPTIONAL"), "{}", generated);
+    }
+
+    #[test]
+    fn test_overlapping_patterns_keep_hierarchy() {
+        // a key repeating a segment of the pattern is not stripped of it, only the prefix is
+        let generated = generate(
+            "[section] config.* config.settings.*",
+            "[config]\ndebug = false\n[config.settings]\ntimeout = 500\n\
+             [config.settings.config]\nlevel = 1",
+        );
+        assert!(
+            generated.contains(
+                "pub const DEBUG : bool = false ; pub const TIMEOUT : i64 = 500i64 ; \
+                 pub mod config { pub const LEVEL : i64 = 1i64 ; }"
+            ),
+            "{}",
+            generated
+        );
+    }
+
+    #[test]
+    fn test_empty_section_generates_nothing() {
+        let toml = "[a.b]\n[a.c.d]\n[e]\nf = {}";
+        for section in [
+            "[section] allow_empty missing.*",
+            "[section] allow_empty a.** allow_empty e.*",
+            "[section as_map count checksum] allow_empty a.**",
+            "[struct Section] allow_empty a.**",
+            "[struct Section derive(Default)] allow_empty a.**",
+        ] {
+            assert_eq!(generate(section, toml), "", "{}", section);
+        }
+        let partly = generate("[section] allow_empty a.** e.*", "[a.b]\n[e]\nx = 1");
+        assert_eq!(partly, "pub mod section { pub const X : i64 = 1i64 ; }");
+    }
+
+    #[test]
+    fn test_flatten_joined_with_nested_annotation() {
+        let toml = "[a.b]\nc.d = 1\ne = 2";
+        let generated = generate("[section flatten(joined)] a.b.* as nested", toml);
+        assert!(generated.contains("pub mod a_b_c { pub const D"), "{}", generated);
+        assert!(generated.contains("A_B_E"), "{}", generated);
+        let err = syn::parse_str::<RootModuleSource>(r#"[s flatten(full) flatten_sep("_")] a.*"#);
+        assert!(err.unwrap_err().to_string().contains("can't be used"));
+    }
+
+    #[test]
+    fn test_collapse_singletons() {
+        let toml = "[app.database]\nurl = \"db\"\n[app.server]\nhost = \"h\"\nport = 1\n\
+                    [app.deep.er]\nkey = 1\n[app.named]\nkey = 1";
+        let generated = generate("[section collapse_singletons] app.* app.named.*", toml);
+        assert!(generated.contains("pub const DATABASE_URL"), "{}", generated);
+        assert!(generated.contains("pub const DEEP_ER_KEY"), "{}", generated);
+        assert!(generated.contains("pub mod server"), "{}", generated);
+        assert!(generated.contains("pub const KEY"), "{}", generated);
+        assert!(!generated.contains("pub mod database"), "{}", generated);
+        let source = "[section flatten(hierarchy) collapse_singletons] app.named.*";
+        let generated = generate(source, toml);
+        assert!(generated.contains("pub mod named { pub const KEY"), "{}", generated);
+        let generated = generate("[section] app.*", toml);
+        assert!(generated.contains("pub mod database"), "{}", generated);
+    }
+
+    #[test]
+    fn test_excluded_tables() {
+        let toml = "[app]\nname = 1\nsecret = 2\n[app.logging]\nlevel = 1\n\
+                    [app.logging.deep]\nx = 1";
+        let expected = "pub mod section { pub const NAME : i64 = 1i64 ; }";
+        for section in [
+            "[section] app.* !app.logging.* !app.secret",
+            "[section] app.** !app.logging !app.secret",
+            "[section] app.** !app.logging.deep !app.logging.level !app.secret",
+        ] {
+            assert_eq!(generate(section, toml), expected, "{}", section);
+        }
+        // a negated leaf leaves its siblings be
+        let generated = generate("[section] app.** !app.logging.level", toml);
+        let logging = "pub mod logging { pub mod deep { pub const X";
+        assert!(generated.contains(logging), "{}", generated);
+        assert!(generated.contains("pub const SECRET"), "{}", generated);
+    }
+
+    #[test]
+    fn test_parse_ignore_patterns() {
+        let raw = "# shared secrets\n\napp.secret\n  !app.logging.**  \n!*.optional == true\n";
+        let patterns = parse_ignore_patterns(raw).unwrap();
+        let patterns = patterns.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(patterns, ["app.secret", "app.logging.**", "*.optional"]);
+        let err = parse_ignore_patterns("app.secret\napp secret").unwrap_err();
+        assert!(err.starts_with("line 2 (`app secret`) isn't a pattern"), "{}", err);
+    }
+
+    #[test]
+    fn test_intern_strings() {
+        let toml = "[app]\nhost = \"localhost\"\nname = \"app\"\n\
+                    [app.db]\nhost = \"localhost\"\n[app.db.replica]\nhost = \"localhost\"";
+        let generated = generate("[section intern_strings] app.*", toml);
+        let expected = [
+            "const __STR_0 : & str = \"localhost\" ;",
+            "pub const HOST : & 'static str = __STR_0 ;",
+            "pub const NAME : & 'static str = \"app\" ;",
+            "pub mod db { pub const HOST : & 'static str = super :: __STR_0 ;",
+            "pub const HOST : & 'static str = super :: super :: __STR_0 ;",
+        ];
+        for expected in expected {
+            assert!(generated.contains(expected), "{}\n{}", expected, generated);
+        }
+        assert!(!generated.contains("__STR_1"), "{}", generated);
+        assert!(!generate("[section] app.*", toml).contains("__STR_0"));
+        let err = syn::parse_str::<RootModuleSource>("[struct S intern_strings] app.*");
+        assert!(err.unwrap_err().to_string().contains("only applies to module sections"));
+    }
+
+    #[test]
+    fn test_alias_templates() {
+        let toml = "[deps]\nserde = \"1\"\nserde-json = \"1\"\n\
+                    tokio = { version = \"1\" }\nrand = \"0.8\"";
+        let generated = generate("[section] alias d_* = deps.* !deps.rand", toml);
+        let expected = [
+            "pub const D_SERDE : & 'static str = \"1\" ;",
+            "pub const D_SERDE_JSON",
+            "pub mod d_tokio { pub const VERSION",
+        ];
+        for expected in expected {
+            assert!(generated.contains(expected), "{}\n{}", expected, generated);
+        }
+        assert!(!generated.contains("RAND"), "{}", generated);
+        // with a `**`, every key below is renamed
+        let generated = generate("[section] alias dep-* = deps.**", toml);
+        assert!(generated.contains("pub mod dep_tokio { pub const DEP_VERSION"), "{}", generated);
+        for (source, err) in [
+            ("[s] alias d_* = deps.serde", "needs a `*` or `**`"),
+            ("[s] alias *_* = deps.*", "takes a single `*`"),
+        ] {
+            let parsed = syn::parse_str::<RootModuleSource>(source);
+            assert!(parsed.unwrap_err().to_string().contains(err), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_defaults() {
+        let toml = "[package]\nrust-version = \"1.73\"\nname = \"x\"";
+        let source = "[section] package.rust_version ?= \"1.70\" package.edition ?= \"2021\" \
+                      package.metadata.retries ?= -3 package.metadata.ratio ?= 0.5";
+        let generated = generate(source, toml);
+        let consts = [
+            "RUST_VERSION : & 'static str = \"1.73\"",
+            "EDITION : & 'static str = \"2021\"",
+            "RETRIES : i64 = - 3i64",
+            "RATIO : f64 = 0.5f64",
+        ];
+        for item in consts {
+            assert!(generated.contains(item), "{}", generated);
+        }
+        let mismatch = generate("[section] package.rust_version ?= 170", toml);
+        let error = "`package.rust_version` is of type `string` in the TOML, but its default `170` \
+                     is of type `integer`";
+        assert!(mismatch.contains(error), "{}", mismatch);
+        let not_table = generate("[section] package.name.first ?= true", toml);
+        assert!(not_table.contains("`package.name` isn't a table"), "{}", not_table);
+        // a `?` not followed by `=` is still a wildcard
+        assert!(generate("[section] package.nam?", toml).contains("NAME"));
+        let err = syn::parse_str::<RootModuleSource>("[s] package.* ?= 1").unwrap_err();
+        assert!(err.to_string().contains("plain paths"));
+        let err = syn::parse_str::<RootModuleSource>("[s] package.name ?= b'x'").unwrap_err();
+        assert!(err.to_string().contains("Expected a string, integer, float or bool"));
+    }
+
+    #[test]
+    fn test_required_keys() {
+        let toml = "[package]\nversion = \"0.1.0\"\nrequire = true";
+        // what's required isn't bound, and a key named `require` is still a pattern
+        let source = "[section] require package.version package.require";
+        assert_eq!(generate(source, toml), "pub mod section { pub const REQUIRE : bool = true ; }");
+        let generated = generate("[section] require package.license require package.readme", toml);
+        assert!(generated.contains("Required key `package.license` is missing"), "{}", generated);
+        assert!(generated.contains("Required key `package.readme` is missing"), "{}", generated);
+        let err = syn::parse_str::<RootModuleSource>("[s] require package.*").unwrap_err();
+        assert!(err.to_string().contains("plain paths"));
+    }
+
+    #[test]
+    fn test_fields_own_the_bound_toml() {
+        let source = syn::parse_str::<RootModuleSource>("[section] a.*").unwrap();
+        // the fields own the values they bind, so building many modules from owned values frees
+        // each value along with its module instead of leaking it
+        for i in 0..1000 {
+            let toml: Value = format!("[a]\nx = {}", i).parse().unwrap();
+            let module = RootModule::from(&source).with_toml(toml.clone()).build();
+            assert_eq!(module.fields.document(), Some(&toml));
+            assert!(module.fields.root_value.is_none());
+            let expected = format!("pub mod section {{ pub const X : i64 = {}i64 ; }}", i);
+            assert_eq!(module.to_token_stream().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_array_index() {
+        let toml = "[package]\nauthors = [\"a\", \"b\", \"c\"]\nname = \"x\"\n\
+                    [[bin]]\nname = \"x\"";
+        // the index is told apart from the header of the next section
+        let source = "[section] package.authors[0] package.authors[-1] [next] package.name";
+        let source = syn::parse_str::<crate::input::MacroInput>(source).unwrap();
+        assert_eq!(source.root_module_sources.len(), 2);
+        let generated = generate("[section] package.authors[0] package.authors[-1]", toml);
+        assert_eq!(
+            generated,
+            "pub mod section { pub const AUTHORS_0 : & 'static str = \"a\" ; \
+             pub const AUTHORS_2 : & 'static str = \"c\" ; }"
+        );
+        let errors = [
+            ("package.authors[3]", "Index 3 is out of range for `package.authors`"),
+            ("package.authors[-4]", "Index -4 is out of range for `package.authors`"),
+            ("package.name[0]", "`package.name` isn't an array to index"),
+            ("bin[0]", "whose values are selected with `bin._0.*` instead"),
+        ];
+        for (pattern, error) in errors {
+            let generated = generate(&format!("[section] {}", pattern), toml);
+            assert!(generated.contains(error), "{}", generated);
+            assert!(!generated.contains("doesn't match any value"), "{}", generated);
+        }
+    }
+
+    #[test]
+    fn test_companion_collisions() {
+        let generated = generate("[section with_docs] a.*", "[a]\nname = 1\nname_doc = 2");
+        let error = "`a.name` and `a.name_doc` both generate the const `NAME_DOC`";
+        assert!(generated.contains(error), "{}", generated);
+        let generated = generate("[section with_docs] a.*", "[a]\nname = 1\nother = 2");
+        assert!(!generated.contains("both generate"), "{}", generated);
+        // only arrays get a `<NAME>_LEN` that can collide
+        let toml = "[a]\nitems = [1, 2]\nitems_len = 3\n[b]\nitems_len = 3\nitems = 1";
+        let generated = generate("[section array_lens] a.*", toml);
+        let error = "`a.items` and `a.items_len` both generate the const `ITEMS_LEN`";
+        assert!(generated.contains(error), "{}", generated);
+        let generated = generate("[section array_lens] b.*", toml);
+        assert!(!generated.contains("both generate"), "{}", generated);
+    }
+
+    #[test]
+    fn test_array_lens() {
+        let generated = generate(
+            "[section array_lens] a.*",
+            "[a]\nnone = []\nsome = [1, 2]\nmixed = [1, \"a\"]\nscalar = 1",
+        );
+        let lens = [
+            "NONE_LEN : usize = 0usize",
+            "SOME_LEN : usize = 2usize",
+            "MIXED_LEN : usize = 2usize",
+        ];
+        for len in lens {
+            assert!(generated.contains(len), "{}", generated);
+        }
+        assert!(!generated.contains("SCALAR_LEN"), "{}", generated);
+        assert!(!generate("[section] a.*", "[a]\nsome = [1]").contains("_LEN"));
+    }
+
+    #[test]
+    fn test_exclude_by_value() {
+        let toml = "debug = true\nname = \"x\"\n[deps]\nserde = \"1\"\n\
+                    tokio = { version = \"1\", optional = true }\n\
+                    rand = { version = \"0.8\", optional = false }";
+        let generated = generate("[section] ** !*.optional == true !debug == true", toml);
+        assert!(generated.contains("pub mod rand"), "{}", generated);
+        assert!(generated.contains("const NAME"), "{}", generated);
+        assert!(!generated.contains("tokio"), "{}", generated);
+        assert!(!generated.contains("DEBUG"), "{}", generated);
+    }
+
+    #[test]
+    fn test_array_element_docs() {
+        let toml = "[app]\n# where it runs\ntargets = [\n  \"linux\", # the main one\n  \
+                    \"macos\", # on request\n  \"windows\", # most users\n]";
+        let mut source = syn::parse_str::<RootModuleSource>("[section] app.*").unwrap();
+        source.comments = extract_comments(toml);
+        let generated = RootModule::from(source)
+            .with_toml(toml.parse().unwrap())
+            .build()
+            .to_token_stream()
+            .to_string();
+        let doc = "where it runs\\n\\n- `\\\"linux\\\"`: the main one\\n\
+                   - `\\\"macos\\\"`: on request\\n- `\\\"windows\\\"`: most users";
+        assert!(generated.contains(doc), "{}", generated);
+    }
+
+    #[test]
+    fn test_inner_docs() {
+        let toml = "[a]\nk = 1\n# the sub table\n[a.sub]\n# the m\nm = 1";
+        let generate = |source: &str| {
+            let mut source = syn::parse_str::<RootModuleSource>(source).unwrap();
+            source.comments = extract_comments(toml);
+            RootModule::from(source)
+                .with_toml(toml.parse().unwrap())
+                .build()
+                .to_token_stream()
+                .to_string()
+        };
+        let inner = generate("[section inner_docs] a.**");
+        assert!(
+            inner.contains("pub mod sub { # ! [doc = \"the sub table\"] # [doc = \"the m\"]"),
+            "{}",
+            inner
+        );
+        let outer = generate("[section] a.**");
+        assert!(outer.contains("# [doc = \"the sub table\"] pub mod sub {"), "{}", outer);
+    }
+
+    #[test]
+    fn test_quoted_keys_with_dots() {
+        let toml = "\"foo.bar\" = 1\n[t]\n# the dotted one\n\"a.b\".c = 2";
+        let mut source = syn::parse_str::<RootModuleSource>("[section] foo_bar t.**").unwrap();
+        source.comments = extract_comments(toml);
+        let generated = RootModule::from(source)
+            .with_toml(toml.parse().unwrap())
+            .build()
+            .to_token_stream()
+            .to_string();
+        assert!(generated.contains("pub const FOO_BAR : i64 = 1"), "{}", generated);
+        assert!(
+            generated.contains("pub mod a_b { # [doc = \"the dotted one\"] pub const C"),
+            "{}",
+            generated
+        );
+    }
+
+    #[test]
+    fn test_group_marker_is_not_documented() {
+        let toml = "[a]\n# the port\n# @group net\nport = 1";
+        let mut source = syn::parse_str::<RootModuleSource>("[section] a.*").unwrap();
+        source.comments = extract_comments(toml);
+        let generated = RootModule::from(source)
+            .with_toml(toml.parse().unwrap())
+            .build()
+            .to_token_stream()
+            .to_string();
+        assert!(generated.contains("pub mod net"), "{}", generated);
+        assert!(generated.contains("\"the port\""), "{}", generated);
+        assert!(!generated.contains("@group"), "{}", generated);
+    }
+
+    #[test]
+    fn test_env_override() {
+        let toml = "[app]\nversion = \"1.0.0\"\nport = 80";
+        let _version = EnvVarGuard::set("TOMLFUSE_app_version", "2.0.0-env");
+        let overridden = generate("[app env_override] app.*", toml);
+        assert!(overridden.contains("\"2.0.0-env\""), "{}", overridden);
+        assert!(overridden.contains("TOMLFUSE_app_version"), "{}", overridden);
+        // not set, so the toml value stays
+        assert!(overridden.contains("80i64"), "{}", overridden);
+        // only sections that opt in are affected
+        let plain = generate("[app] app.*", toml);
+        assert!(plain.contains("\"1.0.0\""), "{}", plain);
+        assert!(!plain.contains("TOMLFUSE_"), "{}", plain);
+    }
+
+    #[test]
+    fn test_virtual_manifest_has_no_package() {
+        let toml = "[workspace]\nmembers = [\"a\"]\n[workspace.package]\nversion = \"1.0.0\"";
+        let generated = generate("[ws] workspace.package.*", toml);
+        assert!(generated.contains("\"1.0.0\""), "{}", generated);
+        let generated = generate("[pkg] package.*", toml);
+        assert!(generated.contains("virtual one without a `[package]`"), "{}", generated);
+    }
+
+    #[test]
+    fn test_extra_attrs() {
+        let generated = generate(
+            r##"[section] #attr("#[allow(dead_code)]") a.*"##,
+            "[a]\nport = 1\n[a.sub]\nname = \"x\"",
+        );
+        assert!(generated.contains("# [allow (dead_code)] pub mod section"), "{}", generated);
+        assert!(generated.contains("# [allow (dead_code)] pub mod sub"), "{}", generated);
+        assert!(generated.contains("# [allow (dead_code)] pub const PORT"), "{}", generated);
+    }
+
+    #[test]
+    fn test_unused_alias() {
+        let toml = "[a]\nport = 1";
+        let live = generate("[section] alias listen = a.port", toml);
+        assert!(live.contains("LISTEN"), "{}", live);
+        assert!(!live.contains("deprecated"), "{}", live);
+
+        let dead = generate("[section] a.* alias listen = a.prot", toml);
+        assert!(dead.contains("Alias `listen` is unused"), "{}", dead);
+        assert!(dead.contains("deprecated"), "{}", dead);
+        let strict = generate("[section strict_aliases] a.* alias listen = a.prot", toml);
+        assert!(strict.contains("compile_error"), "{}", strict);
+        assert!(!strict.contains("deprecated"), "{}", strict);
+    }
+
+    #[test]
+    fn test_derive_needs_a_struct() {
+        let err = syn::parse_str::<RootModuleSource>("[config derive(Default)] a.*").unwrap_err();
+        assert!(err.to_string().contains("only applies to struct sections"), "{}", err);
+        let generated = generate("[struct Config derive(Default)] a.*", "[a]\nport = 1");
+        assert!(generated.contains("port : i64"), "{}", generated);
+    }
+
+    #[test]
+    #[cfg(feature = "phf")]
+    fn test_as_phf() {
+        let generated = generate("[section as_phf] a.*", "[a]\nx = 1\ny = 2");
+        let map = "static MAP : :: phf :: Map < & 'static str , i64 >";
+        assert!(generated.contains(map), "{}", generated);
+        assert!(generated.contains(r#"("y" , Y)"#), "{}", generated);
+
+        let mixed = generate("[section as_phf] a.*", "[a]\nx = 1\ny = \"two\"");
+        assert!(mixed.contains("needs values of one type"), "{}", mixed);
+        let err = syn::parse_str::<RootModuleSource>("[struct A as_phf] a.*").unwrap_err();
+        assert!(err.to_string().contains("only applies to module sections"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(feature = "bitflags")]
+    fn test_as_bitflags() {
+        let generated = generate("[section as_bitflags] a.*", "[a]\nx = true\ny = false");
+        assert!(generated.contains("pub struct Flags : u8"), "{}", generated);
+        assert!(generated.contains("const X = 1 << 0 ; const Y = 1 << 1 ;"), "{}", generated);
+        let enabled = "pub const ENABLED : Flags = Flags :: empty () . union (Flags :: X) ;";
+        assert!(generated.contains(enabled), "{}", generated);
+
+        let mixed = generate("[section as_bitflags] a.*", "[a]\nx = true\ny = 1");
+        assert!(mixed.contains("but `a.y` is of type integer"), "{}", mixed);
+        let err = syn::parse_str::<RootModuleSource>("[struct A as_bitflags] a.*").unwrap_err();
+        assert!(err.to_string().contains("only applies to module sections"), "{}", err);
+    }
+
+    #[test]
+    fn test_serde_needs_a_struct() {
+        let err = syn::parse_str::<RootModuleSource>("[config serde] a.*").unwrap_err();
+        assert!(err.to_string().contains("only applies to struct sections"), "{}", err);
+        let generated = generate("[struct Config serde] a.*", "[a]\nport-no = 1\nname = \"x\"");
+        assert!(generated.contains("# [serde (rename = \"port-no\")] pub port_no : i64"));
+        assert!(generated.contains("pub const DEFAULTS : Self"), "{}", generated);
+        let enums = generate("[struct Config serde] a.name as enum", "[a]\nname = \"x\"");
+        assert!(enums.contains("can't be bound in a `serde` section"), "{}", enums);
+    }
+
+    #[test]
+    fn test_target_dir_source() -> Result<(), Box<dyn std::error::Error>> {
+        let crate_dir = tempfile::TempDir::new()?;
+        let target_dir = tempfile::TempDir::new()?;
+        fs::create_dir_all(target_dir.path().join("shared"))?;
+        fs::write(target_dir.path().join("shared/config.toml"), "[app]\nport = 8080")?;
+        fs::write(crate_dir.path().join("config.toml"), "[app]\nport = 1")?;
+        let dirs = SourceDirs {
+            manifest_dir: crate_dir.path().to_path_buf(),
+            workspace_root: crate_dir.path().to_path_buf(),
+            declared_workspace_dir: None,
+            target_dir: target_dir.path().to_path_buf(),
+        };
+
+        let found = RootModule::resolve_source("target-dir:shared/config.toml", &dirs)?;
+        assert_eq!(found, target_dir.path().join("shared/config.toml"));
+        // only the target dir is looked in, not the crate
+        let err = RootModule::resolve_source("target-dir:config.toml", &dirs).unwrap_err();
+        assert!(err.contains("Couldn't find `target-dir:config.toml`"), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn test_provenance_docs() -> Result<(), Box<dyn std::error::Error>> {
+        let path = SourcePath::Literal(LitStr::new("tests/test.toml", Span::call_site()));
+        let source = syn::parse_str::<RootModuleSource>("[app provenance_docs] config.*")?;
+        let module = RootModule::new(source, &[path], SourceFormat::Toml)?;
+        let generated = module.to_token_stream().to_string();
+        let doc = r#"# [doc = "Source: `config.settings.timeout` in `tests/test.toml`"]"#;
+        assert!(generated.contains(doc), "{}", generated);
+        // the TOML of a build script isn't from a file
+        assert!(generate("[app provenance_docs] a.*", "[a]\nb = 1").contains("Source: `a.b`\""));
+        assert!(!generate("[app] a.*", "[a]\nb = 1").contains("Source"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_env_source() -> Result<(), Box<dyn std::error::Error>> {
+        let env_path = |var: &str, default: Option<&str>| SourcePath::Env {
+            var: LitStr::new(var, Span::call_site()),
+            default: default.map(|path| LitStr::new(path, Span::call_site())),
+        };
+        let generate = |path: SourcePath| {
+            let source = syn::parse_str::<RootModuleSource>("[main] section.*")?;
+            RootModule::new(source, &[path], SourceFormat::Toml)
+                .map(|module| module.to_token_stream().to_string())
+        };
+
+        env::set_var("TOMLFUSE_TEST_SOURCE_PATH", "tests/test.toml");
+        let generated = generate(env_path("TOMLFUSE_TEST_SOURCE_PATH", Some("missing.toml")))?;
+        assert!(generated.contains(r#"KEY : & 'static str = "value""#), "{}", generated);
+        env::remove_var("TOMLFUSE_TEST_SOURCE_PATH");
+
+        let fallback = generate(env_path("TOMLFUSE_TEST_UNSET_PATH", Some("tests/test.toml")))?;
+        assert_eq!(fallback, generated);
+        let err = generate(env_path("TOMLFUSE_TEST_UNSET_PATH", None)).unwrap_err();
+        assert!(err.to_string().contains("`TOMLFUSE_TEST_UNSET_PATH` isn't set"), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn test_declared_workspace_dir() -> Result<(), Box<dyn std::error::Error>> {
+        let workspace = tempfile::TempDir::new()?;
+        let crate_dir = tempfile::TempDir::new()?;
+        let dirs = SourceDirs {
+            manifest_dir: crate_dir.path().join("member"),
+            workspace_root: crate_dir.path().to_path_buf(),
+            declared_workspace_dir: None,
+            target_dir: crate_dir.path().join("target"),
+        };
+        // without it, just the crate relative candidates
+        assert_eq!(RootModule::source_candidates("a.toml", &dirs).len(), 3);
+        let dirs = SourceDirs {
+            declared_workspace_dir: Some(workspace.path().to_path_buf()),
+            ..dirs
+        };
+        let err = RootModule::resolve_source("vendored-only.toml", &dirs).unwrap_err();
+        let tried = [
+            PathBuf::from("vendored-only.toml"),
+            crate_dir.path().join("vendored-only.toml"),
+            crate_dir.path().join("member").join("vendored-only.toml"),
+            workspace.path().join("vendored-only.toml"),
+        ];
+        let tried = tried.map(|path| path.display().to_string()).join("\n  ");
+        assert_eq!(err, format!("Couldn't find `vendored-only.toml`, tried:\n  {}", tried));
+        // a crate outside of a workspace is its own workspace root, which is tried once
+        let single = SourceDirs {
+            workspace_root: dirs.manifest_dir.clone(),
+            ..dirs.clone()
+        };
+        let err = RootModule::resolve_source("vendored-only.toml", &single).unwrap_err();
+        assert_eq!(err.lines().count(), 4, "{}", err);
+        let absolute = workspace.path().join("missing.toml").display().to_string();
+        let err = RootModule::resolve_source(&absolute, &dirs).unwrap_err();
+        assert_eq!(err, format!("Couldn't find `{}`", absolute));
+
+        fs::write(workspace.path().join("vendored-only.toml"), "[app]\nport = 8080")?;
+        let found = RootModule::resolve_source("vendored-only.toml", &dirs)?;
+        assert_eq!(found, workspace.path().join("vendored-only.toml"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_member_manifest_inherits() -> Result<(), Box<dyn std::error::Error>> {
+        let workspace = tempfile::TempDir::new()?;
+        fs::create_dir_all(workspace.path().join("member"))?;
+        fs::write(
+            workspace.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"member\"]\n[workspace.package]\nversion = \"1.2.3\"",
+        )?;
+        let member = workspace.path().join("member").join("Cargo.toml");
+        fs::write(&member, "[package]\nname = \"member\"\nversion.workspace = true")?;
+
+        let source = syn::parse_str::<RootModuleSource>("[package] package.*")?;
+        let module = RootModule::new(
+            source,
+            &[SourcePath::Literal(LitStr::new(&member.to_string_lossy(), Span::call_site()))],
+            SourceFormat::Toml,
+        )?;
+        let generated = module.to_token_stream().to_string();
+        assert!(generated.contains("VERSION : & 'static str = \"1.2.3\""), "{}", generated);
+        Ok(())
+    }
+}
@@ -0,0 +1,449 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use crate::utils::{kebab_to_snake, parse_kebab_name};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::parse::{ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::{token, Attribute, LitStr, Result as SynResult, Token};
+
+/// Per-section options, toggled with `#directive` lines in the section body
+/// or with flags after the section name in its header (`[config keep_case]`).
+///
+/// Both forms share the same names, which are kebab- or snake_case and map onto
+/// the snake_case fields here, e.g. `#checksum` and `[name checksum]` both set `checksum`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SectionOptions {
+    /// `#checksum`: emit a `CHECKSUM: u64` over the values bound by the section
+    pub checksum: bool,
+    /// `#with-original-names`: emit a `<NAME>_ORIGINAL: &str` with the exact TOML key
+    /// alongside each const
+    pub with_original_names: bool,
+    /// `#with-docs`: emit a `<NAME>_DOC: &str` with the TOML comment of each const,
+    /// so the documentation is available at runtime too
+    pub with_docs: bool,
+    /// `keep_case`: keep the casing of TOML keys in generated names, only sanitizing them
+    /// into valid idents, instead of lowercasing modules and uppercasing consts
+    pub keep_case: bool,
+    /// `#targets(bin)`: emit `NAMES` and `PATHS` of the manifest's declared `[[bin]]`
+    /// (or `example`, `test`, `bench`) targets, empty when there are none
+    pub targets: Option<String>,
+    /// `env_override`: let `TOMLFUSE_<path>` environment variables override the bound values
+    /// at compile time, see `env_override::env_var_name`
+    pub env_override: bool,
+    /// `#merge-arrays(append)`: how arrays at the same path are merged when several files are
+    /// given, one of `MERGE_STRATEGIES`; the later file's array replaces the earlier by default
+    pub merge_arrays: Option<String>,
+    /// `as_map`: emit an `entries()` fn listing the section's direct values as stringified
+    /// `(key, value)` pairs, for iterating them at runtime
+    pub as_map: bool,
+    /// `lookup_fn`: emit a `get(key)` fn matching the TOML keys of the section's direct values
+    /// to them as strings, for looking them up by name without building a map
+    pub lookup_fn: bool,
+    /// `as_phf`: emit a `MAP` static, a `phf::Map` from the TOML keys of the section's direct
+    /// values to them, for large sections looked up by key; needs the `phf` feature
+    pub as_phf: bool,
+    /// `as_bitflags`: emit a `bitflags` struct `Flags` with a flag per direct value of the
+    /// section, which must all be bools, and an `ENABLED` const of the ones that are `true`;
+    /// needs the `bitflags` feature
+    pub as_bitflags: bool,
+    /// `#flatten-exports`: re-export the items of each submodule in its parent module,
+    /// so they're reachable without the module prefix
+    pub flatten_exports: bool,
+    /// `#count`: emit a `__COUNT: usize` in each module or struct with the number of value
+    /// consts in it and nested in it, e.g. for checking what patterns matched in tests
+    pub count: bool,
+    /// `pub(crate)`, `pub(super)`, `priv` or `pub`: the visibility of the section's module or
+    /// struct, `pub` by default
+    pub visibility: Option<String>,
+    /// `derive(Default, Debug)`: in struct sections, emit structs with the values as fields
+    /// rather than as associated consts, deriving the listed traits; `Default` can't be derived
+    /// from the TOML values, so it's implemented by hand with them instead
+    pub derives: Option<Vec<String>>,
+    /// `serde`: in struct sections, emit structs like `derive(..)` does that also derive
+    /// `serde`'s traits, with a `DEFAULTS` const holding the TOML values that deserializing
+    /// falls back to for missing keys
+    pub serde: bool,
+    /// `collapse_singletons`: fold tables holding nothing but a single value into their parent,
+    /// joining the names (`database.url` binds as `DATABASE_URL`), unless a pattern names the
+    /// table itself
+    pub collapse_singletons: bool,
+    /// `name_by_wildcard`: name the values of collecting patterns like `dependencies.*.version`
+    /// after what their wildcard matched (`SERDE`), rather than keeping a module per match
+    pub name_by_wildcard: bool,
+    /// `intern_strings`: bind the strings a module section has more than once as private consts
+    /// at its root, which the consts holding them then refer to
+    pub intern_strings: bool,
+    /// `strict_aliases`: make aliases whose original path doesn't exist errors rather than
+    /// warnings
+    pub strict_aliases: bool,
+    /// `strict_types`: make values that have no typed constant, like arrays mixing tables and
+    /// strings, errors rather than binding them as strings
+    pub strict_types: bool,
+    /// `narrow_ints`: bind integers as the smallest signed type that fits them rather than as
+    /// `i64`, see `utils::convert_value_to_narrowed_tokens`
+    pub narrow_ints: bool,
+    /// `as_fns`: emit a fn next to each const returning an owned copy of its value, e.g.
+    /// `fn name() -> String` for `NAME: &str`, see `utils::to_owned_tokens`
+    pub as_fns: bool,
+    /// `array_lens`: emit a `<NAME>_LEN: usize` next to each array const with its number of
+    /// elements, for sizing buffers in const contexts
+    pub array_lens: bool,
+    /// `raw_idents`: name items after the TOML keys as they are, erroring on keys that aren't
+    /// valid identifiers rather than converting them (`with-dash` to `WITH_DASH`)
+    pub raw_idents: bool,
+    /// `flatten(joined)`: how the segments the patterns' literal prefixes consume are treated,
+    /// one of `FLATTEN_MODES`; `full` drops them (the default), `joined` joins them into the
+    /// names of the section's top level items, and `hierarchy` keeps them as modules
+    pub flatten: Option<String>,
+    /// `flatten_sep("__")`: the separator `flatten(joined)` joins the segments with, `_` by
+    /// default; setting it implies `joined`
+    pub flatten_sep: Option<String>,
+    /// `inner_docs`: put the comments of tables as inner `#![doc]`s at the top of their
+    /// modules, rather than as outer docs on the `mod` items
+    pub inner_docs: bool,
+    /// `provenance_docs`: end the docs of each const with where its value is from, as the path
+    /// of the key and the source files it was read from
+    pub provenance_docs: bool,
+    /// `#attr("#[some_macro]")`: outer attributes put on each generated const and module, for
+    /// composing the output with other attribute macros; repeatable
+    pub attrs: Vec<String>,
+}
+
+impl SectionOptions {
+    /// Parses a single `#directive` and applies it to these options.
+    pub fn parse_directive(&mut self, input: ParseStream) -> SynResult<()> {
+        let _pound: Token![#] = input.parse()?;
+        self.parse_option(input, |name| format!("Unknown directive `#{}`", name))
+    }
+
+    /// Parses a single flag from a section header and applies it to these options.
+    pub fn parse_flag(&mut self, input: ParseStream) -> SynResult<()> {
+        self.parse_option(input, |name| format!("Unknown section flag `{}`", name))
+    }
+
+    // parses an option by its kebab- or snake_case name, with its argument if it takes one
+    fn parse_option(
+        &mut self,
+        input: ParseStream,
+        unknown: impl Fn(&str) -> String,
+    ) -> SynResult<()> {
+        let span = input.span();
+        let name = parse_kebab_name(input)?;
+        match kebab_to_snake(&name).as_str() {
+            "checksum" => self.checksum = true,
+            "with_original_names" => self.with_original_names = true,
+            "with_docs" => self.with_docs = true,
+            "keep_case" => self.keep_case = true,
+            "env_override" => self.env_override = true,
+            "as_map" => self.as_map = true,
+            "lookup_fn" => self.lookup_fn = true,
+            "as_phf" if cfg!(feature = "phf") => self.as_phf = true,
+            "as_phf" => {
+                return Err(syn::Error::new(span, "`as_phf` needs the `phf` feature of tomlfuse"));
+            },
+            "as_bitflags" if cfg!(feature = "bitflags") => self.as_bitflags = true,
+            "as_bitflags" => {
+                return Err(syn::Error::new(
+                    span,
+                    "`as_bitflags` needs the `bitflags` feature of tomlfuse",
+                ));
+            },
+            "flatten_exports" => self.flatten_exports = true,
+            "count" => self.count = true,
+            "strict_aliases" => self.strict_aliases = true,
+            "strict_types" => self.strict_types = true,
+            "narrow_ints" => self.narrow_ints = true,
+            "as_fns" => self.as_fns = true,
+            "inner_docs" => self.inner_docs = true,
+            "provenance_docs" => self.provenance_docs = true,
+            "array_lens" => self.array_lens = true,
+            "raw_idents" => self.raw_idents = true,
+            "serde" => self.serde = true,
+            "collapse_singletons" => self.collapse_singletons = true,
+            "intern_strings" => self.intern_strings = true,
+            "name_by_wildcard" => self.name_by_wildcard = true,
+            "targets" => {
+                self.targets = Some(parse_choice(input, "the target kind", TARGET_KINDS)?);
+            },
+            "pub" => {
+                // `pub(crate)` and `pub(super)` restrict it, plain `pub` is the default anyway
+                self.visibility = Some(if input.peek(token::Paren) {
+                    format!("pub({})", parse_choice(input, "the visibility", VIS_RESTRICTIONS)?)
+                } else {
+                    "pub".to_string()
+                });
+            },
+            "priv" => self.visibility = Some("priv".to_string()),
+            "derive" => {
+                let content;
+                syn::parenthesized!(content in input);
+                let paths = Punctuated::<syn::Path, Token![,]>::parse_terminated(&content)?;
+                self.derives = Some(
+                    paths
+                        .iter()
+                        .map(|path| path.to_token_stream().to_string())
+                        .collect(),
+                );
+            },
+            "attr" => {
+                let content;
+                syn::parenthesized!(content in input);
+                let lit = content.parse::<LitStr>()?;
+                let attr = lit.value();
+                let attrs = Parser::parse_str(Attribute::parse_outer, &attr)
+                    .ok()
+                    .filter(|attrs| !attrs.is_empty())
+                    .ok_or_else(|| {
+                        syn::Error::new(
+                            lit.span(),
+                            format!("Expected an attribute like `#[inline]`, got `{}`", attr),
+                        )
+                    })?;
+                self.attrs.extend(attrs.iter().map(|attr| attr.to_token_stream().to_string()));
+            },
+            "flatten" => {
+                self.flatten = Some(parse_choice(input, "the flattening", FLATTEN_MODES)?);
+            },
+            "flatten_sep" => {
+                let content;
+                syn::parenthesized!(content in input);
+                let lit = content.parse::<LitStr>()?;
+                let sep = lit.value();
+                // the separator ends up in identifiers, so it has to be valid in one
+                if sep.is_empty() || !sep.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        format!("Expected a separator of letters, digits and `_`, got `{}`", sep),
+                    ));
+                }
+                self.flatten_sep = Some(sep);
+            },
+            "merge_arrays" => {
+                self.merge_arrays =
+                    Some(parse_choice(input, "the merge strategy", MERGE_STRATEGIES)?);
+            },
+            _ => return Err(syn::Error::new(span, unknown(&name))),
+        }
+        Ok(())
+    }
+
+    /// The `#attr(..)` attributes to put on generated consts and modules.
+    pub fn extra_attrs(&self) -> TokenStream2 {
+        self.attrs
+            .iter()
+            .map(|attr| {
+                attr.parse::<TokenStream2>()
+                    .expect("Expected the attribute to be valid tokens")
+            })
+            .collect()
+    }
+
+    /// The separator to join the consumed segments with, if the section joins them.
+    pub fn flatten_separator(&self) -> Option<&str> {
+        match (self.flatten.as_deref(), self.flatten_sep.as_deref()) {
+            (Some("joined"), sep) | (None, sep @ Some(_)) => Some(sep.unwrap_or("_")),
+            _ => None,
+        }
+    }
+
+    /// Whether the section keeps the full TOML hierarchy, prefixes included.
+    pub fn keeps_hierarchy(&self) -> bool {
+        self.flatten.as_deref() == Some("hierarchy")
+    }
+
+    /// The visibility of the section's root module or struct.
+    pub fn root_visibility(&self) -> TokenStream2 {
+        match self.visibility.as_deref() {
+            None | Some("pub") => quote! { pub },
+            Some("priv") => quote! {},
+            Some(vis) => vis
+                .parse()
+                .expect("Expected the visibility to be valid tokens"),
+        }
+    }
+
+    /// The visibility of everything inside the section.
+    ///
+    /// Items inside a restricted module or next to a restricted struct have to reach at least
+    /// as far as the section itself, so anything but `pub` makes them `pub(crate)`; the root's
+    /// visibility is what actually limits where they can be used from.
+    pub fn item_visibility(&self) -> TokenStream2 {
+        match self.visibility.as_deref() {
+            None | Some("pub") => quote! { pub },
+            Some(_) => quote! { pub(crate) },
+        }
+    }
+}
+
+// parses a parenthesized argument that must be one of `choices`, e.g. the `bin` in `targets(bin)`
+fn parse_choice(input: ParseStream, what: &str, choices: &[&str]) -> SynResult<String> {
+    let content;
+    syn::parenthesized!(content in input);
+    let arg_span = content.span();
+    let choice = parse_kebab_name(&content)?;
+    if !choices.contains(&choice.as_str()) {
+        return Err(syn::Error::new(
+            arg_span,
+            format!(
+                "Expected one of {} as {}, got `{}`",
+                choices.join(", "),
+                what,
+                choice
+            ),
+        ));
+    }
+    Ok(choice)
+}
+
+/// The cargo target kinds `#targets(..)` accepts, by their array-of-tables key in the manifest.
+pub const TARGET_KINDS: &[&str] = &["bin", "example", "test", "bench"];
+
+/// The restrictions `pub(..)` accepts, e.g. `[internal pub(crate)]`.
+pub const VIS_RESTRICTIONS: &[&str] = &["crate", "super"];
+
+/// The ways `flatten(..)` accepts to treat the segments that the patterns' literal prefixes
+/// consume, e.g. `nested.inner` for `nested.inner.*`.
+pub const FLATTEN_MODES: &[&str] = &["full", "joined", "hierarchy"];
+
+/// The array merge strategies `#merge-arrays(..)` accepts, see `utils::merge_values`.
+pub const MERGE_STRATEGIES: &[&str] = &["replace", "append", "union"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse::Parser;
+
+    fn flags(s: &str) -> SynResult<SectionOptions> {
+        let parser = |input: ParseStream| {
+            let mut options = SectionOptions::default();
+            while !input.is_empty() {
+                options.parse_flag(input)?;
+            }
+            Ok(options)
+        };
+        parser.parse_str(s)
+    }
+
+    #[test]
+    fn test_parse_flags() {
+        let options = flags("keep_case checksum").unwrap();
+        assert!(options.keep_case);
+        assert!(options.checksum);
+        assert!(!options.with_original_names);
+        // kebab and snake case are interchangeable
+        assert!(flags("keep-case").unwrap().keep_case);
+        assert!(flags("with_original_names").unwrap().with_original_names);
+        assert!(flags("env-override").unwrap().env_override);
+        assert!(flags("with-docs").unwrap().with_docs);
+        assert!(flags("as_map").unwrap().as_map);
+        assert!(flags("lookup-fn").unwrap().lookup_fn);
+        assert_eq!(flags("as-phf").is_ok(), cfg!(feature = "phf"));
+        assert_eq!(flags("as_bitflags").is_ok(), cfg!(feature = "bitflags"));
+        assert!(flags("flatten-exports").unwrap().flatten_exports);
+        assert!(flags("count").unwrap().count);
+        assert!(flags("strict-aliases").unwrap().strict_aliases);
+        assert!(flags("strict_types").unwrap().strict_types);
+        assert!(flags("narrow_ints").unwrap().narrow_ints);
+        assert!(flags("as-fns").unwrap().as_fns);
+        assert!(flags("inner_docs").unwrap().inner_docs);
+        assert!(flags("provenance-docs").unwrap().provenance_docs);
+        assert!(flags("array-lens").unwrap().array_lens);
+        assert!(flags("raw_idents").unwrap().raw_idents);
+        assert!(flags("serde").unwrap().serde);
+        assert!(flags("collapse-singletons").unwrap().collapse_singletons);
+        assert!(flags("intern_strings").unwrap().intern_strings);
+    }
+
+    #[test]
+    fn test_parse_directive() {
+        let parser = |input: ParseStream| {
+            let mut options = SectionOptions::default();
+            options.parse_directive(input)?;
+            Ok(options)
+        };
+        assert!(parser.parse_str("#with-original-names").unwrap().with_original_names);
+        let err = parser.parse_str("#no-such-thing").unwrap_err();
+        assert_eq!(err.to_string(), "Unknown directive `#no-such-thing`");
+    }
+
+    #[test]
+    fn test_parse_unknown_flag() {
+        let err = flags("keep_case shouty").unwrap_err();
+        assert_eq!(err.to_string(), "Unknown section flag `shouty`");
+    }
+
+    #[test]
+    fn test_parse_targets() {
+        assert_eq!(flags("targets(example)").unwrap().targets.as_deref(), Some("example"));
+        assert!(flags("targets").is_err());
+        assert!(flags("targets(library)").is_err());
+    }
+
+    #[test]
+    fn test_parse_merge_arrays() {
+        assert_eq!(flags("merge-arrays(union)").unwrap().merge_arrays.as_deref(), Some("union"));
+        assert_eq!(flags("merge_arrays(append)").unwrap().merge_arrays.as_deref(), Some("append"));
+        assert!(flags("merge-arrays(concat)").is_err());
+    }
+
+    #[test]
+    fn test_parse_flatten() {
+        assert_eq!(SectionOptions::default().flatten_separator(), None);
+        assert_eq!(flags("flatten(joined)").unwrap().flatten_separator(), Some("_"));
+        assert_eq!(flags(r#"flatten_sep("__")"#).unwrap().flatten_separator(), Some("__"));
+        assert_eq!(flags("flatten(full)").unwrap().flatten_separator(), None);
+        assert!(flags("flatten(hierarchy)").unwrap().keeps_hierarchy());
+        assert!(!flags("flatten(joined)").unwrap().keeps_hierarchy());
+        assert!(flags("flatten(nested)").is_err());
+        assert!(flags(r#"flatten-sep("::")"#).is_err());
+        assert!(flags(r#"flatten-sep("")"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_visibility() {
+        let options = flags("pub(crate)").unwrap();
+        assert_eq!(options.visibility.as_deref(), Some("pub(crate)"));
+        assert_eq!(options.root_visibility().to_string(), "pub (crate)");
+        assert_eq!(options.item_visibility().to_string(), "pub (crate)");
+
+        let options = flags("priv").unwrap();
+        assert!(options.root_visibility().is_empty());
+        assert_eq!(options.item_visibility().to_string(), "pub (crate)");
+
+        assert_eq!(flags("pub").unwrap().root_visibility().to_string(), "pub");
+        assert_eq!(SectionOptions::default().item_visibility().to_string(), "pub");
+        assert!(flags("pub(self)").is_err());
+    }
+
+    #[test]
+    fn test_parse_derive() {
+        let options = flags("derive(Default, Debug, serde::Serialize)").unwrap();
+        assert_eq!(
+            options.derives,
+            Some(vec![
+                "Default".to_string(),
+                "Debug".to_string(),
+                "serde :: Serialize".to_string()
+            ])
+        );
+        assert!(flags("derive").is_err());
+    }
+
+    #[test]
+    fn test_parse_attr() {
+        let options = flags(r##"attr("#[allow(dead_code)]") attr("#[doc(hidden)]")"##).unwrap();
+        assert_eq!(options.attrs, ["# [allow (dead_code)]", "# [doc (hidden)]"]);
+        assert_eq!(
+            options.extra_attrs().to_string(),
+            "# [allow (dead_code)] # [doc (hidden)]"
+        );
+        let err = flags(r#"attr("allow(dead_code)")"#).unwrap_err();
+        assert!(err.to_string().contains("Expected an attribute"), "{}", err);
+        assert!(flags(r##"attr("#![inner]")"##).is_err());
+    }
+}
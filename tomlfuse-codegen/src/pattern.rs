@@ -0,0 +1,826 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use globset::Glob;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{token, Result as SynResult, Token};
+use toml::Value;
+
+/// Represents a pattern for matching TOML paths.
+///
+/// Patterns use dot-separated segments with special syntax:
+/// - Regular identifiers match exact paths
+/// - `*` matches any run of characters, which (for backwards compatibility) includes
+///   the dots between segments, so `section.*` matches `section.a` as well as `section.a.b`
+/// - `**` as a whole segment matches zero or more whole segments (recursive), so `deep.**`
+///   matches every descendant of `deep` and `**.version` matches `version` at any depth,
+///   including the top level
+/// - `?` matches any single character within a segment, so `version.v?` matches `v1`
+///   through `v9` but not `v10`
+/// - `!` at start negates the pattern (for exclusion)
+/// - `{a,b}` braces match any one of the listed alternatives
+/// - `[N]` after the last key selects the element at that index of the array there, from the
+///   end if it's negative, so `package.authors[0]` binds just the first author
+/// - Brackets for grouping (future)
+///
+/// For example: `section.*` matches all direct children of "section",
+/// and `dependencies.{serde,tokio}` matches just those two children.
+///
+/// A pattern can also be constrained to an exact depth below its literal prefix with
+/// `depth == N` (see [`Pattern::parse_depth`]), which the glob alone can't express, and a
+/// negated one can compare the value at its path with `== value` or `!= value`
+/// (see [`Pattern::parse_predicate`]).
+pub struct Pattern {
+    segments: Punctuated<PatternSegment, Token![.]>,
+    spans: Vec<proc_macro2::Span>,
+    depth: Option<usize>,
+    predicate: Option<ValuePredicate>,
+}
+
+/// A comparison of the value at a pattern's path with a literal, like `== true`.
+///
+/// Values of other types than the literal's never equal it, so e.g. `!= "1.0"` holds for
+/// the integer `1`.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ValuePredicate {
+    /// Whether this is `!=` rather than `==`
+    pub not_equal: bool,
+    /// The literal compared with
+    pub literal: PredicateLiteral,
+}
+
+/// The literals a [`ValuePredicate`] can compare with.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum PredicateLiteral {
+    Str(String),
+    Bool(bool),
+    Int(i64),
+}
+
+impl ValuePredicate {
+    /// Checks whether the predicate holds for the given value.
+    pub fn matches(&self, value: &Value) -> bool {
+        let equal = match (&self.literal, value) {
+            (PredicateLiteral::Str(literal), Value::String(s)) => literal == s,
+            (PredicateLiteral::Bool(literal), Value::Boolean(b)) => literal == b,
+            (PredicateLiteral::Int(literal), Value::Integer(i)) => literal == i,
+            _ => false,
+        };
+        equal != self.not_equal
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        if self.segments.len() != other.segments.len()
+            || self.depth != other.depth
+            || self.predicate != other.predicate
+        {
+            return false;
+        }
+
+        for (segment, other_segment) in self.segments.iter().zip(&other.segments) {
+            if segment != other_segment {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl Eq for Pattern {}
+
+// ordered by the written form, so maps keyed by patterns iterate deterministically
+impl PartialOrd for Pattern {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pattern {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.to_string(), self.depth, &self.predicate).cmp(&(
+            other.to_string(),
+            other.depth,
+            &other.predicate,
+        ))
+    }
+}
+
+impl Hash for Pattern {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for segment in &self.segments {
+            segment.hash(state);
+        }
+        self.depth.hash(state);
+        self.predicate.hash(state);
+    }
+}
+
+impl Clone for Pattern {
+    fn clone(&self) -> Self {
+        Pattern {
+            segments: self.segments.clone(),
+            spans: if self.spans.is_empty() {
+                self.segments.iter().map(|seg| seg.span()).collect()
+            } else {
+                self.spans.clone()
+            },
+            depth: self.depth,
+            predicate: self.predicate.clone(),
+        }
+    }
+}
+
+impl Debug for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl Pattern {
+    /// Converts this pattern into a glob matcher.
+    ///
+    /// `globset` only understands `/` as a separator, so segments are joined with `/`
+    /// rather than `.`, and the paths matched against it need the same treatment
+    /// (see `utils::to_glob_path`). Brace groups should be expanded with
+    /// [`Pattern::alternatives`] first.
+    ///
+    /// The element an index selects is bound as a value of its own named after the array and the
+    /// index, so `authors[0]` matches `authors_0`.
+    ///
+    /// A `?` matches a single character within its segment, and globset's own `?` would match
+    /// the `/` between segments too, so it's written as `[!/]` instead.
+    pub fn to_glob(&self) -> Glob {
+        let s = self
+            .join("/", |index| format!("_{}", index))
+            .replace('?', "[!/]");
+        Glob::new(&s).expect("Expected a valid glob pat string")
+    }
+
+    /// Returns the root of the subtree this pattern selects, if it's of the form `app.*` or
+    /// `app.**` with only plain identifiers before the wildcard (here `app`).
+    pub fn subtree_root(&self) -> Option<String> {
+        let segments = self.segments.iter().collect::<Vec<_>>();
+        let (last, prefix) = segments.split_last()?;
+        if prefix.is_empty()
+            || !matches!(last, PatternSegment::Star | PatternSegment::DoubleStar)
+            || !prefix.iter().all(|seg| matches!(seg, PatternSegment::Ident(_)))
+        {
+            return None;
+        }
+        Some(
+            prefix
+                .iter()
+                .map(|seg| seg.to_string())
+                .collect::<Vec<_>>()
+                .join("."),
+        )
+    }
+
+    /// Whether this pattern matches its suffix anywhere in the document, i.e. starts with `**`
+    /// followed by more segments, like `**.version`.
+    pub fn is_anywhere(&self) -> bool {
+        self.segments.len() > 1 && self.segments[0] == PatternSegment::DoubleStar
+    }
+
+    /// The position of the wildcard segment of a pattern that collects the same key of each of
+    /// its matches, like `dependencies.*.version`: keys, then a single `*` or `?` segment, then
+    /// more keys.
+    pub fn collecting_segment(&self) -> Option<usize> {
+        let is_key = |seg: &PatternSegment| matches!(seg, PatternSegment::Ident(_));
+        let position = self.segments.iter().position(|seg| !is_key(seg))?;
+        let is_wildcard = matches!(
+            self.segments[position],
+            PatternSegment::Star | PatternSegment::Wildcard(_)
+        );
+        let keys_follow = position + 1 < self.segments.len()
+            && self.segments.iter().skip(position + 1).all(is_key);
+        (is_wildcard && keys_follow).then_some(position)
+    }
+
+    /// The span of the pattern's first segment, for diagnostics that take a single span.
+    pub fn span(&self) -> proc_macro2::Span {
+        self.spans
+            .first()
+            .copied()
+            .unwrap_or_else(proc_macro2::Span::call_site)
+    }
+
+    /// Creates an error spanning the whole pattern as written in the macro input.
+    pub fn error(&self, message: impl Display) -> syn::Error {
+        let (Some(first), Some(last)) = (self.spans.first(), self.spans.last()) else {
+            return syn::Error::new(proc_macro2::Span::call_site(), message);
+        };
+        // `new_spanned` reports from the start of the first token to the end of the last one
+        let mut tokens = TokenStream2::new();
+        for span in [first, last] {
+            let mut punct = proc_macro2::Punct::new('.', proc_macro2::Spacing::Alone);
+            punct.set_span(*span);
+            tokens.extend([proc_macro2::TokenTree::Punct(punct)]);
+        }
+        syn::Error::new_spanned(tokens, message)
+    }
+
+    /// The path of the array and the index of the element this pattern selects, if it ends with
+    /// an index like `package.authors[0]`.
+    pub fn index(&self) -> Option<(String, i64)> {
+        let PatternSegment::Index(index) = self.segments.last()? else {
+            return None;
+        };
+        let path = self
+            .segments
+            .iter()
+            .take(self.segments.len() - 1)
+            .map(|seg| seg.to_string())
+            .collect::<Vec<_>>()
+            .join(".");
+        Some((path, *index))
+    }
+
+    /// This pattern with the index it ends with replaced, for resolving one counted from the end
+    /// once the length of the array is known.
+    pub fn with_index(&self, index: usize) -> Pattern {
+        let mut pattern = self.clone();
+        if let Some(last) = pattern.segments.last_mut() {
+            if matches!(last, PatternSegment::Index(_)) {
+                *last = PatternSegment::Index(index as i64);
+            }
+        }
+        pattern
+    }
+
+    // the segments joined by `separator`, with indices written by `index` straight after the key
+    // they index rather than as segments of their own
+    fn join(&self, separator: &str, index: impl Fn(i64) -> String) -> String {
+        let mut joined = String::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PatternSegment::Index(n) => joined.push_str(&index(*n)),
+                _ => {
+                    if i > 0 {
+                        joined.push_str(separator);
+                    }
+                    joined.push_str(&segment.to_string());
+                },
+            }
+        }
+        joined
+    }
+
+    /// Expands any brace groups into one pattern per alternative.
+    ///
+    /// `dependencies.{serde,tokio}` expands to `dependencies.serde` and `dependencies.tokio`.
+    /// Multiple brace groups expand to their cartesian product, and a pattern without
+    /// braces expands to just itself.
+    pub fn alternatives(&self) -> Vec<Pattern> {
+        let mut expanded: Vec<Vec<PatternSegment>> = vec![Vec::new()];
+        for segment in &self.segments {
+            expanded = match segment {
+                PatternSegment::Braces(options) => expanded
+                    .iter()
+                    .flat_map(|prefix| {
+                        options.iter().map(move |option| {
+                            let mut out = prefix.clone();
+                            out.push(option.clone());
+                            out
+                        })
+                    })
+                    .collect(),
+                _ => expanded
+                    .into_iter()
+                    .map(|mut prefix| {
+                        prefix.push(segment.clone());
+                        prefix
+                    })
+                    .collect(),
+            };
+        }
+        expanded
+            .into_iter()
+            .map(|segments| Pattern {
+                segments: segments.into_iter().collect(),
+                spans: self.spans.clone(),
+                depth: self.depth,
+                predicate: self.predicate.clone(),
+            })
+            .collect()
+    }
+
+    /// The `depth == N` constraint of this pattern, if it has one.
+    pub fn depth(&self) -> Option<usize> {
+        self.depth
+    }
+
+    /// Parses an optional `depth == N` constraint following the pattern.
+    ///
+    /// Since `*` also matches across dots, `config.*.*` matches `config.a.b.c` too; with
+    /// `config.** depth == 2` only paths exactly two segments below `config` match.
+    pub fn parse_depth(mut self, input: ParseStream) -> SynResult<Self> {
+        let fork = input.fork();
+        if !(fork.parse::<Ident>().is_ok_and(|ident| ident == "depth") && fork.peek(Token![==])) {
+            return Ok(self);
+        }
+        let _depth: Ident = input.parse()?;
+        let _eq: Token![==] = input.parse()?;
+        let lit: syn::LitInt = input.parse()?;
+        let depth = lit.base10_parse::<usize>()?;
+        if depth == 0 {
+            return Err(syn::Error::new(lit.span(), "Expected a depth of at least 1"));
+        }
+        self.depth = Some(depth);
+        Ok(self)
+    }
+
+    /// The `== value` or `!= value` predicate of this pattern, if it has one.
+    pub fn predicate(&self) -> Option<&ValuePredicate> {
+        self.predicate.as_ref()
+    }
+
+    /// Parses an optional `== value` or `!= value` predicate following the pattern.
+    ///
+    /// The value is a string, bool or integer literal. Negated patterns use these to leave out
+    /// entries by what they have in them rather than by their path, e.g. `!*.optional == true`
+    /// leaves out the dependencies with `optional = true`.
+    pub fn parse_predicate(mut self, input: ParseStream) -> SynResult<Self> {
+        let not_equal = if input.peek(Token![==]) {
+            input.parse::<Token![==]>()?;
+            false
+        } else if input.peek(Token![!=]) {
+            input.parse::<Token![!=]>()?;
+            true
+        } else {
+            return Ok(self);
+        };
+        let negative = input.parse::<Option<Token![-]>>()?.is_some();
+        let literal = match input.parse::<syn::Lit>()? {
+            syn::Lit::Str(lit) if !negative => PredicateLiteral::Str(lit.value()),
+            syn::Lit::Bool(lit) if !negative => PredicateLiteral::Bool(lit.value),
+            syn::Lit::Int(lit) => {
+                let digits = lit.base10_digits();
+                let int = if negative { format!("-{}", digits).parse() } else { digits.parse() };
+                PredicateLiteral::Int(
+                    int.map_err(|_| syn::Error::new(lit.span(), "Expected an `i64` integer"))?,
+                )
+            },
+            lit => {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "Expected a string, bool or integer literal to compare the value with",
+                ))
+            },
+        };
+        self.predicate = Some(ValuePredicate { not_equal, literal });
+        Ok(self)
+    }
+
+    /// Checks a dotted path against the `depth == N` constraint, counting the segments below
+    /// the pattern's literal prefix (the plain identifiers before the first wildcard).
+    ///
+    /// Any path passes if the pattern has no constraint. Brace groups should be expanded
+    /// with [`Pattern::alternatives`] first, so they count towards the prefix too.
+    pub fn matches_depth(&self, path: &str) -> bool {
+        let Some(depth) = self.depth else {
+            return true;
+        };
+        let prefix_len = self
+            .segments
+            .iter()
+            .take_while(|seg| matches!(seg, PatternSegment::Ident(_)))
+            .count();
+        path.split('.').count() == prefix_len + depth
+    }
+}
+
+/// Represents a single segment in a pattern.
+///
+/// Segment types:
+/// - `Ident`: Normal identifiers for exact matching (e.g., "section", "key")
+/// - `Star`: Single wildcard (`*`) matching any one segment
+/// - `DoubleStar`: Recursive wildcard (`**`) matching any number of segments
+/// - `Wildcard`: Identifier with `?` single-character wildcards (e.g., "v?")
+/// - `Negation`: Exclusion prefix (`!`) for pattern negation
+/// - `Braces`: Comma-separated alternatives (`{a,b}`), each matching one segment
+/// - `Index`: The index of an array element (`[0]`), written right after the array's key
+/// - `Brackets`: Grouping construct (future)
+#[derive(Clone, Eq, Hash, PartialEq)]
+enum PatternSegment {
+    Ident(Ident),
+    Star,                        // *
+    DoubleStar,                  // **
+    Wildcard(String),            // v?
+    // TODO: what kind of name would this be, negation seems wrong?
+    Negation,                    // !
+    Braces(Vec<PatternSegment>), // {a,b,c}
+    Index(i64),                  // [0]
+    #[allow(dead_code)] // NOTE: useful api for future
+    Brackets(Vec<PatternSegment>),
+    #[allow(dead_code)] // NOTE: useful api for future
+    Parens,
+    // TODO: what else do we support?
+}
+
+impl Parse for Pattern {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let mut segments = Punctuated::new();
+        let mut spans = Vec::new();
+
+        if input.is_empty() {
+            return Err(input.error("Expected a valid pattern segment"));
+        }
+
+        spans.push(input.span());
+        segments.push_value(input.parse::<PatternSegment>()?);
+
+        while input.peek(Token![.]) {
+            segments.push_punct(input.parse::<Token![.]>()?);
+            spans.push(input.span());
+            segments.push_value(input.parse::<PatternSegment>()?);
+        }
+
+        // an index is told apart from a section header after the pattern by its integer
+        if input.peek(token::Bracket) && parse_index(&input.fork()).is_ok() {
+            let (index, span) = parse_index(input)?;
+            if !matches!(segments.last(), Some(PatternSegment::Ident(_))) {
+                return Err(syn::Error::new(span, "Expected an index to follow a plain key"));
+            }
+            segments.push_punct(Token![.](span));
+            spans.push(span);
+            segments.push_value(PatternSegment::Index(index));
+            if input.peek(Token![.]) {
+                return Err(syn::Error::new(
+                    span,
+                    "Expected an index to end the pattern, since only arrays of values can be \
+                     indexed (the tables of `[[bin]]` are `bin._0.*`)",
+                ));
+            }
+        }
+
+        Ok(Pattern {
+            segments,
+            spans,
+            depth: None,
+            predicate: None,
+        })
+    }
+}
+
+// the `[N]` of an index segment, optionally negative, and its span
+fn parse_index(input: ParseStream) -> SynResult<(i64, proc_macro2::Span)> {
+    let content;
+    let bracket = syn::bracketed!(content in input);
+    let negative = content.parse::<Option<Token![-]>>()?.is_some();
+    let lit: syn::LitInt = content.parse()?;
+    if !content.is_empty() {
+        return Err(content.error("Expected just the index within brackets"));
+    }
+    let index = lit.base10_parse::<i64>()?;
+    Ok((if negative { -index } else { index }, bracket.span.join()))
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.join(".", |index| format!("[{}]", index)))
+    }
+}
+
+impl ToTokens for Pattern {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let segments = self.segments.iter().collect::<Vec<_>>();
+        for (i, pair) in self.segments.pairs().enumerate() {
+            pair.value().to_tokens(tokens);
+            // an index follows its key without a dot
+            let next_is_index = matches!(segments.get(i + 1), Some(PatternSegment::Index(_)));
+            if let (Some(dot), false) = (pair.punct(), next_is_index) {
+                dot.to_tokens(tokens);
+            }
+        }
+    }
+}
+
+impl Parse for PatternSegment {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        // FIXME: handle brackets as potential glob syntax segments
+        if input.peek(token::Brace) {
+            // brace group of comma-separated alternatives, e.g. `{serde, tokio}`
+            let content;
+            let _brace = syn::braced!(content in input);
+            let options = Punctuated::<PatternSegment, Token![,]>::parse_terminated(&content)?;
+            if options.is_empty() {
+                return Err(content.error("Expected at least one alternative within braces"));
+            }
+            Ok(PatternSegment::Braces(options.into_iter().collect()))
+        } else if input.peek(Token![*]) {
+            // consume first star
+            input.parse::<Token![*]>()?;
+
+            // check for double star pattern (**)
+            if input.peek(Token![*]) {
+                // consume second star
+                input.parse::<Token![*]>()?;
+                Ok(PatternSegment::DoubleStar)
+            } else {
+                Ok(PatternSegment::Star)
+            }
+        } else if input.peek(Token![!]) {
+            // consume negation
+            input.parse::<Token![!]>()?;
+            Ok(PatternSegment::Negation)
+        } else {
+            // identifiers, joined by dashes and mixed with `?` single-character wildcards,
+            // e.g. `some-key`, `v?`, `?-beta` or `v??`
+            let span = input.span();
+            let mut combined = String::new();
+            let mut has_wildcard = false;
+            loop {
+                // `?=` gives a default to the path before it, see `RootModuleSource::defaults`
+                if input.peek(Token![?]) && !input.peek2(Token![=]) {
+                    input.parse::<Token![?]>()?;
+                    combined.push('?');
+                    has_wildcard = true;
+                } else if combined.ends_with('?') && input.peek(syn::LitInt) {
+                    // digits directly after a wildcard, e.g. `?1`
+                    combined.push_str(&input.parse::<syn::LitInt>()?.to_string());
+                } else if combined.is_empty() || combined.ends_with('_') {
+                    // NOTE: an ident straight after a `?` is not accepted, since it can't be told
+                    //       apart from the start of the next pattern; join with a dash instead
+                    combined.push_str(&input.parse::<Ident>()?.to_string());
+                } else if input.peek(Token![-]) {
+                    // consume dash, combining the parts with an underscore
+                    input.parse::<Token![-]>()?;
+                    combined.push('_');
+                } else {
+                    break;
+                }
+            }
+
+            if has_wildcard {
+                Ok(PatternSegment::Wildcard(combined))
+            } else {
+                // create new identifier from combined segments
+                Ok(PatternSegment::Ident(Ident::new(&combined, span)))
+            }
+        }
+    }
+}
+
+impl ToTokens for PatternSegment {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self {
+            PatternSegment::Ident(ident) => ident.to_tokens(tokens),
+            PatternSegment::Star => quote!(*).to_tokens(tokens),
+            PatternSegment::DoubleStar => quote!(**).to_tokens(tokens),
+            // an ident can't directly follow a `?`, so restore the dash it was joined with
+            PatternSegment::Wildcard(s) => s
+                .replace("?_", "?-")
+                .parse::<TokenStream2>()
+                .expect("Expected a wildcard segment to consist of valid tokens")
+                .to_tokens(tokens),
+            PatternSegment::Negation => quote!(!).to_tokens(tokens),
+            PatternSegment::Braces(segments) => {
+                let segments = segments.iter().map(|seg| seg.to_token_stream());
+                quote!({ #(#segments),* }).to_tokens(tokens)
+            },
+            PatternSegment::Brackets(segments) => {
+                let segments = segments.iter().map(|seg| seg.to_token_stream());
+                quote!([ #(#segments)* ]).to_tokens(tokens)
+            },
+            PatternSegment::Index(index) => {
+                let lit = proc_macro2::Literal::i64_unsuffixed(*index);
+                quote!([#lit]).to_tokens(tokens)
+            },
+            _ => {
+                unimplemented!()
+            },
+        }
+    }
+}
+
+impl Display for PatternSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternSegment::Ident(ident) => write!(f, "{}", ident),
+            PatternSegment::Star => write!(f, "*"),
+            PatternSegment::DoubleStar => write!(f, "**"),
+            PatternSegment::Wildcard(s) => write!(f, "{}", s),
+            PatternSegment::Negation => write!(f, "!"),
+            PatternSegment::Braces(segments) => {
+                let segments: Vec<_> = segments.iter().map(|seg| seg.to_string()).collect();
+                write!(f, "{{{}}}", segments.join(","))
+            },
+            PatternSegment::Brackets(segments) => {
+                let segments: Vec<_> = segments.iter().map(|seg| seg.to_string()).collect();
+                write!(f, "[{}]", segments.join(", "))
+            },
+            PatternSegment::Index(index) => write!(f, "[{}]", index),
+            _ => {
+                unimplemented!()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Pattern {
+        syn::parse_str::<Pattern>(s).expect("Expected a valid pattern")
+    }
+
+    #[test]
+    fn test_parse_braces() {
+        let pat = parse("dependencies.{serde, tokio, clap}");
+        assert_eq!(pat.to_string(), "dependencies.{serde,tokio,clap}");
+        assert_eq!(
+            pat.to_token_stream().to_string(),
+            "dependencies . { serde , tokio , clap }"
+        );
+    }
+
+    #[test]
+    fn test_parse_braces_with_dashes_and_stars() {
+        let pat = parse("{special-chars, mixed-types}.*");
+        assert_eq!(pat.to_string(), "{special_chars,mixed_types}.*");
+    }
+
+    #[test]
+    fn test_parse_empty_braces_fails() {
+        assert!(syn::parse_str::<Pattern>("dependencies.{}").is_err());
+    }
+
+    #[test]
+    fn test_braces_alternatives() {
+        let alternatives = parse("dependencies.{serde,tokio,clap}")
+            .alternatives()
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            alternatives,
+            vec![
+                "dependencies.serde",
+                "dependencies.tokio",
+                "dependencies.clap"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multiple_braces_alternatives() {
+        let alternatives = parse("{a,b}.x.{c,d}")
+            .alternatives()
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(alternatives, vec!["a.x.c", "a.x.d", "b.x.c", "b.x.d"]);
+    }
+
+    #[test]
+    fn test_double_star_glob() {
+        let matches = |pat: &str, path: &str| {
+            parse(pat)
+                .to_glob()
+                .compile_matcher()
+                .is_match(crate::utils::to_glob_path(path))
+        };
+        assert!(matches("deep.**", "deep.level1.level2.level3.value"));
+        assert!(matches("deep.**", "deep.standalone"));
+        assert!(!matches("deep.**", "deeper.standalone"));
+        assert!(matches("**.version", "version"));
+        assert!(matches("**.version", "dependencies.tokio.version"));
+        assert!(!matches("**.version", "dependencies.tokio.versions"));
+        // single star keeps matching across segments
+        assert!(matches("deep.*", "deep.level1.level2.other"));
+
+        assert!(parse("**.version").is_anywhere());
+        assert!(!parse("**").is_anywhere());
+        assert!(!parse("deep.**").is_anywhere());
+    }
+
+    #[test]
+    fn test_collecting_segment() {
+        assert_eq!(parse("dependencies.*.version").collecting_segment(), Some(1));
+        assert_eq!(parse("*.name").collecting_segment(), Some(0));
+        assert_eq!(parse("versions.v?.date").collecting_segment(), Some(1));
+        assert_eq!(parse("dependencies.*").collecting_segment(), None);
+        assert_eq!(parse("dependencies.*.*").collecting_segment(), None);
+        assert_eq!(parse("**.version").collecting_segment(), None);
+        assert_eq!(parse("package.version").collecting_segment(), None);
+    }
+
+    #[test]
+    fn test_subtree_root() {
+        assert_eq!(parse("app.*").subtree_root().as_deref(), Some("app"));
+        assert_eq!(parse("app.sub-tree.**").subtree_root().as_deref(), Some("app.sub_tree"));
+        assert_eq!(parse("app.key").subtree_root(), None);
+        assert_eq!(parse("*").subtree_root(), None);
+        assert_eq!(parse("app.*.key").subtree_root(), None);
+        assert_eq!(parse("{a,b}.*").subtree_root(), None);
+    }
+
+    #[test]
+    fn test_no_braces_alternatives() {
+        let pat = parse("section.*");
+        assert_eq!(pat.alternatives(), vec![pat]);
+    }
+
+    #[test]
+    fn test_parse_question_mark() {
+        let pat = parse("version.v?");
+        assert_eq!(pat.to_string(), "version.v?");
+        assert_eq!(pat.to_token_stream().to_string(), "version . v ?");
+        assert_eq!(parse("?").to_string(), "?");
+        assert_eq!(parse("?-beta.?1").to_string(), "?_beta.?1");
+        assert_eq!(parse("v??").to_string(), "v??");
+        assert_eq!(parse("some-key-?").to_string(), "some_key_?");
+        // round-trips through tokens
+        let pat = parse("a.?-b?1.c");
+        assert_eq!(syn::parse2::<Pattern>(pat.to_token_stream()).unwrap(), pat);
+    }
+
+    #[test]
+    fn test_question_mark_glob() {
+        let matches = |pat: &str, path: &str| {
+            parse(pat)
+                .to_glob()
+                .compile_matcher()
+                .is_match(crate::utils::to_glob_path(path))
+        };
+        assert!(matches("version.v?", "version.v1"));
+        assert!(matches("version.v?", "version.v9"));
+        assert!(!matches("version.v?", "version.v10"));
+        assert!(!matches("version.v?", "version.v"));
+        assert!(matches("version.v??", "version.v10"));
+        assert!(matches("?-beta", "x_beta"));
+        // within a segment, so never the dot between two (`a?b` itself can't be written, since
+        // an ident after a `?` would start the next pattern)
+        assert!(!matches("v?1", "v.1"));
+        assert!(matches("v?1", "v21"));
+    }
+
+    #[test]
+    fn test_parse_depth() {
+        let parser = |input: ParseStream| Pattern::parse(input)?.parse_depth(input);
+        let pat = syn::parse::Parser::parse_str(parser, "config.** depth == 2").unwrap();
+        assert_eq!(pat.depth(), Some(2));
+        assert_eq!(pat.to_string(), "config.**");
+        assert_ne!(pat, parse("config.**"));
+        assert!(pat.matches_depth("config.a.b"));
+        assert!(!pat.matches_depth("config.a"));
+        assert!(!pat.matches_depth("config.a.b.c"));
+        // without a constraint, any depth goes
+        assert!(parse("config.**").matches_depth("config.a.b.c"));
+        assert!(syn::parse::Parser::parse_str(parser, "config.** depth == 0").is_err());
+    }
+
+    #[test]
+    fn test_parse_index() {
+        let pat = parse("package.authors[0]");
+        assert_eq!(pat.to_string(), "package.authors[0]");
+        assert_eq!(pat.to_token_stream().to_string(), "package . authors [0]");
+        assert_eq!(pat.to_glob().glob(), "package/authors_0");
+        assert_eq!(pat.index(), Some(("package.authors".to_string(), 0)));
+        let from_end = parse("package.authors[-1]");
+        assert_eq!(from_end.index(), Some(("package.authors".to_string(), -1)));
+        assert_eq!(from_end.with_index(1).to_string(), "package.authors[1]");
+        assert_eq!(parse("package.authors").index(), None);
+        // only plain keys of arrays of values can be indexed
+        assert!(syn::parse_str::<Pattern>("package.*[0]").is_err());
+        assert!(syn::parse_str::<Pattern>("bin[0].name").is_err());
+        assert!(syn::parse_str::<Pattern>("package.authors[first]").is_err());
+    }
+
+    #[test]
+    fn test_parse_predicate() {
+        let parser = |input: ParseStream| Pattern::parse(input)?.parse_predicate(input);
+        let predicate = |s: &str| {
+            let pat = syn::parse::Parser::parse_str(parser, s).unwrap();
+            pat.predicate().cloned()
+        };
+        let optional = predicate("*.optional == true").unwrap();
+        assert!(optional.matches(&Value::Boolean(true)));
+        assert!(!optional.matches(&Value::Boolean(false)));
+        assert!(!optional.matches(&Value::String("true".into())));
+        let branch = predicate("*.branch != \"main\"").unwrap();
+        assert!(branch.matches(&Value::String("dev".into())));
+        assert!(!branch.matches(&Value::String("main".into())));
+        // other types never equal the literal
+        assert!(branch.matches(&Value::Integer(1)));
+        let level = predicate("log.level == -1").unwrap();
+        assert!(level.matches(&Value::Integer(-1)));
+        assert_eq!(predicate("dependencies.*"), None);
+        assert!(syn::parse::Parser::parse_str(parser, "a.b == 1.5").is_err());
+        assert!(syn::parse::Parser::parse_str(parser, "a.b == -true").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use crate::annotation::Annotation;
+use crate::utils::{
+    convert_annotated_value_to_tokens, convert_value_to_tokens, find_key, get_at, is_table_array,
+    resolve_keys,
+};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::Result as SynResult;
+use toml::Value;
+
+/// The key of a per-OS table holding the value for targets that don't have their own.
+pub const DEFAULT_KEY: &str = "default";
+
+/// The values a `select_os` statement picks from by the target OS.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OsSelect {
+    /// TOML path of the table the values were taken from
+    pub source: String,
+    /// The values by `target_os`, in the order they're checked
+    pub variants: Vec<(String, Value)>,
+    /// The value for any other target, if the table has a `default`
+    pub default: Option<Value>,
+}
+
+impl OsSelect {
+    /// Generates the type and value of the const selecting between the values, along with
+    /// the attributes it needs.
+    ///
+    /// The value checks `cfg!(target_os = ..)` for each OS in turn, which the crate using the
+    /// macro evaluates for its own target (the macro itself is built for the host). Without a
+    /// `default`, the const only exists on the listed OSes, so using it elsewhere fails to build.
+    pub fn generate(
+        &self,
+        annotation: Option<&Annotation>,
+    ) -> SynResult<(TokenStream2, TokenStream2, TokenStream2)> {
+        let mut values = Vec::new();
+        for (_, value) in &self.variants {
+            values.push(convert_annotated_value_to_tokens(value, annotation)?);
+        }
+        let fallback = match &self.default {
+            Some(default) => Some(convert_annotated_value_to_tokens(default, annotation)?),
+            None => None,
+        };
+        let oses = self.variants.iter().map(|(os, _)| os).collect::<Vec<_>>();
+        let (attrs, checked, (ty, last)) = match fallback {
+            Some(fallback) => (quote! {}, oses.len(), fallback),
+            None => (
+                quote! { #[cfg(any(#(target_os = #oses),*))] },
+                oses.len() - 1,
+                values
+                    .pop()
+                    .expect("Expected an os select to have at least one value"),
+            ),
+        };
+        let checked_oses = &oses[..checked];
+        let checked_values = values.iter().map(|(_, val)| val);
+        let val = quote! {
+            #(if ::core::cfg!(target_os = #checked_oses) { #checked_values } else)* { #last }
+        };
+        Ok((ty, val, attrs))
+    }
+}
+
+/// Moves the per-OS table at `source` to a single value at `target`.
+///
+/// Both are dotted paths as written in the macro (dashes read as underscores). The table's keys
+/// are `target_os` values (`linux`, `macos`, `windows`, ..) and optionally `default`, and its
+/// values all have to be of the same type. The value left at `target` is the `default` or the
+/// first of the others, so the field is extracted and typed like any other value.
+///
+/// # Returns
+/// The TOML path the value ended up at, and the values to select between.
+pub fn apply_os_select(
+    toml: &mut Value,
+    target: &str,
+    source: &str,
+) -> Result<(String, OsSelect), String> {
+    let source_keys = resolve_keys(toml, source)
+        .ok_or_else(|| format!("`{}` doesn't exist, so there's nothing to select from", source))?;
+    let source_path = source_keys.join(".");
+    let Some(Value::Table(table)) = get_at(toml, &source_keys) else {
+        return Err(format!("`{}` isn't a table of values by target OS", source_path));
+    };
+    let mut variants = Vec::new();
+    let mut default = None;
+    for (key, value) in table {
+        let is_table = matches!(value, Value::Table(_))
+            || matches!(value, Value::Array(arr) if is_table_array(arr));
+        if is_table {
+            return Err(format!(
+                "`{}.{}` is a table, but `select_os` only picks between values",
+                source_path, key
+            ));
+        }
+        if key == DEFAULT_KEY {
+            default = Some(value.clone());
+        } else {
+            variants.push((key.clone(), value.clone()));
+        }
+    }
+    // the generated types have to match, which for arrays goes for their elements too
+    let all = variants
+        .iter()
+        .map(|(key, value)| (key.as_str(), value))
+        .chain(default.iter().map(|value| (DEFAULT_KEY, value)))
+        .collect::<Vec<_>>();
+    let Some((first_key, first)) = all.first() else {
+        return Err(format!("`{}` is empty, so there's nothing to select from", source_path));
+    };
+    let first_ty = convert_value_to_tokens(first).0.to_string();
+    if let Some((key, value)) = all
+        .iter()
+        .find(|(_, value)| convert_value_to_tokens(value).0.to_string() != first_ty)
+    {
+        return Err(format!(
+            "The values in `{}` have to be of the same type, but `{}` is of type {} and `{}` of \
+             type {}",
+            source_path,
+            first_key,
+            first.type_str(),
+            key,
+            value.type_str()
+        ));
+    }
+
+    // the source goes away, so the target may well be the source itself
+    let (source_key, source_parent) = source_keys
+        .split_last()
+        .expect("Expected a resolved path to have at least one key");
+    if let Some(Value::Table(parent)) = get_at_mut(toml, source_parent) {
+        parent.remove(source_key);
+    }
+    let target_segments = target.split('.').collect::<Vec<_>>();
+    let (target_name, target_parent) = target_segments
+        .split_last()
+        .expect("Expected a path to have at least one segment");
+    let mut target_keys = Vec::new();
+    let mut table = toml;
+    for segment in target_parent {
+        let Value::Table(current) = table else {
+            return Err(format!("`{}` can't be placed in a value", target));
+        };
+        let key = find_key(current, segment).unwrap_or_else(|| segment.to_string());
+        target_keys.push(key.clone());
+        table = current
+            .entry(key)
+            .or_insert_with(|| Value::Table(Default::default()));
+    }
+    let Value::Table(parent) = table else {
+        return Err(format!("`{}` can't be placed in a value", target));
+    };
+    if find_key(parent, target_name).is_some() {
+        return Err(format!("`{}` already exists, select into another path", target));
+    }
+    let placeholder = default
+        .clone()
+        .unwrap_or_else(|| variants[0].1.clone());
+    parent.insert(target_name.to_string(), placeholder);
+    target_keys.push(target_name.to_string());
+
+    Ok((
+        target_keys.join("."),
+        OsSelect {
+            source: source_path,
+            variants,
+            default,
+        },
+    ))
+}
+
+fn get_at_mut<'a>(value: &'a mut Value, keys: &[String]) -> Option<&'a mut Value> {
+    keys.iter()
+        .try_fold(value, |current, key| current.as_table_mut()?.get_mut(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toml(s: &str) -> Value {
+        s.parse().expect("Expected valid toml")
+    }
+
+    #[test]
+    fn test_apply_os_select() {
+        let mut value = toml(
+            r#"
+            [app]
+            cache-by-os = { linux = "~/.cache", windows = "%LOCALAPPDATA%", default = "/tmp" }
+            "#,
+        );
+        let (path, select) = apply_os_select(&mut value, "app.cache", "app.cache_by_os").unwrap();
+        assert_eq!(path, "app.cache");
+        assert_eq!(select.source, "app.cache-by-os");
+        assert_eq!(select.variants.len(), 2);
+        assert_eq!(select.default, Some(Value::from("/tmp")));
+        // the source is consumed and the target holds the default in its place
+        assert!(value["app"].get("cache-by-os").is_none());
+        assert_eq!(value["app"]["cache"].as_str(), Some("/tmp"));
+    }
+
+    #[test]
+    fn test_apply_os_select_in_place() {
+        let mut value = toml("[paths.cache]\nlinux = 1\nmacos = 2\n");
+        let (path, select) = apply_os_select(&mut value, "paths.cache", "paths.cache").unwrap();
+        assert_eq!(path, "paths.cache");
+        assert_eq!(select.default, None);
+        assert_eq!(value["paths"]["cache"].as_integer(), Some(1));
+    }
+
+    #[test]
+    fn test_apply_os_select_errors() {
+        let mut value = toml("[app]\nmixed = { linux = 1, macos = \"two\" }\nname = \"x\"\n");
+        assert!(apply_os_select(&mut value, "app.a", "app.missing")
+            .unwrap_err()
+            .contains("doesn't exist"));
+        assert!(apply_os_select(&mut value, "app.a", "app.name")
+            .unwrap_err()
+            .contains("isn't a table"));
+        assert!(apply_os_select(&mut value, "app.a", "app.mixed")
+            .unwrap_err()
+            .contains("same type"));
+        let mut value = toml("[app]\nname = \"x\"\nby-os = { linux = \"y\" }\n");
+        assert!(apply_os_select(&mut value, "app.name", "app.by_os")
+            .unwrap_err()
+            .contains("already exists"));
+    }
+
+    #[test]
+    fn test_generate() {
+        let select = OsSelect {
+            source: "app.cache".to_string(),
+            variants: vec![
+                ("linux".to_string(), Value::from(1)),
+                ("macos".to_string(), Value::from(2)),
+            ],
+            default: None,
+        };
+        let (ty, val, attrs) = select.generate(None).unwrap();
+        assert_eq!(ty.to_string(), "i64");
+        assert!(val.to_string().contains("target_os = \"linux\""));
+        // the last os needs no check of its own, the `#[cfg]` already limits the const to it
+        assert!(!val.to_string().contains("target_os = \"macos\""));
+        assert!(attrs.to_string().contains("target_os = \"macos\""));
+    }
+}
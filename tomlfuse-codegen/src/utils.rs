@@ -0,0 +1,1488 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use crate::annotation::Annotation;
+use crate::field::{TomlField, ROOT};
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+use syn::ext::IdentExt;
+use syn::parse::ParseStream;
+use syn::{Ident, LitStr, Result as SynResult, Token};
+use toml::value::{Datetime, Offset};
+use toml::Value;
+
+/// The longest mixed array bound as a tuple, as std only implements traits like `Debug` and
+/// `PartialEq` for tuples up to this long.
+pub const MAX_TUPLE_LEN: usize = 12;
+
+/// Converts a toml `Value` into a pair of tokens:
+/// - First token represents the rust type (`&'static str`, `i64`, etc.)
+/// - Second token represents the literal value (`"foo"`, `42`, etc.)
+///
+/// Supports strings, integers, floats, booleans, datetimes, and homogeneous arrays.
+/// Arrays of mixed scalars become tuples, up to `MAX_TUPLE_LEN` elements.
+/// Falls back to string representation for complex/mixed types (which should not be used/valid anyway).
+#[cold]
+pub fn convert_value_to_tokens(value: &Value) -> (TokenStream2, TokenStream2) {
+    convert_value_to_radix_tokens(value, None)
+}
+
+/// Converts a toml `Value` like `convert_value_to_tokens`, but with integers written in the
+/// given radix (16, 8 or 2) rather than in decimal, e.g. `0o755i64` for a file mode.
+///
+/// TOML forgets how an integer was written, so the radix has to be asked for, see
+/// `AnnotationKind::Radix`. It applies to the integers in arrays and tuples too.
+#[cold]
+pub fn convert_value_to_radix_tokens(
+    value: &Value,
+    radix: Option<u32>,
+) -> (TokenStream2, TokenStream2) {
+    convert_value(value, radix, false).expect("Expected the lenient conversion to never fail")
+}
+
+/// Converts a toml `Value` like `convert_value_to_tokens`, but without falling back to a
+/// string for the values that have no typed constant.
+///
+/// # Returns
+/// The tokens, or what the value is if it has no typed constant, e.g. "an array of mixed
+/// types that can't be a tuple".
+#[cold]
+pub fn try_convert_value_to_tokens(value: &Value) -> Result<(TokenStream2, TokenStream2), String> {
+    convert_value(value, None, true)
+}
+
+// converts a value, with the untyped ones either stringified or, when strict, errors
+fn convert_value(
+    value: &Value,
+    radix: Option<u32>,
+    strict: bool,
+) -> Result<(TokenStream2, TokenStream2), String> {
+    let stringified = |what: &str, val_str: String| {
+        if strict {
+            Err(what.to_string())
+        } else {
+            Ok((quote! { &'static str }, quote! { #val_str }))
+        }
+    };
+    Ok(match value {
+        Value::String(s) => (quote! { &'static str }, quote! { #s }),
+        Value::Integer(i) => {
+            let lit = radix.map_or(quote! { #i }, |radix| radix_literal(*i, radix));
+            (quote! { i64 }, lit)
+        },
+        Value::Float(f) => (quote! { f64 }, float_literal(*f)),
+        Value::Boolean(b) => (quote! { bool }, quote! { #b }),
+        Value::Datetime(dt) => {
+            // TODO: proper DateTime support via chrono or similar
+            let dt_str = rfc3339(dt);
+            (quote! { &'static str }, quote! { #dt_str })
+        },
+        Value::Array(arr) => {
+            if arr.is_empty() {
+                (quote! { &'static [&'static str] }, quote! { &[] })
+            } else {
+                // single-step recurse to get type and value for the first element
+                let (elem_ty, _) = convert_value(&arr[0], radix, strict)?;
+                // check all elements are of the same variant as the first (should be the case for most use cases)
+                let same_type = arr
+                    .iter()
+                    .all(|v| std::mem::discriminant(v) == std::mem::discriminant(&arr[0]));
+                if same_type {
+                    let elems = arr
+                        .iter()
+                        .map(|v| convert_value(v, radix, strict).map(|(_, val)| val))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    (quote! { &'static [#elem_ty] }, quote! { &[#(#elems),*] })
+                } else if arr.len() <= MAX_TUPLE_LEN
+                    && !arr.iter().any(|v| v.is_array() || v.is_table())
+                {
+                    // mixed scalars make a tuple, e.g. `[1.0, 2.0, "label"]`
+                    let (tys, vals): (Vec<_>, Vec<_>) = arr
+                        .iter()
+                        .map(|v| convert_value(v, radix, strict))
+                        .collect::<Result<Vec<_>, _>>()?
+                        .into_iter()
+                        .unzip();
+                    (quote! { (#(#tys,)*) }, quote! { (#(#vals,)*) })
+                } else {
+                    // fallback for mixed types that can't be a tuple
+                    return stringified(
+                        "an array of mixed types that can't be a tuple",
+                        format!("{:?}", arr),
+                    );
+                }
+            }
+        },
+        _ => {
+            // fallback for unsupported types, are there any we should support?
+            return stringified("a table", format!("{}", value));
+        },
+    })
+}
+
+/// Converts the elements of a toml array into tokens for a `[T; N]` array of them, with the
+/// length in the type, rather than the `&'static [T]` slice arrays otherwise are.
+///
+/// # Returns
+/// The tokens, or a message if the elements aren't all of the same type, which an array needs.
+#[cold]
+pub fn convert_array_to_fixed_tokens(
+    arr: &[Value],
+) -> Result<(TokenStream2, TokenStream2), String> {
+    let elems = arr
+        .iter()
+        .map(try_convert_value_to_tokens)
+        .collect::<Result<Vec<_>, _>>()?;
+    let elem_ty = match elems.first() {
+        Some((ty, _)) => ty.to_string(),
+        None => return Ok((quote! { [&'static str; 0] }, quote! { [] })),
+    };
+    if let Some(i) = elems.iter().position(|(ty, _)| ty.to_string() != elem_ty) {
+        return Err(format!(
+            "element {} (of type {}) isn't of the same type as the first (of type {}), which \
+             all elements of an array have to be",
+            i,
+            arr[i].type_str(),
+            arr[0].type_str()
+        ));
+    }
+    let len = arr.len();
+    let ty = &elems[0].0;
+    let vals = elems.iter().map(|(_, val)| val);
+    Ok((quote! { [#ty; #len] }, quote! { [#(#vals),*] }))
+}
+
+/// Writes a float as an `f64` literal that parses back to exactly the same value.
+///
+/// Very large and small floats are written with an exponent (`1e300f64`) rather than spelled
+/// out, and the sign of a negative zero is kept. TOML's `inf` and `nan` have no literal, and
+/// their constants aren't what a config value means to say, so they're a `compile_error!`.
+fn float_literal(f: f64) -> TokenStream2 {
+    if !f.is_finite() {
+        let msg = format!("`{}` isn't a finite float, which a constant can't be bound to", f);
+        return quote! { ::core::compile_error!(#msg) };
+    }
+    // `Debug` is the shortest representation that round-trips, and switches to an exponent
+    // at the extremes
+    let lit = syn::LitFloat::new(&format!("{:?}f64", f.abs()), proc_macro2::Span::call_site());
+    if f.is_sign_negative() {
+        quote! { -#lit }
+    } else {
+        quote! { #lit }
+    }
+}
+
+/// Converts a toml `Value` like `convert_value_to_tokens`, but with integers bound as the
+/// smallest signed type that fits them (`i8`, `i16`, `i32` or `i64`) instead of always `i64`.
+///
+/// Arrays of integers share the smallest type that fits all of their elements, so `[1, 2, 300]`
+/// becomes `&'static [i16]`. Everything else converts as usual.
+#[cold]
+pub fn convert_value_to_narrowed_tokens(value: &Value) -> (TokenStream2, TokenStream2) {
+    match value {
+        Value::Integer(i) => {
+            let ty = narrowest_int_type([*i]);
+            let ident = format_ident!("{}", ty);
+            let lit = int_literal(*i, ty);
+            (quote! { #ident }, quote! { #lit })
+        },
+        Value::Array(arr) if !arr.is_empty() && arr.iter().all(Value::is_integer) => {
+            let ints = arr.iter().filter_map(Value::as_integer).collect::<Vec<_>>();
+            let ty = narrowest_int_type(ints.iter().copied());
+            let ident = format_ident!("{}", ty);
+            let lits = ints.iter().map(|i| int_literal(*i, ty));
+            (quote! { &'static [#ident] }, quote! { &[#(#lits),*] })
+        },
+        _ => convert_value_to_tokens(value),
+    }
+}
+
+// the smallest signed integer type that fits all of the given integers
+fn narrowest_int_type(ints: impl IntoIterator<Item = i64>) -> &'static str {
+    let (min, max) = ints.into_iter().fold((0, 0), |(min, max), i| (i.min(min), i.max(max)));
+    if min >= i8::MIN as i64 && max <= i8::MAX as i64 {
+        "i8"
+    } else if min >= i16::MIN as i64 && max <= i16::MAX as i64 {
+        "i16"
+    } else if min >= i32::MIN as i64 && max <= i32::MAX as i64 {
+        "i32"
+    } else {
+        "i64"
+    }
+}
+
+// an integer literal suffixed with `ty`, which it's known to fit in
+fn int_literal(int: i64, ty: &str) -> Literal {
+    match ty {
+        "i8" => Literal::i8_suffixed(int as i8),
+        "i16" => Literal::i16_suffixed(int as i16),
+        "i32" => Literal::i32_suffixed(int as i32),
+        _ => Literal::i64_suffixed(int),
+    }
+}
+
+// an `i64` literal in the given radix, negated outside of it since literals can't be negative;
+// `i64::MIN` has no positive counterpart to negate, so it stays decimal
+fn radix_literal(int: i64, radix: u32) -> TokenStream2 {
+    if int == i64::MIN {
+        return quote! { #int };
+    }
+    let abs = int.unsigned_abs();
+    let digits = match radix {
+        16 => format!("0x{:X}i64", abs),
+        8 => format!("0o{:o}i64", abs),
+        2 => format!("0b{:b}i64", abs),
+        _ => format!("{}i64", abs),
+    };
+    let lit = digits
+        .parse::<Literal>()
+        .expect("Expected a radix literal to be a valid literal");
+    if int < 0 {
+        quote! { -#lit }
+    } else {
+        quote! { #lit }
+    }
+}
+
+/// Makes the tokens of a bound value owned, for the fns of `as_fns` sections.
+///
+/// `&str` becomes a `String`, and slices and arrays become `Vec`s of their elements made owned
+/// in turn, so e.g. `&'static [&'static str]` becomes `Vec<String>`. Anything else is already
+/// owned and is returned as it is.
+///
+/// # Returns
+/// The owned type, and the expression making it from `val`.
+pub fn to_owned_tokens(ty: &syn::Type, val: TokenStream2) -> (TokenStream2, TokenStream2) {
+    if let syn::Type::Reference(reference) = ty {
+        let elem = match &*reference.elem {
+            syn::Type::Path(path) if path.path.is_ident("str") => {
+                return (
+                    quote! { ::std::string::String },
+                    quote! { ::std::string::String::from(#val) },
+                );
+            },
+            syn::Type::Slice(slice) => &slice.elem,
+            syn::Type::Array(array) => &array.elem,
+            _ => return (quote! { #ty }, val),
+        };
+        let (elem_ty, elem_val) = to_owned_tokens(elem, quote! { *elem });
+        return (
+            quote! { ::std::vec::Vec<#elem_ty> },
+            quote! { #val.iter().map(|elem| #elem_val).collect() },
+        );
+    }
+    (quote! { #ty }, val)
+}
+
+/// Makes the tokens of a bound value deserializable, for the structs of `serde` sections.
+///
+/// References can't be deserialized into, and the owned `String`s and `Vec`s that can't be made
+/// in a const, so references become `Cow<'static, ..>`s borrowing the value, which deserialize
+/// into owned ones. The elements of slices and tuples are made so in turn, so e.g.
+/// `&'static [&'static str]` becomes `Cow<'static, [Cow<'static, str>]>`. Anything else is
+/// returned as it is.
+///
+/// # Returns
+/// The deserializable type, and `val` made into it.
+pub fn to_serde_tokens(ty: &syn::Type, val: &syn::Expr) -> (TokenStream2, TokenStream2) {
+    (serde_type(ty), serde_value(ty, val))
+}
+
+// the type half of `to_serde_tokens`
+fn serde_type(ty: &syn::Type) -> TokenStream2 {
+    match ty {
+        syn::Type::Reference(reference) => {
+            let elem = match &*reference.elem {
+                syn::Type::Slice(slice) => {
+                    let elem = serde_type(&slice.elem);
+                    quote! { [#elem] }
+                },
+                syn::Type::Array(array) => {
+                    let (elem, len) = (serde_type(&array.elem), &array.len);
+                    quote! { [#elem; #len] }
+                },
+                elem => quote! { #elem },
+            };
+            quote! { ::std::borrow::Cow<'static, #elem> }
+        },
+        syn::Type::Tuple(tuple) => {
+            let elems = tuple.elems.iter().map(serde_type);
+            quote! { (#(#elems,)*) }
+        },
+        _ => quote! { #ty },
+    }
+}
+
+// the value half of `to_serde_tokens`, following the type
+fn serde_value(ty: &syn::Type, val: &syn::Expr) -> TokenStream2 {
+    match (ty, val) {
+        (syn::Type::Reference(reference), syn::Expr::Reference(expr)) => {
+            let elem_ty = match &*reference.elem {
+                syn::Type::Slice(slice) => Some(&slice.elem),
+                syn::Type::Array(array) => Some(&array.elem),
+                _ => None,
+            };
+            match (elem_ty, &*expr.expr) {
+                (Some(elem_ty), syn::Expr::Array(array)) => {
+                    let elems = array.elems.iter().map(|elem| serde_value(elem_ty, elem));
+                    quote! { ::std::borrow::Cow::Borrowed(&[#(#elems),*]) }
+                },
+                _ => quote! { ::std::borrow::Cow::Borrowed(#val) },
+            }
+        },
+        (syn::Type::Reference(_), _) => quote! { ::std::borrow::Cow::Borrowed(#val) },
+        (syn::Type::Tuple(tuple), syn::Expr::Tuple(expr)) => {
+            let elems = tuple
+                .elems
+                .iter()
+                .zip(&expr.elems)
+                .map(|(ty, val)| serde_value(ty, val));
+            quote! { (#(#elems,)*) }
+        },
+        _ => quote! { #val },
+    }
+}
+
+/// Converts a toml `Value` into type and value tokens, honoring an optional annotation.
+///
+/// Without an annotation this is just `convert_value_to_tokens`. With one, the annotation
+/// decides the type, and a value it can't represent becomes an error at the annotation's span.
+#[cold]
+pub fn convert_annotated_value_to_tokens(
+    value: &Value,
+    annotation: Option<&Annotation>,
+) -> SynResult<(TokenStream2, TokenStream2)> {
+    match annotation {
+        Some(annotation) => annotation.convert(value).map_err(|msg| {
+            syn::Error::new(
+                annotation.span(),
+                format!("Can't bind value as `{}`: {}", annotation, msg),
+            )
+        }),
+        None => Ok(convert_value_to_tokens(value)),
+    }
+}
+
+/// Formats a TOML datetime as RFC 3339, regardless of how it was written in the TOML.
+///
+/// Offset datetimes are full RFC 3339 timestamps, with `T` between the date and the time, the
+/// fraction of a second without trailing zeros, and a zero offset as `Z`
+/// (`1979-05-27 07:32:00+00:00` is `1979-05-27T07:32:00Z`). TOML's local forms have no RFC 3339
+/// equivalent, so they are the respective parts of one: local datetimes lack the offset
+/// (`1979-05-27T07:32:00`), dates are RFC 3339 `full-date`s (`1979-05-27`) and times are
+/// `partial-time`s (`07:32:00.5`).
+pub fn rfc3339(dt: &Datetime) -> String {
+    let mut out = String::new();
+    if let Some(date) = &dt.date {
+        out.push_str(&format!("{:04}-{:02}-{:02}", date.year, date.month, date.day));
+    }
+    if let Some(time) = &dt.time {
+        if dt.date.is_some() {
+            out.push('T');
+        }
+        out.push_str(&format!("{:02}:{:02}:{:02}", time.hour, time.minute, time.second));
+        if time.nanosecond != 0 {
+            let fraction = format!("{:09}", time.nanosecond);
+            out.push('.');
+            out.push_str(fraction.trim_end_matches('0'));
+        }
+    }
+    match dt.offset {
+        Some(Offset::Z | Offset::Custom { minutes: 0 }) => out.push('Z'),
+        Some(Offset::Custom { minutes }) => {
+            let sign = if minutes < 0 { '-' } else { '+' };
+            let minutes = minutes.unsigned_abs();
+            out.push_str(&format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60));
+        },
+        None => {},
+    }
+    out
+}
+
+/// Converts a TOML `Value` to a string token representation.
+///
+/// String values are kept as-is, other types are converted to string form.
+#[inline]
+pub fn value_to_string_token(value: &Value) -> TokenStream2 {
+    match value {
+        Value::String(s) => quote! { #s },
+        Value::Datetime(dt) => {
+            let s = rfc3339(dt);
+            quote! { #s }
+        },
+        _ => {
+            // render any value as string token
+            let s = value.to_string();
+            if s.contains("& ") {
+                // remove unnecessary space after ampersand if no lifetime
+                let s = s.replace("& ", "&");
+                quote! { #s }
+            } else {
+                // no processing required
+                quote! { #s }
+            }
+        },
+    }
+}
+
+/// Wraps a field's comment into a `#[doc = "..."]` attribute token.
+///
+/// Blank `#` lines in the TOML comment (kept as empty lines by `extract_comments`) separate
+/// paragraphs, so each run of them becomes a single blank line for rustdoc, and ones at the
+/// start or the end are dropped. Other lines are kept as they are.
+///
+/// The comments of array elements are listed after it, a bullet per element with its value.
+///
+/// Returns empty tokens if the field has no comment.
+#[inline]
+pub fn get_doc_comment(field: &TomlField) -> TokenStream2 {
+    // println!(" >> Figuring out the comment for field: {}", field.name);
+    match doc_comment_lit(field) {
+        Some(lit) => quote! {
+            #[doc = #lit]
+        },
+        None => quote! {},
+    }
+}
+
+/// Like `get_doc_comment`, but as an inner `#![doc = "..."]` attribute, for putting a table's
+/// comment at the top of its module body with `inner_docs`.
+#[inline]
+pub fn get_inner_doc_comment(field: &TomlField) -> TokenStream2 {
+    match doc_comment_lit(field) {
+        Some(lit) => quote! {
+            #![doc = #lit]
+        },
+        None => quote! {},
+    }
+}
+
+// the text of a field's docs, see `get_doc_comment`
+fn doc_comment_lit(field: &TomlField) -> Option<LitStr> {
+    let mut comment = to_doc_paragraphs(field.comment.as_deref().unwrap_or_default());
+    let elements = field
+        .element_comments
+        .iter()
+        .filter_map(|(i, text)| {
+            let value = field.value.as_array()?.get(*i)?;
+            // continuation lines are indented to stay in the bullet
+            Some(format!("- `{}`: {}", value, text.replace('\n', "\n  ")))
+        })
+        .collect::<Vec<_>>();
+    if !elements.is_empty() {
+        if !comment.is_empty() {
+            comment.push_str("\n\n");
+        }
+        comment.push_str(&elements.join("\n"));
+    }
+    // println!("     >> It did have a comment! ({})", comment);
+    (!comment.is_empty()).then(|| LitStr::new(&comment, proc_macro2::Span::call_site()))
+}
+
+// joins comment lines back up with exactly one blank line between paragraphs
+fn to_doc_paragraphs(comment: &str) -> String {
+    let mut paragraphs: Vec<Vec<&str>> = vec![Vec::new()];
+    for line in comment.lines() {
+        if line.trim().is_empty() {
+            paragraphs.push(Vec::new());
+        } else {
+            paragraphs.last_mut().expect("Expected a paragraph").push(line);
+        }
+    }
+    paragraphs
+        .iter()
+        .filter(|lines| !lines.is_empty())
+        .map(|lines| lines.join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Finds the workspace root by traversing upward from `CARGO_MANIFEST_DIR`.
+///
+/// See `workspace_root_of`, which this reads the manifest directory for.
+#[cold]
+pub fn find_workspace_root() -> PathBuf {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
+    workspace_root_of(Path::new(&manifest_dir))
+}
+
+/// Finds the workspace root by traversing upward from `manifest_dir`.
+///
+/// Searches parent directories until it finds one with a Cargo.toml file
+/// that contains a `[workspace]` table. This allows finding the workspace
+/// root from any crate within the workspace.
+///
+/// # Returns
+/// Falls back to the original manifest directory if no workspace root is found.
+pub fn workspace_root_of(manifest_dir: &Path) -> PathBuf {
+    // search upwards for workspace root
+    let mut path = manifest_dir.to_path_buf();
+
+    while !is_workspace_root(&path.join("Cargo.toml")) {
+        if !path.pop() {
+            // fallback to pkg dir if no workspace found
+            return manifest_dir.to_path_buf();
+        }
+    }
+    path
+}
+
+/// Writes an absolute path relative to `CARGO_MANIFEST_DIR`, so that it doesn't carry the
+/// directories of the machine it was built on, see `relative_to_dir`.
+pub fn relative_to_manifest_dir(path: &str) -> String {
+    match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(manifest_dir) => relative_to_dir(path, Path::new(&manifest_dir)),
+        None => path.to_string(),
+    }
+}
+
+/// Writes an absolute path relative to `dir`.
+///
+/// Steps up with `..` for paths outside the directory, like the workspace root's manifest is
+/// for a member. Relative paths are taken to be relative to it already and are returned as given.
+pub fn relative_to_dir(path: &str, dir: &Path) -> String {
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        return path.to_string_lossy().into_owned();
+    }
+    let mut relative = PathBuf::new();
+    for base in dir.ancestors() {
+        if let Ok(rest) = path.strip_prefix(base) {
+            return relative.join(rest).to_string_lossy().replace('\\', "/");
+        }
+        relative.push("..");
+    }
+    path.to_string_lossy().into_owned()
+}
+
+/// The workspace directory a build declares in `CARGO_WORKSPACE_DIR`, for builds where the
+/// sources aren't where `CARGO_MANIFEST_DIR` points, like vendored or Bazel builds.
+///
+/// Cargo itself doesn't set it, but it's commonly set in `.cargo/config.toml` `[env]`.
+#[cold]
+pub fn declared_workspace_dir() -> Option<PathBuf> {
+    env::var_os("CARGO_WORKSPACE_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Finds cargo's target directory, for sources generated into it by other crates' builds.
+///
+/// Follows cargo's precedence as far as a macro can see it. `CARGO_TARGET_DIR` (or
+/// `CARGO_BUILD_TARGET_DIR`) comes first, resolving a relative one against the workspace root
+/// like cargo does when invoked there. Then the `build.target-dir` of the nearest cargo config
+/// above the manifest directory, and cargo's default of `target` in the workspace root otherwise.
+///
+/// A `--target-dir` given on the command line isn't passed on to macros, so it can't be followed.
+#[cold]
+pub fn find_target_dir() -> PathBuf {
+    let from_env = ["CARGO_TARGET_DIR", "CARGO_BUILD_TARGET_DIR"]
+        .into_iter()
+        .filter_map(env::var_os)
+        .find(|dir| !dir.is_empty());
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
+    let configured = || configured_target_dir(Path::new(&manifest_dir));
+    resolve_target_dir(from_env.map(PathBuf::from), configured, &find_workspace_root())
+}
+
+/// Picks the target directory by cargo's precedence, see `find_target_dir`.
+///
+/// The cargo config is only read when the environment doesn't set the directory.
+fn resolve_target_dir(
+    from_env: Option<PathBuf>,
+    configured: impl FnOnce() -> Option<PathBuf>,
+    workspace_root: &Path,
+) -> PathBuf {
+    match from_env {
+        Some(dir) => workspace_root.join(dir),
+        None => configured().unwrap_or_else(|| workspace_root.join("target")),
+    }
+}
+
+/// The `build.target-dir` of the nearest `.cargo/config.toml` in `dir` or above it that sets one.
+///
+/// Like cargo, the legacy `.cargo/config` is preferred where both exist.
+///
+/// A relative one is resolved against the directory holding `.cargo`, as cargo does.
+fn configured_target_dir(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().find_map(|dir| {
+        ["config", "config.toml"].into_iter().find_map(|name| {
+            let raw = fs::read_to_string(dir.join(".cargo").join(name)).ok()?;
+            let config = raw.parse::<Value>().ok()?;
+            let target_dir = config.get("build")?.get("target-dir")?.as_str()?;
+            Some(dir.join(target_dir))
+        })
+    })
+}
+
+/// Determines if a path contains a workspace Cargo.toml file.
+///
+/// Checks if the file exists, can be read as TOML, and contains
+/// a `[workspace]` table. Virtual manifests (without a `[package]`) count as well.
+/// # Parameters
+/// - `path`: Path to a potential Cargo.toml file
+///
+/// # Returns
+/// `true` if the path exists, can be read, parsed as TOML, and has a workspace table.
+#[cold]
+pub fn is_workspace_root(path: &Path) -> bool {
+    manifest_has_table(path, "workspace")
+}
+
+/// Determines if a path contains a package Cargo.toml file.
+///
+/// Checks if the file exists, can be read as TOML, and contains a `[package]` table,
+/// which a virtual workspace manifest doesn't.
+/// # Parameters
+/// - `path`: Path to a potential Cargo.toml file
+///
+/// # Returns
+/// `true` if the path exists, can be read, parsed as TOML, and has a package table.
+#[cold]
+pub fn is_package_manifest(path: &Path) -> bool {
+    manifest_has_table(path, "package")
+}
+
+// whether the toml file at `path` has a top-level table `key`
+fn manifest_has_table(path: &Path, key: &str) -> bool {
+    if let Ok(content) = fs::read_to_string(path) {
+        if let Ok(toml) = content.parse::<Value>() {
+            return toml.get(key).is_some_and(Value::is_table);
+        }
+    }
+    false
+}
+
+/// Whether a source file is markdown, which is bound by its TOML front-matter, see
+/// `toml_front_matter`.
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+}
+
+/// Slices the TOML front-matter out of a markdown file, i.e. what's between the first two lines
+/// that are `+++`, like static site generators have it. The rest of the file is ignored.
+///
+/// The lines before the front-matter are kept as empty ones, so the line numbers of parse
+/// errors still point into the markdown file.
+///
+/// # Returns
+/// The front-matter, or `None` if the file has no `+++` fenced block.
+pub fn toml_front_matter(raw: &str) -> Option<String> {
+    let mut lines = raw.lines().enumerate().filter(|(_, line)| line.trim_end() == "+++");
+    let (open, _) = lines.next()?;
+    let (close, _) = lines.next()?;
+    let body = raw.lines().skip(open + 1).take(close - open - 1);
+    Some(std::iter::repeat("").take(open + 1).chain(body).collect::<Vec<_>>().join("\n"))
+}
+
+/// Converts a TOML key into a valid Rust identifier string.
+///
+/// Transformations applied:
+///
+/// 1. Strips surrounding quotes if present
+/// 2. Replaces dashes with underscores (kebab-case to snake_case)
+/// 3. Replaces other runs of characters that can't be in an identifier with an underscore,
+///    e.g. in target tables like `cfg(target_os = "linux")`, which becomes `cfg_target_os_linux`
+/// 4. Returns `ROOT` constant for empty input
+///
+/// # Parameters
+/// - `input`: Raw TOML key to normalize
+#[inline]
+pub fn to_valid_ident(input: &str) -> String {
+    // handle potentially somehow still quoted keys by removing quotes
+    let i = input.trim_start_matches('"').trim_end_matches('"');
+    // empty input handling
+    if i.is_empty() {
+        return ROOT.to_string(); // default name
+    }
+    let i = kebab_to_snake(i);
+    if i.chars().all(|c| c == '.' || c == '_' || c.is_alphanumeric()) {
+        return i;
+    }
+    i.split('.').map(sanitize_segment).collect::<Vec<_>>().join(".")
+}
+
+/// Checks whether a TOML key can be an identifier as is, casing aside: starts with a letter or
+/// an underscore, and has only letters, digits and underscores. Keywords are fine, since consts
+/// are uppercased and modules can be raw identifiers.
+pub fn is_ident_like(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c == '_' || c.is_alphabetic())
+        && chars.all(|c| c == '_' || c.is_alphanumeric())
+}
+
+// replaces each run of characters that can't be in an ident with an underscore, dropping them
+// at the ends, so e.g. `cfg(target_os = "linux")` reads as `cfg_target_os_linux`
+fn sanitize_segment(segment: &str) -> String {
+    segment
+        .split(|c: char| c != '_' && !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Turns a single TOML key into one segment of a dotted path.
+///
+/// Quoted keys may contain literal dots, e.g. `"foo.bar" = 1`, which would otherwise read as
+/// a nested path, so they are replaced with underscores (`foo_bar`).
+#[inline]
+pub fn key_segment(key: &str) -> String {
+    key.replace('.', "_")
+}
+
+/// Finds the key in `table` that normalizes to `segment`, e.g. `cache-dir` for `cache_dir`.
+pub fn find_key(table: &toml::map::Map<String, Value>, segment: &str) -> Option<String> {
+    table
+        .keys()
+        .find(|key| to_valid_ident(&key_segment(key)) == segment)
+        .cloned()
+}
+
+/// Resolves a normalized dotted path (as written in the macro) to the keys it has in the
+/// document, e.g. `app.cache_dir` to `["app", "cache-dir"]`.
+pub fn resolve_keys(value: &Value, path: &str) -> Option<Vec<String>> {
+    let mut keys = Vec::new();
+    let mut current = value;
+    for segment in path.split('.') {
+        let table = current.as_table()?;
+        let key = find_key(table, segment)?;
+        current = &table[&key];
+        keys.push(key);
+    }
+    Some(keys)
+}
+
+/// Gets the value at the given keys, see `resolve_keys`.
+pub fn get_at<'a>(value: &'a Value, keys: &[String]) -> Option<&'a Value> {
+    keys.iter()
+        .try_fold(value, |current, key| current.as_table()?.get(key))
+}
+
+/// Checks whether an array is an array of tables (`[[bin]]`), i.e. non-empty and only tables.
+///
+/// These are generated as modules with a submodule per element rather than as array consts.
+#[inline]
+pub fn is_table_array(arr: &[Value]) -> bool {
+    !arr.is_empty() && arr.iter().all(Value::is_table)
+}
+
+/// Deep-merges `overlay` into `base`, for layering several source files.
+///
+/// Tables are merged key by key recursively, and scalars in `overlay` replace what was in `base`.
+/// Arrays are merged by `arrays`, one of `options::MERGE_STRATEGIES`:
+/// - `replace` (also the default for `None`): the array in `overlay` replaces the one in `base`
+/// - `append`: the array in `overlay` is appended to the one in `base`
+/// - `union`: like `append`, but elements already in `base` are skipped, and so are duplicates
+///   within `overlay`
+pub fn merge_values(base: &mut Value, overlay: Value, arrays: Option<&str>) {
+    match (base, overlay) {
+        (Value::Table(base), Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value, arrays),
+                    None => {
+                        base.insert(key, value);
+                    },
+                }
+            }
+        },
+        (Value::Array(base), Value::Array(overlay)) if arrays == Some("append") => {
+            base.extend(overlay);
+        },
+        (Value::Array(base), Value::Array(overlay)) if arrays == Some("union") => {
+            for value in overlay {
+                if !base.contains(&value) {
+                    base.push(value);
+                }
+            }
+        },
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Converts a dotted TOML path into the `/`-separated form the pattern globs match against.
+///
+/// # Parameters
+/// - `path`: Dot-separated path, e.g. `deep.level1.value`
+///
+/// See `Pattern::to_glob` for why this is needed.
+#[inline]
+pub fn to_glob_path(path: &str) -> String {
+    path.replace('.', "/")
+}
+
+/// Replaces the last segment of a dotted TOML path with an alias, keeping it where it was.
+///
+/// # Parameters
+/// - `path`: Dot-separated path of the aliased key, e.g. `section.key`
+/// - `alias`: Name to give the key instead, e.g. `renamed`
+#[inline]
+pub fn rename_last_segment(path: &str, alias: &str) -> String {
+    match path.rsplit_once('.') {
+        Some((parent, _)) => format!("{}.{}", parent, alias),
+        None => format!(".{}", alias),
+    }
+}
+
+/// Parses a kebab-case name, such as an annotation or directive name, from the input.
+///
+/// Identifiers are parsed with `Ident::parse_any`, so keywords like `as` or `enum`
+/// are accepted as (parts of) names too.
+pub fn parse_kebab_name(input: ParseStream) -> SynResult<String> {
+    let mut name = Ident::parse_any(input)?.to_string();
+    while input.peek(Token![-]) {
+        input.parse::<Token![-]>()?;
+        name.push('-');
+        name.push_str(&Ident::parse_any(input)?.to_string());
+    }
+    Ok(name)
+}
+
+/// Converts kebab-case to snake_case by replacing all dashes with underscores.
+///
+/// # Parameters
+/// - `input`: String potentially containing dashes
+///
+/// Used for converting kebab-case to snake_case in toml keys.
+#[inline]
+pub fn kebab_to_snake(input: &str) -> String {
+    // TODO: more sophisticated conversion and covering edge cases that I assume have to exist
+    input.replace('-', "_")
+}
+
+/// Converts snake_case to kebab-case by replacing all underscores with dashes.
+///
+/// # Parameters
+/// - `input`: String potentially containing underscores
+///
+/// Used for converting snake_case to kebab-case in TOML keys.
+#[inline]
+pub fn snake_to_kebab(input: &str) -> String {
+    input.replace('_', "-")
+}
+
+/// Converts a TOML key into a snake_case name, like `to_valid_ident` does, also splitting the
+/// words of camelCase and PascalCase keys, e.g. `maxRetries` and `HTTPPort` into `max_retries`
+/// and `http_port`.
+///
+/// # Parameters
+/// - `input`: Raw TOML key to convert
+pub fn to_snake_case(input: &str) -> String {
+    let chars = to_valid_ident(input).chars().collect::<Vec<_>>();
+    let mut snake = String::with_capacity(chars.len());
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 && chars[i - 1] != '_' {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            // an acronym ends where the next word starts, `HTTPPort` as `http_port`
+            if prev.is_lowercase() || prev.is_numeric() || prev.is_uppercase() && next_lower {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+/// Creates the identifier of a generated fn, as a raw identifier (`r#type`) if the name is a
+/// keyword. The keywords that can't be raw identifiers, like `self`, get a trailing underscore
+/// instead.
+pub fn to_fn_ident(name: &str) -> Ident {
+    if syn::parse_str::<Ident>(name).is_ok() {
+        format_ident!("{}", name)
+    } else if matches!(name, "_" | "self" | "Self" | "super" | "crate") {
+        format_ident!("{}_", name)
+    } else {
+        Ident::new_raw(name, proc_macro2::Span::call_site())
+    }
+}
+
+/// Converts snake_case to PascalCase, e.g. for struct names generated from TOML keys.
+///
+/// # Parameters
+/// - `input`: String in snake_case (see `to_valid_ident`)
+#[inline]
+pub fn snake_to_pascal(input: &str) -> String {
+    input
+        .split('_')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let mut chars = s.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+// TODO: unit test for `snake_to_kebab`, but in practice unless we expand or add to this, it should do what it says on the tin
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::TomlField;
+    use std::f64::consts::PI;
+    use std::fs;
+    use tempfile::TempDir;
+    use toml::Value;
+
+    // NOTE: the escaped quotes for string values are expected, because they need to be
+    //       string literals in the generated code
+    // FIXME: make this make more sense, the above note alone tells me this smells
+    fn escaped(s: &str) -> String {
+        if s.starts_with('"') {
+            // already escaped
+            return s.to_string();
+        }
+        format!("\"{}\"", s)
+    }
+
+    #[test]
+    fn test_toml_front_matter() {
+        let raw = "+++\ntitle = \"post\"\n# the tags\ntags = [\"a\"]\n+++\n\n# Heading\n\n+++\n";
+        let front_matter = toml_front_matter(raw).unwrap();
+        assert_eq!(front_matter, "\ntitle = \"post\"\n# the tags\ntags = [\"a\"]");
+        let value = front_matter.parse::<Value>().unwrap();
+        assert_eq!(value.get("title").and_then(Value::as_str), Some("post"));
+        // the error is on the same line as in the markdown file
+        let err = toml_front_matter("intro\n+++\na = 1\nb =\n+++").unwrap().parse::<Value>();
+        assert!(err.unwrap_err().to_string().contains("line 4"));
+        assert_eq!(toml_front_matter("+++\na = 1\n"), None);
+        assert_eq!(toml_front_matter("# just markdown"), None);
+        assert!(is_markdown(Path::new("content/post.md")));
+        assert!(!is_markdown(Path::new("config.toml")));
+    }
+
+    #[test]
+    fn test_string_value_conversion() {
+        let value = Value::String("test".to_string());
+        let (ty, val) = convert_value_to_tokens(&value);
+        assert_eq!(ty.to_string(), "& 'static str");
+        assert_eq!(val.to_string(), "\"test\"");
+    }
+
+    #[test]
+    fn test_numeric_values() {
+        let int = Value::Integer(42);
+        let (ty, val) = convert_value_to_tokens(&int);
+        assert_eq!(ty.to_string(), "i64");
+        // numeric literal includes type suffix in output
+        assert_eq!(val.to_string(), "42i64");
+        let float = Value::Float(PI);
+        let (ty, val) = convert_value_to_tokens(&float);
+        assert_eq!(ty.to_string(), "f64");
+        assert!(val.to_string().starts_with("3.14"));
+    }
+
+    #[test]
+    fn test_float_literals() {
+        let float = |f: f64| convert_value_to_tokens(&Value::Float(f)).1.to_string();
+        assert_eq!(float(3.0), "3.0f64");
+        assert_eq!(float(1e10), "10000000000.0f64");
+        assert_eq!(float(1e300), "1e300f64");
+        assert_eq!(float(-2.5e-300), "- 2.5e-300f64");
+        assert_eq!(float(-0.0), "- 0.0f64");
+        // every float parses back to itself
+        for f in [1e300, f64::MAX, f64::MIN_POSITIVE, 5e-324, 0.1 + 0.2, -1.5] {
+            let lit = float(f.abs());
+            let parsed: f64 = lit.trim_end_matches("f64").parse().unwrap();
+            assert_eq!(parsed.to_bits(), f.abs().to_bits(), "{}", lit);
+        }
+        for f in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(float(f).contains("compile_error"), "{}", float(f));
+        }
+        // wherever they are
+        let floats = Value::Array(vec![Value::Float(1.0), Value::Float(f64::INFINITY)]);
+        let (_, val) = convert_value_to_tokens(&floats);
+        assert!(val.to_string().contains("compile_error"), "{}", val);
+    }
+
+    #[test]
+    fn test_narrowed_ints() {
+        let (ty, val) = convert_value_to_narrowed_tokens(&Value::Integer(42));
+        assert_eq!(ty.to_string(), "i8");
+        assert_eq!(val.to_string(), "42i8");
+        let (ty, val) = convert_value_to_narrowed_tokens(&Value::Integer(40000));
+        assert_eq!(ty.to_string(), "i32");
+        assert_eq!(val.to_string(), "40000i32");
+        let (ty, _) = convert_value_to_narrowed_tokens(&Value::Integer(-129));
+        assert_eq!(ty.to_string(), "i16");
+        let (ty, _) = convert_value_to_narrowed_tokens(&Value::Integer(i64::MAX));
+        assert_eq!(ty.to_string(), "i64");
+
+        let ints = Value::Array(vec![Value::Integer(1), Value::Integer(2), Value::Integer(300)]);
+        let (ty, val) = convert_value_to_narrowed_tokens(&ints);
+        assert_eq!(ty.to_string(), "& 'static [i16]");
+        assert_eq!(val.to_string(), "& [1i16 , 2i16 , 300i16]");
+
+        // anything but integers converts as usual
+        let (ty, _) = convert_value_to_narrowed_tokens(&Value::Float(PI));
+        assert_eq!(ty.to_string(), "f64");
+    }
+
+    #[test]
+    fn test_owned_tokens() {
+        let owned = |ty: &str| {
+            let (ty, val) = to_owned_tokens(&syn::parse_str(ty).unwrap(), quote! { X });
+            (ty.to_string(), val.to_string())
+        };
+        assert_eq!(
+            owned("&'static str"),
+            (
+                ":: std :: string :: String".to_string(),
+                ":: std :: string :: String :: from (X)".to_string()
+            )
+        );
+        let (ty, val) = owned("&'static [&'static str]");
+        assert_eq!(ty, ":: std :: vec :: Vec < :: std :: string :: String >");
+        assert!(val.starts_with("X . iter () . map"), "{}", val);
+        assert_eq!(owned("&'static [u8; 4]").0, ":: std :: vec :: Vec < u8 >");
+        assert_eq!(owned("i64"), ("i64".to_string(), "X".to_string()));
+    }
+
+    #[test]
+    fn test_serde_tokens() {
+        let serde = |ty: &str, val: &str| {
+            let (ty, val) =
+                to_serde_tokens(&syn::parse_str(ty).unwrap(), &syn::parse_str(val).unwrap());
+            (ty.to_string(), val.to_string())
+        };
+        let cow = |inner: &str| format!(":: std :: borrow :: Cow < 'static , {} >", inner);
+        let borrowed = |inner: &str| format!(":: std :: borrow :: Cow :: Borrowed ({})", inner);
+        assert_eq!(serde("&'static str", r#""a""#), (cow("str"), borrowed(r#""a""#)));
+        let (ty, val) = serde("&'static [(i64, &'static str)]", r#"&[(1, "a",)]"#);
+        assert_eq!(ty, cow(&format!("[(i64 , {} ,)]", cow("str"))));
+        assert_eq!(val, borrowed(&format!("& [(1 , {} ,)]", borrowed(r#""a""#))));
+        assert_eq!(serde("&'static [u8]", r#"b"ab""#).1, borrowed(r#"b"ab""#));
+        assert_eq!(serde("f64", "1.5"), ("f64".to_string(), "1.5".to_string()));
+    }
+
+    #[test]
+    fn test_boolean_value() {
+        let t = Value::Boolean(true);
+        let (ty, val) = convert_value_to_tokens(&t);
+        assert_eq!(ty.to_string(), "bool");
+        assert_eq!(val.to_string(), "true");
+
+        let f = Value::Boolean(false);
+        let (_, val) = convert_value_to_tokens(&f);
+        assert_eq!(val.to_string(), "false");
+    }
+
+    #[test]
+    fn test_datetime_value() {
+        // parse a toml string containing a datetime to get a Value::Datetime
+        // TODO: see if we can somehow, from somewhere, import and directly use the toml_datetime::DateTime...?
+        let toml_str = r#"date = 2023-01-01T12:00:00Z"#;
+        let parsed: toml::Value = toml_str.parse().unwrap();
+        let date_value = parsed.get("date").unwrap();
+
+        // extract type and value tokens
+        let (ty, val) = convert_value_to_tokens(date_value);
+
+        // assert that we get a string type (per the implementation)
+        assert_eq!(ty.to_string(), "& 'static str");
+
+        // assert the value contains the date string (exact format may vary)
+        assert!(val.to_string().contains("2023-01-01"));
+        assert!(val.to_string().contains("12:00:00"));
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        let formatted = |toml_str: &str| {
+            let parsed: Value = format!("dt = {}", toml_str).parse().unwrap();
+            match parsed.get("dt") {
+                Some(Value::Datetime(dt)) => rfc3339(dt),
+                other => panic!("Expected a datetime, got {:?}", other),
+            }
+        };
+        // offset datetimes
+        assert_eq!(formatted("1979-05-27 07:32:00Z"), "1979-05-27T07:32:00Z");
+        assert_eq!(formatted("1979-05-27t07:32:00+00:00"), "1979-05-27T07:32:00Z");
+        assert_eq!(formatted("1979-05-27T00:32:00.500-07:30"), "1979-05-27T00:32:00.5-07:30");
+        // local datetime, date and time
+        assert_eq!(formatted("1979-05-27 07:32:00"), "1979-05-27T07:32:00");
+        assert_eq!(formatted("1979-05-27"), "1979-05-27");
+        assert_eq!(formatted("07:32:00.999000"), "07:32:00.999");
+    }
+
+    #[test]
+    fn test_homogeneous_array() {
+        let strings = Value::Array(vec![Value::String("a".into()), Value::String("b".into())]);
+        let (ty, val) = convert_value_to_tokens(&strings);
+        assert_eq!(ty.to_string(), "& 'static [& 'static str]");
+        assert!(val.to_string().contains("\"a\""));
+        assert!(val.to_string().contains("\"b\""));
+    }
+
+    #[test]
+    fn test_empty_array() {
+        let empty = Value::Array(vec![]);
+        let (ty, val) = convert_value_to_tokens(&empty);
+        assert_eq!(ty.to_string(), "& 'static [& 'static str]");
+        assert_eq!(val.to_string(), "& []"); // this is proper form because tokens display with space delims
+    }
+
+    #[test]
+    fn test_mixed_array_tuples() {
+        let pair = Value::Array(vec![Value::Integer(8080), Value::String("http".into())]);
+        let (ty, val) = convert_value_to_tokens(&pair);
+        assert_eq!(ty.to_string(), "(i64 , & 'static str ,)");
+        assert_eq!(val.to_string(), "(8080i64 , \"http\" ,)");
+
+        let point = Value::Array(vec![
+            Value::Float(1.0),
+            Value::Float(2.0),
+            Value::String("label".into()),
+        ]);
+        let (ty, _) = convert_value_to_tokens(&point);
+        assert_eq!(ty.to_string(), "(f64 , f64 , & 'static str ,)");
+    }
+
+    #[test]
+    fn test_mixed_array_fallback() {
+        // nested arrays can't be tuple elements, so this stays a string
+        let mixed = Value::Array(vec![
+            Value::String("a".into()),
+            Value::Array(vec![Value::Integer(1)]),
+        ]);
+        let (ty, val) = convert_value_to_tokens(&mixed);
+        assert_eq!(ty.to_string(), "& 'static str");
+        // debug representation of mixed array
+        // NOTE: when a str value converts to a token, it gets escaped on display
+        //       unsure whether or not this should be thus, or we should make it more sensible?
+        let pat = format!("[String(\\\"{}\\\"), Array([Integer({})])]", "a", 1);
+        assert!(
+            val.to_string().contains(&pat),
+            "{}, should contain: {}",
+            val,
+            pat
+        );
+    }
+
+    #[test]
+    fn test_strict_conversion() {
+        let mixed = Value::Array(vec![
+            Value::String("a".into()),
+            Value::Array(vec![Value::Integer(1)]),
+        ]);
+        let err = try_convert_value_to_tokens(&mixed).unwrap_err();
+        assert_eq!(err, "an array of mixed types that can't be a tuple");
+        // nested in an otherwise typed array, it's an error all the same
+        let nested = Value::Array(vec![mixed.clone(), mixed]);
+        assert!(try_convert_value_to_tokens(&nested).is_err());
+        let pair = Value::Array(vec![Value::Integer(8080), Value::String("http".into())]);
+        let (ty, _) = try_convert_value_to_tokens(&pair).unwrap();
+        assert_eq!(ty.to_string(), "(i64 , & 'static str ,)");
+    }
+
+    #[test]
+    fn test_value_to_string_token() {
+        let str_val = Value::String("hello".into());
+        assert_eq!(
+            value_to_string_token(&str_val).to_string(),
+            escaped("hello")
+        );
+
+        let int_val = Value::Integer(42);
+        // non-string values get quoted in string token output
+        assert_eq!(value_to_string_token(&int_val).to_string(), escaped("42"));
+        let bool_val = Value::Boolean(true);
+        assert_eq!(
+            value_to_string_token(&bool_val).to_string(),
+            escaped("true")
+        );
+    }
+
+    #[test]
+    fn test_get_doc_comment_empty() {
+        let field = TomlField::default();
+        assert_eq!(get_doc_comment(&field).to_string(), "");
+    }
+
+    #[test]
+    fn test_get_doc_comment_with_escaping() {
+        let field = TomlField {
+            comment: Some("with `code` and 'quotes'".into()),
+            ..Default::default()
+        };
+        let doc = get_doc_comment(&field).to_string();
+        // assert!(doc.contains("with \\`code\\` and \\'quotes"));
+        // verify doc comment contains the basic content
+        assert!(doc.contains("with"));
+        assert!(doc.contains("code"));
+        assert!(doc.contains("quotes"));
+    }
+
+    #[test]
+    fn test_get_doc_comment_paragraphs() {
+        let doc = |comment: &str| {
+            let field = TomlField {
+                comment: Some(comment.into()),
+                ..Default::default()
+            };
+            get_doc_comment(&field).to_string()
+        };
+        assert_eq!(
+            doc("first paragraph\ncontinued\n\nsecond paragraph"),
+            "# [doc = \"first paragraph\\ncontinued\\n\\nsecond paragraph\"]"
+        );
+        // runs of blank lines are one break, and ones around the text are dropped
+        assert_eq!(doc("\nfirst\n\n \n\nsecond\n"), doc("first\n\nsecond"));
+        assert_eq!(doc("just one line"), "# [doc = \"just one line\"]");
+        assert_eq!(doc("\n\n"), "");
+    }
+
+    #[test]
+    fn test_get_doc_comment_elements() {
+        let value = Value::Array(vec!["a".into(), "b".into(), "c".into()]);
+        let field = TomlField {
+            value,
+            comment: Some("the letters".into()),
+            element_comments: vec![(0, "first".into()), (2, "last\nof them".into())],
+            ..Default::default()
+        };
+        let lit = LitStr::new(
+            "the letters\n\n- `\"a\"`: first\n- `\"c\"`: last\n  of them",
+            proc_macro2::Span::call_site(),
+        );
+        assert_eq!(get_doc_comment(&field).to_string(), quote!(#[doc = #lit]).to_string());
+    }
+
+    #[test]
+    fn test_find_workspace_root_setup() -> Result<(), Box<dyn std::error::Error>> {
+        // create a temporary directory structure
+        let temp = TempDir::new()?;
+        let root = temp.path().to_path_buf();
+
+        // create workspace structure
+        let ws_root = root.join("workspace");
+        let project = ws_root.join("project");
+        fs::create_dir_all(&project)?;
+
+        // create workspace Cargo.toml
+        fs::write(
+            ws_root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"project\"]",
+        )?;
+
+        // create project Cargo.toml
+        fs::write(project.join("Cargo.toml"), "[package]\nname = \"test\"")?;
+
+        // should find workspace root from the project's manifest dir
+        let found = workspace_root_of(&project);
+        assert_eq!(found, ws_root);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_workspace_root() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = TempDir::new()?;
+        let ws_toml = temp.path().join("Cargo.toml");
+        let pkg_toml = temp.path().join("pkg").join("Cargo.toml");
+
+        fs::create_dir_all(temp.path().join("pkg"))?;
+        fs::write(&ws_toml, "[workspace]\nmembers = [\"pkg\"]")?;
+        fs::write(&pkg_toml, "[package]\nname = \"pkg\"")?;
+
+        assert!(is_workspace_root(&ws_toml));
+        assert!(!is_workspace_root(&pkg_toml));
+        assert!(!is_workspace_root(&temp.path().join("nonexistent.toml")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_virtual_manifest() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = TempDir::new()?;
+        let ws_toml = temp.path().join("Cargo.toml");
+        let member = temp.path().join("member");
+        fs::create_dir_all(&member)?;
+        // a virtual manifest, with a workspace but no package of its own
+        fs::write(&ws_toml, "[workspace]\nmembers = [\"member\"]\nresolver = \"2\"")?;
+        fs::write(member.join("Cargo.toml"), "[package]\nname = \"member\"")?;
+
+        assert!(is_workspace_root(&ws_toml));
+        assert!(!is_package_manifest(&ws_toml));
+        assert!(is_package_manifest(&member.join("Cargo.toml")));
+        assert!(!is_workspace_root(&member.join("Cargo.toml")));
+
+        assert_eq!(workspace_root_of(&member), temp.path());
+        // the workspace manifest `workspace!` binds is noted in docs relative to the member
+        let noted = relative_to_dir(&ws_toml.to_string_lossy(), &member);
+        let own = relative_to_dir(&member.join("Cargo.toml").to_string_lossy(), &member);
+        assert_eq!(noted, "../Cargo.toml");
+        assert_eq!(own, "Cargo.toml");
+        assert_eq!(relative_to_dir("tests/test.toml", &member), "tests/test.toml");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_configured_target_dir() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = TempDir::new()?;
+        let member = temp.path().join("member");
+        fs::create_dir_all(member.join(".cargo"))?;
+        fs::create_dir_all(temp.path().join(".cargo"))?;
+
+        // a config that doesn't set it leaves it to the ones further up
+        fs::write(member.join(".cargo/config.toml"), "[build]\njobs = 2")?;
+        fs::write(temp.path().join(".cargo/config.toml"), "[build]\ntarget-dir = \"out\"")?;
+        assert_eq!(configured_target_dir(&member), Some(temp.path().join("out")));
+
+        // the nearest one wins, relative to the directory that holds its `.cargo`
+        fs::write(member.join(".cargo/config.toml"), "[build]\ntarget-dir = \"../shared\"")?;
+        assert_eq!(configured_target_dir(&member), Some(member.join("../shared")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_target_dir() {
+        let root = Path::new("/workspace");
+        let configured = || Some(PathBuf::from("/configured"));
+        // the environment wins over the config, relative to the workspace root
+        let env_dir = Some(PathBuf::from("out"));
+        assert_eq!(resolve_target_dir(env_dir, configured, root), root.join("out"));
+        let env_dir = Some(PathBuf::from("/elsewhere"));
+        assert_eq!(resolve_target_dir(env_dir, configured, root), Path::new("/elsewhere"));
+        assert_eq!(resolve_target_dir(None, configured, root), Path::new("/configured"));
+        // cargo's default without either
+        assert_eq!(resolve_target_dir(None, || None, root), root.join("target"));
+    }
+
+    #[test]
+    fn test_to_valid_ident() {
+        assert_eq!(to_valid_ident(""), ROOT.to_string());
+        assert_eq!(to_valid_ident("normal"), "normal");
+        assert_eq!(to_valid_ident("with-dash"), "with_dash");
+        assert_eq!(to_valid_ident("\"quoted\""), "quoted");
+        assert_eq!(to_valid_ident("\"quoted-with-dash\""), "quoted_with_dash");
+        assert_eq!(to_valid_ident("target.cfg(unix)"), "target.cfg_unix");
+        assert_eq!(
+            to_valid_ident("cfg(all(target_os = \"linux\", not(target_env = \"musl\")))"),
+            "cfg_all_target_os_linux_not_target_env_musl"
+        );
+        assert_eq!(to_valid_ident("x86_64-pc-windows-gnu"), "x86_64_pc_windows_gnu");
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("max_retries"), "max_retries");
+        assert_eq!(to_snake_case("maxRetries"), "max_retries");
+        assert_eq!(to_snake_case("MaxRetries"), "max_retries");
+        assert_eq!(to_snake_case("HTTPPort"), "http_port");
+        assert_eq!(to_snake_case("UPPER"), "upper");
+        assert_eq!(to_snake_case("with-dash"), "with_dash");
+        assert_eq!(to_snake_case("v2Api"), "v2_api");
+    }
+
+    #[test]
+    fn test_to_fn_ident() {
+        assert_eq!(to_fn_ident("port").to_string(), "port");
+        assert_eq!(to_fn_ident("type").to_string(), "r#type");
+        assert_eq!(to_fn_ident("match").to_string(), "r#match");
+        assert_eq!(to_fn_ident("self").to_string(), "self_");
+    }
+
+    #[test]
+    fn test_is_ident_like() {
+        assert!(is_ident_like("with_underscore"));
+        assert!(is_ident_like("_private"));
+        assert!(is_ident_like("type"));
+        assert!(!is_ident_like("with-dash"));
+        assert!(!is_ident_like("1st"));
+        assert!(!is_ident_like(""));
+    }
+
+    #[test]
+    fn test_to_glob_path() {
+        assert_eq!(to_glob_path(""), "");
+        assert_eq!(to_glob_path("key"), "key");
+        assert_eq!(to_glob_path("deep.level1.value"), "deep/level1/value");
+    }
+
+    #[test]
+    fn test_merge_values() {
+        let mut base: Value = "a = 1\nlist = [1, 2]\n[t]\nx = 1\ny = 2\n[t.deep]\nz = 3"
+            .parse()
+            .unwrap();
+        let overlay: Value = "list = [3]\nb = 2\n[t]\ny = 20\n[t.deep]\nw = 4".parse().unwrap();
+        merge_values(&mut base, overlay, None);
+        assert_eq!(base["a"].as_integer(), Some(1));
+        assert_eq!(base["b"].as_integer(), Some(2));
+        // arrays are replaced, not appended to
+        assert_eq!(base["list"].as_array().unwrap().len(), 1);
+        assert_eq!(base["t"]["x"].as_integer(), Some(1));
+        assert_eq!(base["t"]["y"].as_integer(), Some(20));
+        assert_eq!(base["t"]["deep"]["z"].as_integer(), Some(3));
+        assert_eq!(base["t"]["deep"]["w"].as_integer(), Some(4));
+
+        // a table replaces a scalar and vice versa
+        let mut base: Value = "a = 1\n[b]\nx = 1".parse().unwrap();
+        merge_values(&mut base, "b = 2\n[a]\nx = 1".parse().unwrap(), None);
+        assert!(base["a"].is_table());
+        assert_eq!(base["b"].as_integer(), Some(2));
+    }
+
+    #[test]
+    fn test_merge_arrays() {
+        let merged = |strategy: &str| {
+            let mut base: Value = "list = [1, 2]\n[t]\nlist = [1]".parse().unwrap();
+            let overlay: Value = "list = [2, 3, 3]\n[t]\nlist = [4]".parse().unwrap();
+            merge_values(&mut base, overlay, Some(strategy));
+            base
+        };
+        let ints = |value: &Value| {
+            value
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| v.as_integer().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ints(&merged("replace")["list"]), [2, 3, 3]);
+        assert_eq!(ints(&merged("append")["list"]), [1, 2, 2, 3, 3]);
+        assert_eq!(ints(&merged("union")["list"]), [1, 2, 3]);
+        // nested arrays follow the same strategy
+        assert_eq!(ints(&merged("append")["t"]["list"]), [1, 4]);
+    }
+
+    #[test]
+    fn test_is_table_array() {
+        let value: Value = "a = [{ x = 1 }, { y = 2 }]\nb = [1, 2]\nc = []\nd = [{ x = 1 }, 2]"
+            .parse()
+            .unwrap();
+        assert!(is_table_array(value["a"].as_array().unwrap()));
+        assert!(!is_table_array(value["b"].as_array().unwrap()));
+        assert!(!is_table_array(value["c"].as_array().unwrap()));
+        assert!(!is_table_array(value["d"].as_array().unwrap()));
+    }
+
+    #[test]
+    fn test_snake_to_pascal() {
+        assert_eq!(snake_to_pascal("network"), "Network");
+        assert_eq!(snake_to_pascal("retry_policy"), "RetryPolicy");
+        assert_eq!(snake_to_pascal("level1"), "Level1");
+        assert_eq!(snake_to_pascal("_leading__double"), "LeadingDouble");
+    }
+
+    #[test]
+    fn test_fix_dashes() {
+        assert_eq!(kebab_to_snake("no-dashes-here"), "no_dashes_here");
+        assert_eq!(kebab_to_snake("already_good"), "already_good");
+        assert_eq!(kebab_to_snake("mixed-case_style"), "mixed_case_style");
+        assert_eq!(kebab_to_snake("-leading-dash"), "_leading_dash");
+        assert_eq!(kebab_to_snake("trailing-dash-"), "trailing_dash_");
+    }
+}
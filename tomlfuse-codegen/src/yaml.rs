@@ -0,0 +1,118 @@
+//------------------------------------------------------------------------------
+// Copyright (c) 2025                 orgrinrt           orgrinrt@ikiuni.dev
+//                                    Hiisi Digital Oy   contact@hiisi.digital
+// SPDX-License-Identifier: MPL-2.0    O. R. Toimela      N2963@student.jamk.fi
+//------------------------------------------------------------------------------
+
+use toml::Value;
+
+/// Parses a yaml document into the TOML value tree the rest of the pipeline works with.
+///
+/// Anchors and aliases are resolved by the parser, and `<<` merge keys are applied before
+/// converting. Files with more than one document are rejected, since it's not obvious which
+/// one should be bound. An empty document is treated as an empty table.
+pub fn parse_yaml(content: &str) -> Result<Value, serde_yaml::Error> {
+    if content.trim().is_empty() {
+        return Ok(Value::Table(Default::default()));
+    }
+    let mut yaml: serde_yaml::Value = serde_yaml::from_str(content)?;
+    yaml.apply_merge()?;
+    Ok(yaml_to_toml(yaml).unwrap_or(Value::Table(Default::default())))
+}
+
+/// Converts a yaml value into the closest TOML value.
+///
+/// - `null` has no TOML counterpart, so it's dropped (from mappings and sequences alike)
+/// - Numbers become integers when they fit in an `i64`, and floats otherwise
+/// - Mapping keys that are booleans or numbers are stringified, other non-string keys are dropped
+/// - Tags are ignored and the tagged value is converted as is
+///
+/// # Returns
+/// `None` only for `null`.
+pub fn yaml_to_toml(yaml: serde_yaml::Value) -> Option<Value> {
+    use serde_yaml::Value as Yaml;
+    Some(match yaml {
+        Yaml::Null => return None,
+        Yaml::Bool(b) => Value::Boolean(b),
+        Yaml::Number(n) => match n.as_i64() {
+            Some(int) => Value::Integer(int),
+            None => Value::Float(n.as_f64()?),
+        },
+        Yaml::String(s) => Value::String(s),
+        Yaml::Sequence(seq) => Value::Array(seq.into_iter().filter_map(yaml_to_toml).collect()),
+        Yaml::Mapping(map) => Value::Table(
+            map.into_iter()
+                .filter_map(|(key, value)| {
+                    let key = match key {
+                        Yaml::String(s) => s,
+                        Yaml::Bool(b) => b.to_string(),
+                        Yaml::Number(n) => n.to_string(),
+                        _ => return None,
+                    };
+                    Some((key, yaml_to_toml(value)?))
+                })
+                .collect(),
+        ),
+        Yaml::Tagged(tagged) => return yaml_to_toml(tagged.value),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yaml_to_toml() {
+        let value = parse_yaml(
+            r#"
+name: app
+port: 8080
+ratio: 0.5
+enabled: true
+missing: ~
+tags: [a, null, b]
+404: not found
+nested:
+  inner:
+    value: 1
+"#,
+        )
+        .unwrap();
+        assert_eq!(value["name"].as_str(), Some("app"));
+        assert_eq!(value["port"].as_integer(), Some(8080));
+        assert_eq!(value["ratio"].as_float(), Some(0.5));
+        assert_eq!(value["enabled"].as_bool(), Some(true));
+        assert!(value.get("missing").is_none());
+        assert_eq!(value["tags"].as_array().unwrap().len(), 2);
+        assert_eq!(value["404"].as_str(), Some("not found"));
+        assert_eq!(value["nested"]["inner"]["value"].as_integer(), Some(1));
+    }
+
+    #[test]
+    fn test_yaml_anchors() {
+        let value = parse_yaml(
+            r#"
+base: &base
+  retries: 3
+  timeout: 10
+copy: *base
+merged:
+  <<: *base
+  timeout: 20
+"#,
+        )
+        .unwrap();
+        assert_eq!(value["copy"]["retries"].as_integer(), Some(3));
+        assert_eq!(value["merged"]["retries"].as_integer(), Some(3));
+        // keys set next to the merge key win over the merged ones
+        assert_eq!(value["merged"]["timeout"].as_integer(), Some(20));
+        assert!(value["merged"].get("<<").is_none());
+    }
+
+    #[test]
+    fn test_yaml_documents() {
+        assert_eq!(parse_yaml("  ").unwrap(), Value::Table(Default::default()));
+        assert!(parse_yaml("a: 1\n---\nb: 2\n").is_err());
+        assert!(parse_yaml("a: [").is_err());
+    }
+}